@@ -0,0 +1,5 @@
+-- Add per-contest scoring formula selection
+-- Existing contests keep the Reshel x McCullough scoring they were created with
+-- Allowed values are validated by the application (ScoringFormula enum)
+
+ALTER TABLE contests ADD COLUMN scoring_formula TEXT NOT NULL DEFAULT 'ReshelMcCullough';
@@ -0,0 +1,5 @@
+-- Coefficient of the contest's scoring formula, computed at registration time
+ALTER TABLE registrations ADD COLUMN scoring_coefficient REAL;
+
+-- Existing registrations were scored with Reshel
+UPDATE registrations SET scoring_coefficient = reshel_coefficient;
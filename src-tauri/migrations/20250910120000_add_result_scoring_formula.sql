@@ -0,0 +1,11 @@
+-- Registration coefficients now hold the full multiplier of the contest's formula,
+-- so Reshel/McCullough registrations fold the age factor in
+UPDATE registrations
+SET scoring_coefficient = scoring_coefficient * COALESCE(mccullough_coefficient, 1.0)
+WHERE contest_id IN (SELECT id FROM contests WHERE scoring_formula = 'ReshelMcCullough');
+
+-- Record which formula produced each result's points
+ALTER TABLE results ADD COLUMN scoring_formula TEXT NOT NULL DEFAULT 'ReshelMcCullough';
+
+UPDATE results
+SET scoring_formula = (SELECT scoring_formula FROM contests WHERE contests.id = results.contest_id);
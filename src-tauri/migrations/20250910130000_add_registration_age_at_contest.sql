@@ -0,0 +1,10 @@
+-- Age in full years on contest day that the McCullough factor was computed from
+ALTER TABLE registrations ADD COLUMN age_at_contest INTEGER;
+
+UPDATE registrations
+SET age_at_contest = (
+    SELECT CAST(strftime('%Y', ct.date) AS INTEGER) - CAST(strftime('%Y', c.birth_date) AS INTEGER)
+        - (strftime('%m-%d', ct.date) < strftime('%m-%d', c.birth_date))
+    FROM competitors c, contests ct
+    WHERE c.id = registrations.competitor_id AND ct.id = registrations.contest_id
+);
@@ -0,0 +1,41 @@
+-- Federation profiles: weight classes and age categories are grouped into sets named
+-- after the profile that defines them, and each contest records the profile it runs under.
+-- SQLite cannot change a UNIQUE constraint in place, so both category tables are rebuilt.
+-- Foreign key checks are deferred to commit; the recreated rows satisfy the registrations
+-- that reference them again before then.
+PRAGMA defer_foreign_keys = ON;
+
+CREATE TABLE weight_classes_backup AS SELECT * FROM weight_classes;
+DROP TABLE weight_classes;
+
+CREATE TABLE weight_classes (
+    id TEXT PRIMARY KEY,
+    class_set TEXT NOT NULL DEFAULT 'PZKFiTS', -- Federation profile the class belongs to
+    gender TEXT NOT NULL CHECK(gender IN ('Male','Female')),
+    name TEXT NOT NULL, -- e.g., "DO 75 KG", "+ 140 KG"
+    weight_min REAL, -- Minimum weight in kg (NULL for open class)
+    weight_max REAL, -- Maximum weight in kg (NULL for open class)
+    UNIQUE(class_set, gender, name)
+);
+
+INSERT INTO weight_classes (id, class_set, gender, name, weight_min, weight_max)
+SELECT id, 'PZKFiTS', gender, name, weight_min, weight_max FROM weight_classes_backup;
+DROP TABLE weight_classes_backup;
+
+CREATE TABLE age_categories_backup AS SELECT * FROM age_categories;
+DROP TABLE age_categories;
+
+CREATE TABLE age_categories (
+    id TEXT PRIMARY KEY,
+    category_set TEXT NOT NULL DEFAULT 'PZKFiTS', -- Federation profile the category belongs to
+    name TEXT NOT NULL,
+    min_age INTEGER, -- Minimum age (NULL for no limit)
+    max_age INTEGER, -- Maximum age (NULL for no limit)
+    UNIQUE(category_set, name)
+);
+
+INSERT INTO age_categories (id, category_set, name, min_age, max_age)
+SELECT id, 'PZKFiTS', name, min_age, max_age FROM age_categories_backup;
+DROP TABLE age_categories_backup;
+
+ALTER TABLE contests ADD COLUMN federation_profile TEXT NOT NULL DEFAULT 'PZKFiTS';
@@ -0,0 +1,24 @@
+-- Weight classes are assigned from a class set selected per contest. Contests start with
+-- the set of their federation profile; the IPF 2019+ classes ship so they can be selected
+-- without an IPF profile contest having been created first.
+ALTER TABLE contests ADD COLUMN weight_class_set TEXT NOT NULL DEFAULT 'PZKFiTS';
+
+UPDATE contests SET weight_class_set = federation_profile;
+
+INSERT OR IGNORE INTO weight_classes (id, class_set, gender, name, weight_min, weight_max) VALUES
+    ('IPF_M_59', 'IPF', 'Male', '59 kg', NULL, 59.0),
+    ('IPF_M_66', 'IPF', 'Male', '66 kg', 59.01, 66.0),
+    ('IPF_M_74', 'IPF', 'Male', '74 kg', 66.01, 74.0),
+    ('IPF_M_83', 'IPF', 'Male', '83 kg', 74.01, 83.0),
+    ('IPF_M_93', 'IPF', 'Male', '93 kg', 83.01, 93.0),
+    ('IPF_M_105', 'IPF', 'Male', '105 kg', 93.01, 105.0),
+    ('IPF_M_120', 'IPF', 'Male', '120 kg', 105.01, 120.0),
+    ('IPF_M_120_PLUS', 'IPF', 'Male', '120+ kg', 120.01, NULL),
+    ('IPF_F_47', 'IPF', 'Female', '47 kg', NULL, 47.0),
+    ('IPF_F_52', 'IPF', 'Female', '52 kg', 47.01, 52.0),
+    ('IPF_F_57', 'IPF', 'Female', '57 kg', 52.01, 57.0),
+    ('IPF_F_63', 'IPF', 'Female', '63 kg', 57.01, 63.0),
+    ('IPF_F_69', 'IPF', 'Female', '69 kg', 63.01, 69.0),
+    ('IPF_F_76', 'IPF', 'Female', '76 kg', 69.01, 76.0),
+    ('IPF_F_84', 'IPF', 'Female', '84 kg', 76.01, 84.0),
+    ('IPF_F_84_PLUS', 'IPF', 'Female', '84+ kg', 84.01, NULL);
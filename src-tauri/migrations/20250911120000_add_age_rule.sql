@@ -0,0 +1,8 @@
+-- Age categories are assigned either by the exact age on the contest date or by the age
+-- reached during the contest year. Contests select the rule; registrations record the rule
+-- their age category was assigned by. Existing categories were assigned by exact age.
+ALTER TABLE contests ADD COLUMN age_rule TEXT NOT NULL DEFAULT 'ExactDate';
+
+ALTER TABLE registrations ADD COLUMN age_rule TEXT;
+
+UPDATE registrations SET age_rule = 'ExactDate';
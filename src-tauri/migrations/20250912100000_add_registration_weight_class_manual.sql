@@ -0,0 +1,3 @@
+-- Registrations remember whether the weight class was chosen by hand, so that recalculation
+-- only reassigns classes it assigned itself. Existing classes are treated as auto-assigned.
+ALTER TABLE registrations ADD COLUMN weight_class_manual BOOLEAN NOT NULL DEFAULT 0;
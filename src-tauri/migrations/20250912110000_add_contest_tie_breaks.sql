@@ -0,0 +1,3 @@
+-- Tied lifters are separated by a per-contest chain of tie-breaks, stored as a
+-- comma-separated list applied in order. An empty list lets tied lifters share a place.
+ALTER TABLE contests ADD COLUMN tie_breaks TEXT NOT NULL DEFAULT 'Bodyweight,AchievedAt,LotNumber';
@@ -0,0 +1,3 @@
+-- Lifters without a successful attempt in a lift their discipline requires have no total.
+-- They keep their per-lift bests but are excluded from placings.
+ALTER TABLE results ADD COLUMN is_bombed_out BOOLEAN NOT NULL DEFAULT 0;
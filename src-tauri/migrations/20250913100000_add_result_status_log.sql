@@ -0,0 +1,17 @@
+-- Disqualifications and "did not finish" are set on results by officials, with a reason and
+-- the time of the decision. Every decision, including reinstatements, is logged for review.
+ALTER TABLE results ADD COLUMN did_not_finish BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE results ADD COLUMN status_changed_at TEXT;
+
+CREATE TABLE result_status_log (
+    id TEXT PRIMARY KEY,
+    registration_id TEXT NOT NULL,
+    contest_id TEXT NOT NULL,
+    action TEXT NOT NULL CHECK(action IN ('Disqualify','DidNotFinish','Reinstate')),
+    reason TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (registration_id) REFERENCES registrations(id) ON DELETE CASCADE,
+    FOREIGN KEY (contest_id) REFERENCES contests(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_result_status_log_contest ON result_status_log(contest_id);
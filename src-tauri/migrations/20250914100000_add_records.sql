@@ -0,0 +1,29 @@
+-- Records registry. A record is held per scope, sex, weight class, age category, equipment
+-- and lift (or total); the current record is the heaviest entry for that combination, older
+-- entries are kept as history. Club and regional records carry the club or region name.
+-- Records broken in a contest reference the attempt that set them.
+CREATE TABLE records (
+    id TEXT PRIMARY KEY,
+    scope TEXT NOT NULL CHECK(scope IN ('Club','Regional','National')),
+    scope_name TEXT, -- Club or region; NULL for national records
+    gender TEXT NOT NULL CHECK(gender IN ('Male','Female')),
+    weight_class_id TEXT NOT NULL,
+    age_category_id TEXT NOT NULL,
+    equipment TEXT NOT NULL CHECK(equipment IN ('Raw','SinglePly','MultiPly')),
+    lift TEXT NOT NULL CHECK(lift IN ('Squat','Bench','Deadlift','Total')),
+    weight REAL NOT NULL CHECK(weight > 0),
+    holder_name TEXT NOT NULL,
+    set_on TEXT NOT NULL, -- YYYY-MM-DD
+    competitor_id TEXT,
+    contest_id TEXT,
+    attempt_id TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (weight_class_id) REFERENCES weight_classes(id),
+    FOREIGN KEY (age_category_id) REFERENCES age_categories(id),
+    FOREIGN KEY (competitor_id) REFERENCES competitors(id) ON DELETE SET NULL,
+    FOREIGN KEY (contest_id) REFERENCES contests(id) ON DELETE SET NULL,
+    FOREIGN KEY (attempt_id) REFERENCES attempts(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_records_lookup ON records(scope, gender, weight_class_id, age_category_id, equipment, lift);
+CREATE INDEX idx_records_attempt ON records(attempt_id);
@@ -0,0 +1,5 @@
+-- 4th attempts are record attempts: they do not count towards the best lifts or the total,
+-- so a successful one is kept apart on the result.
+ALTER TABLE results ADD COLUMN record_attempt_squat REAL;
+ALTER TABLE results ADD COLUMN record_attempt_bench REAL;
+ALTER TABLE results ADD COLUMN record_attempt_deadlift REAL;
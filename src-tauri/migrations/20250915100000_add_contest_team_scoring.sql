@@ -0,0 +1,4 @@
+-- Team classification: a club scores the place points its best lifters earned in their
+-- weight classes. The contest sets how many lifters count and the points per place.
+ALTER TABLE contests ADD COLUMN team_top_n INTEGER NOT NULL DEFAULT 5 CHECK(team_top_n > 0);
+ALTER TABLE contests ADD COLUMN team_place_points TEXT NOT NULL DEFAULT '12,9,8,7,6,5,4,3,2,1';
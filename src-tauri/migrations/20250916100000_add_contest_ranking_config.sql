@@ -0,0 +1,9 @@
+-- Per-contest ranking configuration. Each of the open, age class and weight class rankings
+-- is decided by total or coefficient points and held separately per the comma-separated
+-- list of splits (Sex, Equipment, AgeCategory).
+ALTER TABLE contests ADD COLUMN open_ranking_basis TEXT NOT NULL DEFAULT 'Points' CHECK(open_ranking_basis IN ('Total','Points'));
+ALTER TABLE contests ADD COLUMN open_ranking_split TEXT NOT NULL DEFAULT 'Sex,Equipment';
+ALTER TABLE contests ADD COLUMN age_class_ranking_basis TEXT NOT NULL DEFAULT 'Points' CHECK(age_class_ranking_basis IN ('Total','Points'));
+ALTER TABLE contests ADD COLUMN age_class_ranking_split TEXT NOT NULL DEFAULT 'Sex,Equipment';
+ALTER TABLE contests ADD COLUMN weight_class_ranking_basis TEXT NOT NULL DEFAULT 'Total' CHECK(weight_class_ranking_basis IN ('Total','Points'));
+ALTER TABLE contests ADD COLUMN weight_class_ranking_split TEXT NOT NULL DEFAULT 'Sex,Equipment';
@@ -0,0 +1,30 @@
+-- Named divisions of a contest, e.g. "Junior -74 kg". A division ranks the lifters matching
+-- all of its filters; a filter left NULL matches everyone. Placings are stored per division,
+-- next to the built-in open, age class and weight class placings of the results table.
+CREATE TABLE divisions (
+    id TEXT PRIMARY KEY,
+    contest_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    gender TEXT CHECK(gender IN ('Male','Female')),
+    age_category_id TEXT,
+    weight_class_id TEXT,
+    equipment TEXT CHECK(equipment IN ('Raw','SinglePly','MultiPly')),
+    basis TEXT NOT NULL DEFAULT 'Total' CHECK(basis IN ('Total','Points')),
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (contest_id) REFERENCES contests(id) ON DELETE CASCADE,
+    FOREIGN KEY (age_category_id) REFERENCES age_categories(id),
+    FOREIGN KEY (weight_class_id) REFERENCES weight_classes(id),
+    UNIQUE(contest_id, name)
+);
+
+CREATE TABLE division_placings (
+    division_id TEXT NOT NULL,
+    result_id TEXT NOT NULL,
+    place INTEGER NOT NULL,
+    PRIMARY KEY (division_id, result_id),
+    FOREIGN KEY (division_id) REFERENCES divisions(id) ON DELETE CASCADE,
+    FOREIGN KEY (result_id) REFERENCES results(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_divisions_contest ON divisions(contest_id);
+CREATE INDEX idx_division_placings_result ON division_placings(result_id);
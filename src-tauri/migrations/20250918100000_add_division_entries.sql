@@ -0,0 +1,13 @@
+-- Registrations entered in divisions they do not match by age category, e.g. a master also
+-- lifting in the senior open. The lifter's one set of attempts is scored in every division
+-- they match or entered.
+CREATE TABLE division_entries (
+    division_id TEXT NOT NULL,
+    registration_id TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (division_id, registration_id),
+    FOREIGN KEY (division_id) REFERENCES divisions(id) ON DELETE CASCADE,
+    FOREIGN KEY (registration_id) REFERENCES registrations(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_division_entries_registration ON division_entries(registration_id);
@@ -0,0 +1,4 @@
+-- Best lifter awards: one trophy per award group, formed by the comma-separated list of
+-- splits (Sex, Equipment, AgeCategory), given when the group has enough entrants.
+ALTER TABLE contests ADD COLUMN best_lifter_split TEXT NOT NULL DEFAULT 'Sex,AgeCategory,Equipment';
+ALTER TABLE contests ADD COLUMN best_lifter_min_entrants INTEGER NOT NULL DEFAULT 3 CHECK(best_lifter_min_entrants > 0);
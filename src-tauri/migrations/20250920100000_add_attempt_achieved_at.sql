@@ -0,0 +1,31 @@
+-- Judging order of successful attempts, used to tie-break equal totals by who achieved them
+-- first. Each attempt gets the next number the first time it is judged successful and keeps
+-- it when re-judged, unlike the timestamp which has second resolution and is overwritten.
+ALTER TABLE attempts ADD COLUMN achieved_at INTEGER;
+
+-- Number attempts already judged successful in the order they were completed
+UPDATE attempts SET achieved_at = (
+    SELECT COUNT(*) FROM attempts earlier
+    WHERE earlier.status = 'Successful'
+    AND (COALESCE(earlier.timestamp, earlier.created_at), earlier.rowid)
+        <= (COALESCE(attempts.timestamp, attempts.created_at), attempts.rowid)
+)
+WHERE status = 'Successful';
+
+CREATE TRIGGER set_attempt_achieved_at_on_update
+    AFTER UPDATE OF status ON attempts
+    WHEN NEW.status = 'Successful' AND NEW.achieved_at IS NULL
+    BEGIN
+        UPDATE attempts
+        SET achieved_at = (SELECT COALESCE(MAX(achieved_at), 0) + 1 FROM attempts)
+        WHERE id = NEW.id;
+    END;
+
+CREATE TRIGGER set_attempt_achieved_at_on_insert
+    AFTER INSERT ON attempts
+    WHEN NEW.status = 'Successful' AND NEW.achieved_at IS NULL
+    BEGIN
+        UPDATE attempts
+        SET achieved_at = (SELECT COALESCE(MAX(achieved_at), 0) + 1 FROM attempts)
+        WHERE id = NEW.id;
+    END;
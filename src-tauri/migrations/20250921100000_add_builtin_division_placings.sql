@@ -0,0 +1,51 @@
+-- The built-in open, age class and weight class rankings are placed like named divisions:
+-- their placings are stored in division_placings under the reserved division IDs 'open',
+-- 'age' and 'weight' (models::division) instead of the place columns of results. Those IDs
+-- have no divisions row, so placings no longer reference divisions and are removed with
+-- their named division by a trigger.
+CREATE TABLE division_placings_new (
+    division_id TEXT NOT NULL,
+    result_id TEXT NOT NULL,
+    place INTEGER NOT NULL,
+    PRIMARY KEY (division_id, result_id),
+    FOREIGN KEY (result_id) REFERENCES results(id) ON DELETE CASCADE
+);
+
+INSERT INTO division_placings_new (division_id, result_id, place)
+SELECT division_id, result_id, place FROM division_placings;
+
+DROP TABLE division_placings;
+ALTER TABLE division_placings_new RENAME TO division_placings;
+CREATE INDEX idx_division_placings_result ON division_placings(result_id);
+
+CREATE TRIGGER delete_division_placings
+    AFTER DELETE ON divisions
+    BEGIN
+        DELETE FROM division_placings WHERE division_id = OLD.id;
+    END;
+
+INSERT INTO division_placings (division_id, result_id, place)
+SELECT 'open', id, place_open FROM results WHERE place_open IS NOT NULL;
+INSERT INTO division_placings (division_id, result_id, place)
+SELECT 'age', id, place_in_age_class FROM results WHERE place_in_age_class IS NOT NULL;
+INSERT INTO division_placings (division_id, result_id, place)
+SELECT 'weight', id, place_in_weight_class FROM results WHERE place_in_weight_class IS NOT NULL;
+
+ALTER TABLE results DROP COLUMN place_open;
+ALTER TABLE results DROP COLUMN place_in_age_class;
+ALTER TABLE results DROP COLUMN place_in_weight_class;
+
+-- Results with their places in the built-in rankings, derived from division_placings
+CREATE VIEW ranked_results AS
+SELECT
+    results.*,
+    open.place AS place_open,
+    age.place AS place_in_age_class,
+    weight.place AS place_in_weight_class
+FROM results
+LEFT JOIN division_placings open
+    ON open.division_id = 'open' AND open.result_id = results.id
+LEFT JOIN division_placings age
+    ON age.division_id = 'age' AND age.result_id = results.id
+LEFT JOIN division_placings weight
+    ON weight.division_id = 'weight' AND weight.result_id = results.id;
@@ -0,0 +1,215 @@
+//! Best lifter awards.
+//!
+//! Trophies go to the lifter with the most coefficient points in each award group, formed
+//! by the contest's splits (e.g. per sex, age category and equipment). A group's award is
+//! only given when the group has the minimum number of entrants; every registration counts
+//! as an entrant, but only ranked lifters can win. Lifters still equal after the tie-break
+//! chain share the award.
+
+use crate::models::contest::{BestLifterAwards, RankingSplit, TieBreak};
+use crate::models::registration::Equipment;
+use crate::ranking::{self, RankEntry};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A registration taking part in the awards
+#[derive(Debug, Clone)]
+pub struct BestLifterEntry {
+    pub registration_id: String,
+    pub competitor_name: String,
+    pub gender: String,
+    pub age_category: String,
+    pub equipment: Equipment,
+    /// Points and tie-break data; None when the lifter has no place (bomb-out, DSQ, DNF)
+    pub ranked: Option<RankEntry>,
+}
+
+/// Lifters an award is contested by; attributes the awards are not split by are None
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BestLifterGroup {
+    pub gender: Option<String>,
+    pub age_category: Option<String>,
+    pub equipment: Option<Equipment>,
+}
+
+impl BestLifterGroup {
+    /// Name of the group for sheets and exports, e.g. "Female Senior Raw"
+    pub fn label(&self) -> String {
+        let parts: Vec<String> = [
+            self.gender.clone(),
+            self.age_category.clone(),
+            self.equipment.map(|equipment| equipment.to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if parts.is_empty() {
+            "Overall".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BestLifterWinner {
+    pub registration_id: String,
+    pub competitor_name: String,
+    pub points: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BestLifterAward {
+    pub group: BestLifterGroup,
+    pub entrants: usize,
+    /// False when the group has too few entrants or no ranked lifter
+    pub awarded: bool,
+    pub winners: Vec<BestLifterWinner>,
+}
+
+/// Decide the best lifter of every award group
+pub fn award_best_lifters(
+    entries: Vec<BestLifterEntry>,
+    awards: &BestLifterAwards,
+    tie_breaks: &[TieBreak],
+) -> Vec<BestLifterAward> {
+    let mut groups: BTreeMap<BestLifterGroup, Vec<BestLifterEntry>> = BTreeMap::new();
+    for entry in entries {
+        let split = |by: RankingSplit| awards.split_by.contains(&by);
+        let group = BestLifterGroup {
+            gender: split(RankingSplit::Sex).then(|| entry.gender.clone()),
+            age_category: split(RankingSplit::AgeCategory).then(|| entry.age_category.clone()),
+            equipment: split(RankingSplit::Equipment).then_some(entry.equipment),
+        };
+        groups.entry(group).or_default().push(entry);
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, entries)| {
+            let entrants = entries.len();
+            let mut winners = Vec::new();
+            if entrants >= awards.min_entrants as usize {
+                let ranked: Vec<RankEntry> = entries
+                    .iter()
+                    .filter_map(|entry| entry.ranked.clone())
+                    .collect();
+                for (result_id, place) in ranking::assign_places(ranked, tie_breaks) {
+                    if place > 1 {
+                        break;
+                    }
+                    let Some(entry) = entries.iter().find(|entry| {
+                        entry
+                            .ranked
+                            .as_ref()
+                            .is_some_and(|ranked| ranked.result_id == result_id)
+                    }) else {
+                        continue;
+                    };
+                    winners.push(BestLifterWinner {
+                        registration_id: entry.registration_id.clone(),
+                        competitor_name: entry.competitor_name.clone(),
+                        points: entry.ranked.as_ref().map_or(0.0, |ranked| ranked.score),
+                    });
+                }
+            }
+            BestLifterAward {
+                group,
+                entrants,
+                awarded: !winners.is_empty(),
+                winners,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::contest::DEFAULT_TIE_BREAKS;
+
+    fn entry(id: &str, gender: &str, points: Option<f64>, bodyweight: f64) -> BestLifterEntry {
+        BestLifterEntry {
+            registration_id: id.to_string(),
+            competitor_name: format!("Lifter {}", id),
+            gender: gender.to_string(),
+            age_category: "Senior".to_string(),
+            equipment: Equipment::Raw,
+            ranked: points.map(|score| RankEntry {
+                result_id: format!("result-{}", id),
+                score,
+                bodyweight,
+                achieved_at: None,
+                lot_number: None,
+            }),
+        }
+    }
+
+    fn by_sex(min_entrants: u32) -> BestLifterAwards {
+        BestLifterAwards {
+            split_by: vec![RankingSplit::Sex],
+            min_entrants,
+        }
+    }
+
+    #[test]
+    fn test_most_points_win_each_group() {
+        let awards = award_best_lifters(
+            vec![
+                entry("m1", "Male", Some(400.0), 80.0),
+                entry("m2", "Male", Some(420.0), 90.0),
+                entry("f1", "Female", Some(350.0), 60.0),
+            ],
+            &by_sex(1),
+            &DEFAULT_TIE_BREAKS,
+        );
+
+        assert_eq!(awards.len(), 2);
+        assert_eq!(awards[0].group.label(), "Female");
+        assert_eq!(awards[0].winners[0].registration_id, "f1");
+        assert_eq!(awards[1].group.label(), "Male");
+        assert_eq!(awards[1].entrants, 2);
+        assert_eq!(awards[1].winners.len(), 1);
+        assert_eq!(awards[1].winners[0].registration_id, "m2");
+    }
+
+    #[test]
+    fn test_small_groups_are_not_awarded() {
+        let awards = award_best_lifters(
+            vec![
+                entry("m1", "Male", Some(400.0), 80.0),
+                entry("m2", "Male", None, 90.0),
+                entry("m3", "Male", Some(300.0), 70.0),
+                entry("f1", "Female", Some(350.0), 60.0),
+            ],
+            &by_sex(3),
+            &DEFAULT_TIE_BREAKS,
+        );
+
+        // Lifters without a place still count as entrants
+        assert!(!awards[0].awarded);
+        assert!(awards[0].winners.is_empty());
+        assert!(awards[1].awarded);
+        assert_eq!(awards[1].entrants, 3);
+        assert_eq!(awards[1].winners[0].registration_id, "m1");
+    }
+
+    #[test]
+    fn test_tied_lifters_share_the_award() {
+        let awards = award_best_lifters(
+            vec![
+                entry("a", "Male", Some(400.0), 80.0),
+                entry("b", "Female", Some(400.0), 80.0),
+            ],
+            &BestLifterAwards {
+                split_by: Vec::new(),
+                min_entrants: 1,
+            },
+            &[],
+        );
+
+        assert_eq!(awards.len(), 1);
+        assert_eq!(awards[0].group.label(), "Overall");
+        assert_eq!(awards[0].winners.len(), 2);
+    }
+}
@@ -1,166 +1,406 @@
-use chrono::{NaiveDate, Utc};
+use crate::models::contest::{AgeRule, Discipline};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::sync::OnceLock;
 
-/// Calculate Reshel coefficient based on bodyweight and gender
-/// This is a simplified formula - in practice you'd use official IPF tables
-pub fn calculate_reshel_coefficient(bodyweight: f64, gender: &str) -> f64 {
-    // Simplified Wilks/IPF GL coefficient calculation
-    // These are approximate coefficients for demonstration
+/// Errors raised when a coefficient cannot be computed for a lifter
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CoefficientError {
+    #[error("Unknown gender: {0}")]
+    UnknownGender(String),
+    #[error("Invalid bodyweight: {0} kg")]
+    InvalidBodyweight(f64),
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    #[error("{formula} is not defined for {discipline} contests")]
+    UnsupportedDiscipline {
+        formula: &'static str,
+        discipline: Discipline,
+    },
+}
+
+/// Lifter sex as used by the coefficient tables
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sex {
+    Male,
+    Female,
+}
+
+fn parse_sex(gender: &str) -> Result<Sex, CoefficientError> {
     match gender.to_lowercase().as_str() {
-        "male" | "m" => {
-            let a = -216.0475144;
-            let b = 16.2606339;
-            let c = -0.002388645;
-            let d = -0.00113732;
-            let e = 7.01863e-06;
-            let f = -1.291e-08;
-
-            let x = bodyweight;
-            500.0
-                / (a + b * x
-                    + c * x * x
-                    + d * x * x * x
-                    + e * x * x * x * x
-                    + f * x * x * x * x * x)
-        }
-        "female" | "f" => {
-            let a = 594.31747775582;
-            let b = -27.23842536447;
-            let c = 0.82112226871;
-            let d = -0.00930733913;
-            let e = 4.731582e-05;
-            let f = -9.054e-08;
-
-            let x = bodyweight;
-            500.0
-                / (a + b * x
-                    + c * x * x
-                    + d * x * x * x
-                    + e * x * x * x * x
-                    + f * x * x * x * x * x)
-        }
-        _ => 1.0, // Default
+        "male" | "m" => Ok(Sex::Male),
+        "female" | "f" => Ok(Sex::Female),
+        _ => Err(CoefficientError::UnknownGender(gender.to_string())),
     }
 }
 
-/// Calculate McCullough coefficient based on age
-/// Age adjustment factor for masters/veterans and juniors
-pub fn calculate_mccullough_coefficient(birth_date: &str, contest_date: &str) -> f64 {
-    // Parse dates
-    let birth = match NaiveDate::parse_from_str(birth_date, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => return 1.0, // Default if parsing fails
-    };
+fn validate_bodyweight(bodyweight: f64) -> Result<(), CoefficientError> {
+    if bodyweight.is_finite() && bodyweight > 0.0 {
+        Ok(())
+    } else {
+        Err(CoefficientError::InvalidBodyweight(bodyweight))
+    }
+}
+
+/// IPF GL parameters: points = total * 100 / (A - B * e^(-C * bodyweight))
+struct IpfGlParameters {
+    a: f64,
+    b: f64,
+    c: f64,
+}
+
+// Official IPF GL parameters (IPF Technical Committee, 2020)
+const IPF_GL_MEN_CLASSIC_POWERLIFTING: IpfGlParameters = IpfGlParameters {
+    a: 1199.72839,
+    b: 1025.18162,
+    c: 0.00921,
+};
+const IPF_GL_MEN_EQUIPPED_POWERLIFTING: IpfGlParameters = IpfGlParameters {
+    a: 1236.25115,
+    b: 1449.21864,
+    c: 0.01644,
+};
+const IPF_GL_MEN_CLASSIC_BENCH: IpfGlParameters = IpfGlParameters {
+    a: 320.98041,
+    b: 281.40258,
+    c: 0.01008,
+};
+const IPF_GL_MEN_EQUIPPED_BENCH: IpfGlParameters = IpfGlParameters {
+    a: 381.22073,
+    b: 733.79378,
+    c: 0.02398,
+};
+const IPF_GL_WOMEN_CLASSIC_POWERLIFTING: IpfGlParameters = IpfGlParameters {
+    a: 610.32796,
+    b: 1045.59282,
+    c: 0.03048,
+};
+const IPF_GL_WOMEN_EQUIPPED_POWERLIFTING: IpfGlParameters = IpfGlParameters {
+    a: 758.63878,
+    b: 949.31382,
+    c: 0.02435,
+};
+const IPF_GL_WOMEN_CLASSIC_BENCH: IpfGlParameters = IpfGlParameters {
+    a: 142.40398,
+    b: 442.52671,
+    c: 0.04724,
+};
+const IPF_GL_WOMEN_EQUIPPED_BENCH: IpfGlParameters = IpfGlParameters {
+    a: 221.82209,
+    b: 357.00377,
+    c: 0.02937,
+};
 
-    let contest = match NaiveDate::parse_from_str(contest_date, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => Utc::now().date_naive(), // Use today if parsing fails
+/// Calculate the IPF GL (Goodlift) coefficient
+/// Full powerlifting and bench-only contests use separate parameter sets,
+/// each with a classic (raw) and an equipped variant
+pub fn calculate_ipf_gl_coefficient(
+    bodyweight: f64,
+    gender: &str,
+    equipped: bool,
+    discipline: &Discipline,
+) -> Result<f64, CoefficientError> {
+    validate_bodyweight(bodyweight)?;
+    let sex = parse_sex(gender)?;
+
+    let params = match (sex, discipline, equipped) {
+        (Sex::Male, Discipline::Powerlifting, false) => &IPF_GL_MEN_CLASSIC_POWERLIFTING,
+        (Sex::Male, Discipline::Powerlifting, true) => &IPF_GL_MEN_EQUIPPED_POWERLIFTING,
+        (Sex::Male, Discipline::Bench, false) => &IPF_GL_MEN_CLASSIC_BENCH,
+        (Sex::Male, Discipline::Bench, true) => &IPF_GL_MEN_EQUIPPED_BENCH,
+        (Sex::Female, Discipline::Powerlifting, false) => &IPF_GL_WOMEN_CLASSIC_POWERLIFTING,
+        (Sex::Female, Discipline::Powerlifting, true) => &IPF_GL_WOMEN_EQUIPPED_POWERLIFTING,
+        (Sex::Female, Discipline::Bench, false) => &IPF_GL_WOMEN_CLASSIC_BENCH,
+        (Sex::Female, Discipline::Bench, true) => &IPF_GL_WOMEN_EQUIPPED_BENCH,
+        (_, Discipline::Squat | Discipline::Deadlift, _) => {
+            return Err(CoefficientError::UnsupportedDiscipline {
+                formula: "IPF GL",
+                discipline: discipline.clone(),
+            })
+        }
     };
 
-    // Calculate age on contest date
-    let age = contest.years_since(birth).unwrap_or(0);
+    Ok(100.0 / (params.a - params.b * (-params.c * bodyweight).exp()))
+}
+
+/// Calculate IPF GL points for a total
+pub fn calculate_ipf_gl_points(
+    total_weight: f64,
+    bodyweight: f64,
+    gender: &str,
+    equipped: bool,
+    discipline: &Discipline,
+) -> Result<f64, CoefficientError> {
+    let coefficient = calculate_ipf_gl_coefficient(bodyweight, gender, equipped, discipline)?;
+    Ok(calculate_points(total_weight, coefficient, 1.0))
+}
+
+/// Evaluate a polynomial with coefficients in ascending order of power
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c)
+}
 
-    // McCullough age factors (simplified)
-    match age {
-        // Juniors (under 24)
-        13..=15 => 1.13, // Junior 13
-        16..=18 => 1.08, // Junior 16
-        19 => 1.06,      // Junior 19
-        20..=23 => 1.03, // Junior 23
+// DOTS (2019) polynomial coefficients, valid for 40-210 kg men and 40-150 kg women
+const DOTS_MEN: [f64; 5] = [
+    -307.75076,
+    24.0900756,
+    -0.1918759221,
+    0.0007391293,
+    -0.000001093,
+];
+const DOTS_WOMEN: [f64; 5] = [
+    -57.96288,
+    13.6175032,
+    -0.1126655495,
+    0.0005158568,
+    -0.0000010706,
+];
 
-        // Senior (24-39) - no adjustment
-        24..=39 => 1.0,
+// Original Wilks polynomial coefficients
+const WILKS_MEN: [f64; 6] = [
+    -216.0475144,
+    16.2606339,
+    -0.002388645,
+    -0.00113732,
+    7.01863e-06,
+    -1.291e-08,
+];
+const WILKS_WOMEN: [f64; 6] = [
+    594.31747775582,
+    -27.23842536447,
+    0.82112226871,
+    -0.00930733913,
+    4.731582e-05,
+    -9.054e-08,
+];
 
-        // Masters/Veterans (40+)
-        40..=44 => 1.01, // Veteran 40
-        45..=49 => 1.02,
-        50..=54 => 1.04, // Veteran 50
-        55..=59 => 1.06,
-        60..=64 => 1.09, // Veteran 60
-        65..=69 => 1.12,
-        70..=74 => 1.16, // Veteran 70
-        75..=79 => 1.21,
-        80.. => 1.27, // Veteran 80+
+// Wilks-2020 polynomial coefficients
+const WILKS2020_MEN: [f64; 6] = [
+    47.4617885411949,
+    8.47206137941125,
+    0.073694103462609,
+    -0.00139583381094385,
+    7.07665973070743e-06,
+    -1.20804336482315e-08,
+];
+const WILKS2020_WOMEN: [f64; 6] = [
+    -125.425539779509,
+    13.7121941940668,
+    -0.0330725063103405,
+    -0.0010504000506583,
+    9.38773881462799e-06,
+    -2.3334613884954e-08,
+];
 
-        _ => 1.0, // Default
-    }
+/// Calculate the DOTS coefficient
+/// Bodyweight is clamped to the range the polynomial was fitted on
+pub fn calculate_dots_coefficient(bodyweight: f64, gender: &str) -> Result<f64, CoefficientError> {
+    validate_bodyweight(bodyweight)?;
+    let (coefficients, bodyweight) = match parse_sex(gender)? {
+        Sex::Male => (&DOTS_MEN, bodyweight.clamp(40.0, 210.0)),
+        Sex::Female => (&DOTS_WOMEN, bodyweight.clamp(40.0, 150.0)),
+    };
+
+    Ok(500.0 / polynomial(coefficients, bodyweight))
 }
 
-/// Determine age category based on age
-pub fn determine_age_category(birth_date: &str, contest_date: &str) -> String {
-    let birth = match NaiveDate::parse_from_str(birth_date, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => return "SENIOR".to_string(),
+/// Calculate the original Wilks coefficient
+/// Bodyweight is clamped to the range the polynomial was fitted on
+pub fn calculate_wilks_coefficient(bodyweight: f64, gender: &str) -> Result<f64, CoefficientError> {
+    validate_bodyweight(bodyweight)?;
+    let (coefficients, bodyweight) = match parse_sex(gender)? {
+        Sex::Male => (&WILKS_MEN, bodyweight.clamp(40.0, 201.9)),
+        Sex::Female => (&WILKS_WOMEN, bodyweight.clamp(26.51, 154.53)),
     };
 
-    let contest = match NaiveDate::parse_from_str(contest_date, "%Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => Utc::now().date_naive(),
+    Ok(500.0 / polynomial(coefficients, bodyweight))
+}
+
+/// Calculate the Wilks-2020 coefficient
+/// Bodyweight is clamped to the range the polynomial was fitted on
+pub fn calculate_wilks2020_coefficient(
+    bodyweight: f64,
+    gender: &str,
+) -> Result<f64, CoefficientError> {
+    validate_bodyweight(bodyweight)?;
+    let (coefficients, bodyweight) = match parse_sex(gender)? {
+        Sex::Male => (&WILKS2020_MEN, bodyweight.clamp(40.0, 200.95)),
+        Sex::Female => (&WILKS2020_WOMEN, bodyweight.clamp(40.0, 150.95)),
     };
 
-    let age = contest.years_since(birth).unwrap_or(0);
+    Ok(600.0 / polynomial(coefficients, bodyweight))
+}
+
+/// Official Reshel lookup table for one sex, as published by the federation
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReshelTable {
+    entries: Vec<ReshelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReshelEntry {
+    bodyweight_kg: f64,
+    coefficient: f64,
+}
+
+impl ReshelTable {
+    fn parse(json: &str) -> Self {
+        let table: ReshelTable =
+            serde_json::from_str(json).expect("embedded Reshel table is valid JSON");
+        assert!(!table.entries.is_empty(), "embedded Reshel table is empty");
+        table
+    }
 
-    match age {
-        13..=15 => "JUNIOR13".to_string(),
-        16..=18 => "JUNIOR16".to_string(),
-        19 => "JUNIOR19".to_string(),
-        20..=23 => "JUNIOR23".to_string(),
-        24..=39 => "SENIOR".to_string(),
-        40..=49 => "VETERAN40".to_string(),
-        50..=59 => "VETERAN50".to_string(),
-        60..=69 => "VETERAN60".to_string(),
-        70.. => "VETERAN70".to_string(),
-        _ => "SENIOR".to_string(),
+    /// Look up a bodyweight, interpolating linearly between published rows
+    /// The lightest and heaviest rows apply to every lifter below and above them
+    fn coefficient(&self, bodyweight: f64) -> f64 {
+        let first = &self.entries[0];
+        let last = &self.entries[self.entries.len() - 1];
+
+        if bodyweight <= first.bodyweight_kg {
+            return first.coefficient;
+        }
+        if bodyweight >= last.bodyweight_kg {
+            return last.coefficient;
+        }
+
+        // Index of the first row heavier than the lifter; always in 1..len here
+        let upper = self
+            .entries
+            .partition_point(|entry| entry.bodyweight_kg <= bodyweight);
+        let lower = &self.entries[upper - 1];
+        let upper = &self.entries[upper];
+
+        let fraction =
+            (bodyweight - lower.bodyweight_kg) / (upper.bodyweight_kg - lower.bodyweight_kg);
+        lower.coefficient + fraction * (upper.coefficient - lower.coefficient)
     }
 }
 
-/// Determine weight class based on bodyweight and gender
-pub fn determine_weight_class(bodyweight: f64, gender: &str) -> String {
-    match gender.to_lowercase().as_str() {
-        "male" | "m" => {
-            if bodyweight <= 52.0 {
-                "M_52".to_string()
-            } else if bodyweight <= 56.0 {
-                "M_56".to_string()
-            } else if bodyweight <= 60.0 {
-                "M_60".to_string()
-            } else if bodyweight <= 67.5 {
-                "M_67_5".to_string()
-            } else if bodyweight <= 75.0 {
-                "M_75".to_string()
-            } else if bodyweight <= 82.5 {
-                "M_82_5".to_string()
-            } else if bodyweight <= 90.0 {
-                "M_90".to_string()
-            } else if bodyweight <= 100.0 {
-                "M_100".to_string()
-            } else if bodyweight <= 110.0 {
-                "M_110".to_string()
-            } else if bodyweight <= 125.0 {
-                "M_125".to_string()
-            } else if bodyweight <= 140.0 {
-                "M_140".to_string()
-            } else {
-                "M_140_PLUS".to_string()
-            }
+fn reshel_table(sex: Sex) -> &'static ReshelTable {
+    static MEN: OnceLock<ReshelTable> = OnceLock::new();
+    static WOMEN: OnceLock<ReshelTable> = OnceLock::new();
+
+    match sex {
+        Sex::Male => {
+            MEN.get_or_init(|| ReshelTable::parse(include_str!("../data/reshel-men.json")))
         }
-        "female" | "f" => {
-            if bodyweight <= 47.0 {
-                "F_47".to_string()
-            } else if bodyweight <= 52.0 {
-                "F_52".to_string()
-            } else if bodyweight <= 57.0 {
-                "F_57".to_string()
-            } else if bodyweight <= 63.0 {
-                "F_63".to_string()
-            } else if bodyweight <= 72.0 {
-                "F_72".to_string()
-            } else if bodyweight <= 84.0 {
-                "F_84".to_string()
-            } else {
-                "F_84_PLUS".to_string()
-            }
+        Sex::Female => {
+            WOMEN.get_or_init(|| ReshelTable::parse(include_str!("../data/reshel-women.json")))
         }
-        _ => "M_75".to_string(), // Default
+    }
+}
+
+/// Calculate Reshel coefficient based on bodyweight and gender
+/// Uses the official Reshel tables; bodyweights between rows are interpolated, lifters
+/// lighter than the first row get its coefficient
+pub fn calculate_reshel_coefficient(
+    bodyweight: f64,
+    gender: &str,
+) -> Result<f64, CoefficientError> {
+    validate_bodyweight(bodyweight)?;
+    let sex = parse_sex(gender)?;
+    Ok(reshel_table(sex).coefficient(bodyweight))
+}
+
+/// Junior age factors (Foster), applied below the masters table
+const MCCULLOUGH_JUNIOR: [(u32, f64); 9] = [
+    (14, 1.23),
+    (15, 1.18),
+    (16, 1.13),
+    (17, 1.08),
+    (18, 1.06),
+    (19, 1.04),
+    (20, 1.03),
+    (21, 1.02),
+    (22, 1.01),
+];
+
+/// Official per-year McCullough masters table, ages 40 to 90
+#[derive(Debug, Deserialize)]
+struct McCulloughTable {
+    entries: Vec<McCulloughEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct McCulloughEntry {
+    age: u32,
+    coefficient: f64,
+}
+
+fn mccullough_masters_table() -> &'static McCulloughTable {
+    static TABLE: OnceLock<McCulloughTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let table: McCulloughTable = serde_json::from_str(include_str!("../data/mccullough.json"))
+            .expect("embedded McCullough table is valid JSON");
+        assert!(
+            !table.entries.is_empty(),
+            "embedded McCullough table is empty"
+        );
+        table
+    })
+}
+
+/// Calculate McCullough coefficient based on age
+/// Age adjustment factor for masters/veterans and juniors
+pub fn calculate_mccullough_coefficient(
+    birth_date: &str,
+    contest_date: &str,
+) -> Result<f64, CoefficientError> {
+    Ok(mccullough_factor(age_on_date(birth_date, contest_date)?))
+}
+
+/// Age of a lifter on contest day in full years
+pub fn age_on_date(birth_date: &str, contest_date: &str) -> Result<u32, CoefficientError> {
+    let birth = parse_date(birth_date)?;
+    let contest = parse_date(contest_date)?;
+
+    contest
+        .years_since(birth)
+        .ok_or_else(|| CoefficientError::InvalidDate(birth_date.to_string()))
+}
+
+/// Age a lifter reaches during the calendar year of the contest
+pub fn age_in_contest_year(birth_date: &str, contest_date: &str) -> Result<u32, CoefficientError> {
+    let birth = parse_date(birth_date)?;
+    let contest = parse_date(contest_date)?;
+
+    u32::try_from(contest.year() - birth.year())
+        .map_err(|_| CoefficientError::InvalidDate(birth_date.to_string()))
+}
+
+/// Age used for age category assignment under an age rule
+pub fn category_age(
+    rule: AgeRule,
+    birth_date: &str,
+    contest_date: &str,
+) -> Result<u32, CoefficientError> {
+    match rule {
+        AgeRule::ExactDate => age_on_date(birth_date, contest_date),
+        AgeRule::BirthYear => age_in_contest_year(birth_date, contest_date),
+    }
+}
+
+fn parse_date(date: &str) -> Result<NaiveDate, CoefficientError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| CoefficientError::InvalidDate(date.to_string()))
+}
+
+/// McCullough age factor for an age in full years
+/// Seniors (23-39) score 1.0; lifters older than the table get its last factor and lifters
+/// younger than the junior table get no age adjustment
+pub fn mccullough_factor(age: u32) -> f64 {
+    if let Some((_, factor)) = MCCULLOUGH_JUNIOR.iter().find(|(junior, _)| *junior == age) {
+        return *factor;
+    }
+
+    let masters = &mccullough_masters_table().entries;
+    match masters.iter().find(|entry| entry.age == age) {
+        Some(entry) => entry.coefficient,
+        None if age < masters[0].age => 1.0,
+        None => masters[masters.len() - 1].coefficient,
     }
 }
 
@@ -179,31 +419,204 @@ mod tests {
 
     #[test]
     fn test_reshel_calculation() {
-        // Test male coefficient
-        let coeff = calculate_reshel_coefficient(82.5, "male");
-        assert!(coeff > 0.6 && coeff < 0.8); // Reasonable range for 82.5kg male
+        // Bodyweights on a published row get exactly the table value
+        assert_eq!(calculate_reshel_coefficient(52.0, "male").unwrap(), 1.818);
+        assert_eq!(calculate_reshel_coefficient(140.0, "male").unwrap(), 0.84);
+        assert_eq!(calculate_reshel_coefficient(52.0, "female").unwrap(), 2.079);
+
+        // Lifters below the lightest or above the heaviest row get its value
+        assert_eq!(
+            calculate_reshel_coefficient(45.0, "male").unwrap(),
+            calculate_reshel_coefficient(50.0, "male").unwrap()
+        );
+        assert_eq!(calculate_reshel_coefficient(38.0, "female").unwrap(), 3.145);
+        assert_eq!(calculate_reshel_coefficient(195.0, "male").unwrap(), 0.814);
+        assert_eq!(
+            calculate_reshel_coefficient(125.0, "female").unwrap(),
+            1.306
+        );
+    }
+
+    #[test]
+    fn test_reshel_interpolates_between_rows() {
+        let lower = calculate_reshel_coefficient(82.5, "male").unwrap();
+        let upper = calculate_reshel_coefficient(82.75, "male").unwrap();
+        let between = calculate_reshel_coefficient(82.6, "male").unwrap();
+
+        let expected = lower + (upper - lower) * 0.4;
+        assert!((between - expected).abs() < 1e-9);
+        assert!(between <= lower && between >= upper);
+    }
 
-        // Test female coefficient
-        let coeff = calculate_reshel_coefficient(63.0, "female");
-        assert!(coeff > 0.9 && coeff < 1.2); // Reasonable range for 63kg female
+    #[test]
+    fn test_reshel_rejects_invalid_input() {
+        assert_eq!(
+            calculate_reshel_coefficient(80.0, "unknown"),
+            Err(CoefficientError::UnknownGender("unknown".to_string()))
+        );
+        assert_eq!(
+            calculate_reshel_coefficient(-1.0, "female"),
+            Err(CoefficientError::InvalidBodyweight(-1.0))
+        );
+    }
+
+    #[test]
+    fn test_mccullough_per_year_factors() {
+        assert_eq!(mccullough_factor(14), 1.23);
+        assert_eq!(mccullough_factor(19), 1.04);
+        assert_eq!(mccullough_factor(30), 1.0);
+        assert_eq!(mccullough_factor(40), 1.0);
+        assert_eq!(mccullough_factor(43), 1.031);
+        assert_eq!(mccullough_factor(50), 1.13);
+        assert_eq!(mccullough_factor(70), 1.645);
+        assert_eq!(mccullough_factor(90), 2.549);
+        assert_eq!(mccullough_factor(94), 2.549);
+    }
+
+    #[test]
+    fn test_mccullough_leaves_lifters_below_the_table_unadjusted() {
+        assert_eq!(mccullough_factor(13), 1.0);
+        assert_eq!(mccullough_factor(8), 1.0);
+    }
+
+    #[test]
+    fn test_mccullough_uses_age_on_contest_day() {
+        // Turns 50 the day after the contest
+        assert_eq!(age_on_date("1975-06-02", "2025-06-01").unwrap(), 49);
+        assert_eq!(
+            calculate_mccullough_coefficient("1975-06-01", "2025-06-01").unwrap(),
+            1.13
+        );
+        assert_eq!(
+            calculate_mccullough_coefficient("01.06.1975", "2025-06-01"),
+            Err(CoefficientError::InvalidDate("01.06.1975".to_string()))
+        );
     }
 
     #[test]
     fn test_age_category() {
-        let category = determine_age_category("1998-05-15", "2025-09-01");
-        assert_eq!(category, "SENIOR"); // 27 years old
+        let registry = crate::federation::FederationRegistry::builtin();
+        let profile = registry.get("PZKFiTS").unwrap();
+        let category_for = |birth_date: &str, rule: AgeRule| {
+            let age = category_age(rule, birth_date, "2025-09-01").unwrap();
+            profile.age_category_for(age).unwrap().id.clone()
+        };
+
+        assert_eq!(category_for("1998-05-15", AgeRule::ExactDate), "SENIOR"); // 27 years old
+        assert_eq!(category_for("2006-03-20", AgeRule::ExactDate), "JUNIOR19"); // 19 years old
+
+        // Turns 40 in December, after the contest
+        assert_eq!(category_for("1985-12-10", AgeRule::BirthYear), "VETERAN40");
+    }
+
+    #[test]
+    fn test_birth_year_age_rule() {
+        // Turns 40 in December, after the contest
+        assert_eq!(
+            category_age(AgeRule::ExactDate, "1985-12-10", "2025-06-01").unwrap(),
+            39
+        );
+        assert_eq!(
+            category_age(AgeRule::BirthYear, "1985-12-10", "2025-06-01").unwrap(),
+            40
+        );
+        assert!(category_age(AgeRule::BirthYear, "2026-01-01", "2025-06-01").is_err());
+    }
+
+    #[test]
+    fn test_ipf_gl_reference_values() {
+        let points =
+            calculate_ipf_gl_points(700.0, 93.0, "male", false, &Discipline::Powerlifting).unwrap();
+        assert!((points - 91.575).abs() < 0.01);
+
+        let points =
+            calculate_ipf_gl_points(500.0, 63.0, "female", false, &Discipline::Powerlifting)
+                .unwrap();
+        assert!((points - 109.392).abs() < 0.01);
+
+        let points =
+            calculate_ipf_gl_points(900.0, 120.0, "male", true, &Discipline::Powerlifting).unwrap();
+        assert!((points - 86.981).abs() < 0.01);
+
+        let points =
+            calculate_ipf_gl_points(200.0, 83.0, "male", false, &Discipline::Bench).unwrap();
+        assert!((points - 100.459).abs() < 0.01);
+
+        let points =
+            calculate_ipf_gl_points(120.0, 57.0, "female", true, &Discipline::Bench).unwrap();
+        assert!((points - 77.474).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ipf_gl_rejects_invalid_input() {
+        assert_eq!(
+            calculate_ipf_gl_coefficient(80.0, "unknown", false, &Discipline::Powerlifting),
+            Err(CoefficientError::UnknownGender("unknown".to_string()))
+        );
+        assert_eq!(
+            calculate_ipf_gl_coefficient(0.0, "male", false, &Discipline::Powerlifting),
+            Err(CoefficientError::InvalidBodyweight(0.0))
+        );
+        assert!(calculate_ipf_gl_coefficient(80.0, "male", false, &Discipline::Deadlift).is_err());
+    }
+
+    #[test]
+    fn test_dots_reference_values() {
+        let points = calculate_points(
+            700.0,
+            calculate_dots_coefficient(100.0, "male").unwrap(),
+            1.0,
+        );
+        assert!((points - 430.861).abs() < 0.01);
+
+        let points = calculate_points(
+            400.0,
+            calculate_dots_coefficient(60.0, "female").unwrap(),
+            1.0,
+        );
+        assert!((points - 443.418).abs() < 0.01);
+
+        // Above the fitted range the 210 kg coefficient is used
+        let points = calculate_points(
+            700.0,
+            calculate_dots_coefficient(250.0, "male").unwrap(),
+            1.0,
+        );
+        assert!((points - 346.934).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_wilks_reference_values() {
+        let points = calculate_points(
+            700.0,
+            calculate_wilks_coefficient(100.0, "male").unwrap(),
+            1.0,
+        );
+        assert!((points - 426.012).abs() < 0.01);
 
-        let category = determine_age_category("2006-03-20", "2025-09-01");
-        assert_eq!(category, "JUNIOR19"); // 19 years old
+        let points = calculate_points(
+            400.0,
+            calculate_wilks_coefficient(60.0, "female").unwrap(),
+            1.0,
+        );
+        assert!((points - 445.955).abs() < 0.01);
     }
 
     #[test]
-    fn test_weight_class() {
-        let class = determine_weight_class(82.5, "male");
-        assert_eq!(class, "M_82_5");
+    fn test_wilks2020_reference_values() {
+        let points = calculate_points(
+            700.0,
+            calculate_wilks2020_coefficient(100.0, "male").unwrap(),
+            1.0,
+        );
+        assert!((points - 510.553).abs() < 0.01);
 
-        let class = determine_weight_class(63.0, "female");
-        assert_eq!(class, "F_63");
+        let points = calculate_points(
+            400.0,
+            calculate_wilks2020_coefficient(60.0, "female").unwrap(),
+            1.0,
+        );
+        assert!((points - 527.613).abs() < 0.01);
     }
 
     #[test]
@@ -1,14 +1,40 @@
 use crate::database::queries;
 use crate::error::AppError;
 use crate::models::attempt::{
-    Attempt, AttemptStatus, AttemptUpdateResult, AttemptUpsert, LiftType,
+    Attempt, AttemptStatus, AttemptUpdateResult, AttemptUpsert, LiftType, RECORD_ATTEMPT_NUMBER,
 };
+use crate::recalculation;
+use crate::records;
 use crate::AppState;
+use serde::Serialize;
 use std::str::FromStr;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
+
+/// Event pushed after an attempt change recalculated a registration's result and rankings
+const RESULTS_CHANGED_EVENT: &str = "results-changed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ResultsChanged {
+    contest_id: String,
+    registration_id: String,
+}
+
+/// Tell the windows that results changed; the change is already committed, so a failed
+/// event is only logged
+fn emit_results_changed(app: &AppHandle, contest_id: &str, registration_id: &str) {
+    let payload = ResultsChanged {
+        contest_id: contest_id.to_string(),
+        registration_id: registration_id.to_string(),
+    };
+    if let Err(e) = app.emit(RESULTS_CHANGED_EVENT, payload) {
+        tracing::warn!("Failed to emit {} event: {}", RESULTS_CHANGED_EVENT, e);
+    }
+}
 
 #[tauri::command]
 pub async fn attempt_upsert_weight(
+    app: AppHandle,
     state: State<'_, AppState>,
     attempt: AttemptUpsert,
 ) -> Result<(), AppError> {
@@ -16,14 +42,64 @@ pub async fn attempt_upsert_weight(
 
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
-    queries::attempts::upsert_attempt_weight(
-        db_pool,
+
+    // Enforce the attempt rules of the contest's federation profile
+    let registration =
+        queries::registrations::get_registration_by_id(db_pool, &attempt.registration_id).await?;
+    let contest = queries::contests::get_contest_by_id(db_pool, &registration.contest_id)
+        .await?
+        .ok_or_else(|| AppError::ContestNotFound {
+            id: registration.contest_id.clone(),
+        })?;
+    state
+        .federations
+        .get(&contest.federation_profile)?
+        .validate_attempt(attempt.attempt_number, attempt.weight)
+        .map_err(|reason| AppError::InvalidAttempt { reason })?;
+    // The record check runs in the weight change's transaction, so both see the same records
+    // and a refused weight is rolled back
+    let mut tx = db_pool.begin().await?;
+    let attempt_id = queries::attempts::upsert_attempt_weight(
+        &mut *tx,
         &attempt.registration_id,
         &attempt.lift_type.to_string(),
         attempt.attempt_number,
         attempt.weight,
     )
     .await?;
+    // Records this attempt set itself do not count against it
+    if attempt.attempt_number == RECORD_ATTEMPT_NUMBER
+        && records::records_in_reach(
+            &mut *tx,
+            &attempt.registration_id,
+            &attempt.lift_type,
+            attempt.weight,
+            Some(&attempt_id),
+        )
+        .await?
+        .is_empty()
+    {
+        return Err(AppError::InvalidAttempt {
+            reason: format!(
+                "4th attempts are record attempts; {} kg sets or breaks no {} record of the lifter",
+                attempt.weight, attempt.lift_type
+            ),
+        });
+    }
+
+    // A weight change of a judged lift changes the result; lifters without a result yet
+    // get one once their first attempt is judged
+    let recalculated =
+        if queries::results::result_exists(&mut *tx, &attempt.registration_id).await? {
+            Some(recalculation::recalculate_attempt(&mut *tx, &attempt_id).await?)
+        } else {
+            None
+        };
+    tx.commit().await?;
+
+    if let Some(result) = recalculated {
+        emit_results_changed(&app, &result.contest_id, &result.registration_id);
+    }
 
     Ok(())
 }
@@ -51,6 +127,7 @@ pub async fn attempt_list(
                     AppError::Internal(format!("Invalid lift type '{}' in database for attempt {}. Expected 'Squat', 'Bench', or 'Deadlift'", a.lift_type, attempt_id))
                 })?,
                 attempt_number: a.attempt_number,
+                is_record_attempt: a.attempt_number == RECORD_ATTEMPT_NUMBER,
                 weight: a.weight,
                 status: AttemptStatus::from_str(&a.status).map_err(|_| {
                     AppError::Internal(format!("Invalid attempt status '{}' in database for attempt {}. Expected 'Pending', 'Successful', 'Failed', or 'Skipped'", a.status, attempt_id))
@@ -71,6 +148,7 @@ pub async fn attempt_list(
 
 #[tauri::command]
 pub async fn attempt_update_result(
+    app: AppHandle,
     state: State<'_, AppState>,
     update: AttemptUpdateResult,
 ) -> Result<(), AppError> {
@@ -78,8 +156,12 @@ pub async fn attempt_update_result(
 
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
+
+    // The decision, the records it sets and the recalculated result and rankings are stored
+    // together, so no window ever sees a judged attempt with a stale result
+    let mut tx = db_pool.begin().await?;
     queries::attempts::update_attempt_result(
-        db_pool,
+        &mut *tx,
         &update.attempt_id,
         &update.status.to_string(),
         None,
@@ -87,6 +169,10 @@ pub async fn attempt_update_result(
         None,
     )
     .await?;
+    let result = recalculation::recalculate_attempt(&mut *tx, &update.attempt_id).await?;
+    tx.commit().await?;
+
+    emit_results_changed(&app, &result.contest_id, &result.registration_id);
 
     Ok(())
 }
@@ -116,6 +202,7 @@ pub async fn attempt_list_for_contest(
                     AppError::Internal(format!("Invalid lift type '{}' in database for attempt {}. Expected 'Squat', 'Bench', or 'Deadlift'", a.lift_type, attempt_id))
                 })?,
                 attempt_number: a.attempt_number,
+                is_record_attempt: a.attempt_number == RECORD_ATTEMPT_NUMBER,
                 weight: a.weight,
                 status: AttemptStatus::from_str(&a.status).map_err(|_| {
                     AppError::Internal(format!("Invalid attempt status '{}' in database for attempt {}. Expected 'Pending', 'Successful', 'Failed', or 'Skipped'", a.status, attempt_id))
@@ -152,6 +239,7 @@ pub async fn attempt_get_current(
                 AppError::Internal(format!("Invalid lift type: {}", db_attempt.lift_type))
             })?,
             attempt_number: db_attempt.attempt_number,
+            is_record_attempt: db_attempt.attempt_number == RECORD_ATTEMPT_NUMBER,
             weight: db_attempt.weight,
             status: AttemptStatus::from_str(&db_attempt.status).map_err(|_| {
                 AppError::Internal(format!("Invalid status: {}", db_attempt.status))
@@ -234,6 +322,7 @@ pub async fn attempt_get_next_in_queue(
                         AppError::Internal(format!("Invalid lift type: {}", a.lift_type))
                     })?,
                     attempt_number: a.attempt_number,
+                    is_record_attempt: a.attempt_number == RECORD_ATTEMPT_NUMBER,
                     weight: a.weight,
                     status: AttemptStatus::from_str(&a.status)
                         .map_err(|_| AppError::Internal(format!("Invalid status: {}", a.status)))?,
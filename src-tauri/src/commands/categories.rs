@@ -4,15 +4,28 @@ use crate::AppState;
 use tauri::State;
 
 #[tauri::command]
-pub async fn weight_class_list(state: State<'_, AppState>) -> Result<Vec<WeightClass>, String> {
+pub async fn weight_class_list(
+    state: State<'_, AppState>,
+    class_set: Option<String>,
+) -> Result<Vec<WeightClass>, String> {
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
 
-    categories::get_weight_classes(db_pool)
+    categories::get_weight_classes(db_pool, class_set.as_deref())
         .await
         .map_err(|e| format!("Failed to get weight classes: {}", e))
 }
 
+#[tauri::command]
+pub async fn weight_class_set_list(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    categories::get_weight_class_sets(db_pool)
+        .await
+        .map_err(|e| format!("Failed to get weight class sets: {}", e))
+}
+
 #[tauri::command]
 pub async fn age_category_list(state: State<'_, AppState>) -> Result<Vec<AgeCategory>, String> {
     let db_pool = state.db.lock().await;
@@ -1,6 +1,7 @@
 use crate::database::queries;
 use crate::error::AppError;
 use crate::models::competitor::{Competitor, CompetitorCreate};
+use crate::recalculation;
 use crate::AppState;
 use base64::{engine::general_purpose, Engine as _};
 use tauri::State;
@@ -118,6 +119,8 @@ pub async fn competitor_update(
 ) -> Result<(), AppError> {
     tracing::info!("competitor_update called for id: {}", competitor_id);
 
+    let birth_date = competitor.birth_date.clone();
+    let gender = competitor.gender.clone();
     let request = queries::competitors::CreateCompetitorRequest {
         first_name: competitor.first_name,
         last_name: competitor.last_name,
@@ -132,7 +135,16 @@ pub async fn competitor_update(
 
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
-    queries::competitors::update_competitor(db_pool, &competitor_id, request).await?;
+    // The competitor is only saved if every registration can be re-derived from the new data
+    let mut tx = db_pool.begin().await?;
+    let previous = queries::competitors::get_competitor_by_id(&mut *tx, &competitor_id).await?;
+    queries::competitors::update_competitor(&mut *tx, &competitor_id, request).await?;
+
+    // Age and gender feed coefficients and categories of every registration
+    if previous.birth_date != birth_date || previous.gender != gender {
+        recalculation::recalculate_competitor(&mut *tx, &state.federations, &competitor_id).await?;
+    }
+    tx.commit().await?;
 
     Ok(())
 }
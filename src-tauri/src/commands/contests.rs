@@ -1,19 +1,57 @@
+use crate::database::queries::categories as category_queries;
 use crate::database::queries::contests as contest_queries;
 use crate::database::queries::plate_sets as plate_set_queries;
+use crate::database::queries::results as results_queries;
 use crate::error::AppError;
-use crate::models::contest::{Contest, NewContest};
+use crate::federation::FederationProfile;
+use crate::models::contest::{BestLifterAwards, Contest, NewContest, TeamScoring};
+use crate::models::registration::Registration;
+use crate::recalculation;
+use crate::scoring::{self, ScoringFormulaInfo};
 use crate::AppState;
 use tauri::State;
 
 #[tauri::command]
 pub async fn contest_create(
     state: State<'_, AppState>,
-    new_contest: NewContest,
+    mut new_contest: NewContest,
 ) -> Result<Contest, AppError> {
     tracing::info!("Creating new contest: {:?}", new_contest);
+
+    // Resolve the federation profile; it supplies the formula and age rule unless chosen
+    let profile_id = match new_contest.federation_profile.take() {
+        Some(id) => id,
+        None => {
+            let settings = state.settings.lock().await;
+            settings
+                .get_settings()
+                .competition
+                .default_federation
+                .clone()
+        }
+    };
+    let profile = state.federations.get(&profile_id)?;
+    new_contest.federation_profile = Some(profile.id.clone());
+    new_contest
+        .scoring_formula
+        .get_or_insert(profile.scoring_formula);
+    new_contest.age_rule.get_or_insert(profile.age_rule);
+
     let db_guard = state.db.lock().await;
     let pool = &*db_guard;
 
+    // Make the profile's classes and categories available to registrations
+    category_queries::sync_profile_categories(pool, profile).await?;
+    if let Some(class_set) = &new_contest.weight_class_set {
+        ensure_weight_class_set(pool, class_set).await?;
+    }
+    if let Some(team_scoring) = &new_contest.team_scoring {
+        ensure_team_scoring(team_scoring)?;
+    }
+    if let Some(best_lifter) = &new_contest.best_lifter {
+        ensure_best_lifter(best_lifter)?;
+    }
+
     // Create the contest
     let contest = contest_queries::create_contest(pool, new_contest).await?;
     tracing::info!("Successfully created contest with ID: {}", contest.id);
@@ -37,9 +75,64 @@ pub async fn contest_create(
         }
     }
 
+    // Apply the profile's bar weights
+    if let Err(e) = plate_set_queries::update_contest_bar_weights(
+        pool,
+        &contest.id,
+        profile.bar_weights.men,
+        profile.bar_weights.women,
+    )
+    .await
+    {
+        tracing::warn!(
+            "Failed to apply {} bar weights to contest {}: {}",
+            profile.id,
+            contest.id,
+            e
+        );
+    }
+
     Ok(contest)
 }
 
+/// Reject weight class sets with no classes defined
+async fn ensure_weight_class_set(
+    pool: &sqlx::Pool<sqlx::Sqlite>,
+    class_set: &str,
+) -> Result<(), AppError> {
+    let sets = category_queries::get_weight_class_sets(pool).await?;
+    if sets.iter().any(|set| set == class_set) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput {
+            field: "weight_class_set".to_string(),
+            reason: format!("Unknown weight class set {}", class_set),
+        })
+    }
+}
+
+/// Reject team scoring in which no lifter counts
+fn ensure_team_scoring(team_scoring: &TeamScoring) -> Result<(), AppError> {
+    if team_scoring.top_n == 0 {
+        return Err(AppError::InvalidInput {
+            field: "team_scoring.top_n".to_string(),
+            reason: "At least one lifter per club must count".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Reject best lifter awards that need no entrants
+fn ensure_best_lifter(best_lifter: &BestLifterAwards) -> Result<(), AppError> {
+    if best_lifter.min_entrants == 0 {
+        return Err(AppError::InvalidInput {
+            field: "best_lifter.min_entrants".to_string(),
+            reason: "An award group needs at least one entrant".to_string(),
+        });
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn contest_list(state: State<'_, AppState>) -> Result<Vec<Contest>, AppError> {
     tracing::info!("Fetching all contests");
@@ -76,11 +169,54 @@ pub async fn contest_update(
     tracing::info!("Updating contest with ID: {}", contest_id);
     let db_guard = state.db.lock().await;
     let pool = &*db_guard;
-    let updated_contest = contest_queries::update_contest(pool, &contest_id, contest).await?;
+    ensure_weight_class_set(pool, &contest.weight_class_set).await?;
+    ensure_team_scoring(&contest.team_scoring)?;
+    ensure_best_lifter(&contest.best_lifter)?;
+    // The contest is only saved if every registration can be re-derived under its new rules
+    let mut tx = pool.begin().await?;
+    let previous = contest_queries::get_contest_by_id(&mut *tx, &contest_id)
+        .await?
+        .ok_or_else(|| AppError::ContestNotFound {
+            id: contest_id.clone(),
+        })?;
+    let updated_contest = contest_queries::update_contest(&mut *tx, &contest_id, contest).await?;
+
+    // The date and rules feed coefficients and categories of every registration
+    if previous.date != updated_contest.date
+        || previous.scoring_formula != updated_contest.scoring_formula
+        || previous.age_rule != updated_contest.age_rule
+        || previous.weight_class_set != updated_contest.weight_class_set
+    {
+        recalculation::recalculate_contest(&mut *tx, &state.federations, &contest_id).await?;
+    } else if previous.tie_breaks != updated_contest.tie_breaks
+        || previous.ranking != updated_contest.ranking
+    {
+        results_queries::update_all_rankings(&mut *tx, &contest_id).await?;
+    }
+    tx.commit().await?;
     tracing::info!("Successfully updated contest with ID: {}", contest_id);
+
     Ok(updated_contest)
 }
 
+#[tauri::command]
+pub async fn contest_recalculate_coefficients(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<Registration>, AppError> {
+    tracing::info!("Recalculating coefficients for contest: {}", contest_id);
+    let db_guard = state.db.lock().await;
+    let pool = &*db_guard;
+    let registrations =
+        recalculation::recalculate_contest(pool, &state.federations, &contest_id).await?;
+    tracing::info!(
+        "Recalculated {} registrations for contest {}",
+        registrations.len(),
+        contest_id
+    );
+    Ok(registrations.into_iter().map(Registration::from).collect())
+}
+
 #[tauri::command]
 pub async fn contest_delete(
     state: State<'_, AppState>,
@@ -93,3 +229,29 @@ pub async fn contest_delete(
     tracing::info!("Successfully deleted contest with ID: {}", contest_id);
     Ok(())
 }
+
+#[tauri::command]
+pub async fn contest_list_scoring_formulas() -> Result<Vec<ScoringFormulaInfo>, AppError> {
+    tracing::info!("Listing scoring formulas");
+    Ok(scoring::formulas()
+        .iter()
+        .map(|formula| ScoringFormulaInfo::from(*formula))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn federation_list_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<FederationProfile>, AppError> {
+    tracing::info!("Listing federation profiles");
+    Ok(state.federations.profiles().to_vec())
+}
+
+#[tauri::command]
+pub async fn federation_get_profile(
+    state: State<'_, AppState>,
+    profile_id: String,
+) -> Result<FederationProfile, AppError> {
+    tracing::info!("Getting federation profile: {}", profile_id);
+    Ok(state.federations.get(&profile_id)?.clone())
+}
@@ -0,0 +1,294 @@
+use crate::database::queries;
+use crate::database::queries::divisions::RegistrationPlacing;
+use crate::error::AppError;
+use crate::models::contest::RankingBasis;
+use crate::models::division::{Division, NewDivision};
+use crate::models::registration::Equipment;
+use crate::AppState;
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeSet;
+use tauri::State;
+
+#[tauri::command]
+pub async fn division_list(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<Division>, AppError> {
+    tracing::info!("division_list called for contest: {}", contest_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::divisions::get_divisions_by_contest(db_pool, &contest_id).await?)
+}
+
+#[tauri::command]
+pub async fn division_create(
+    state: State<'_, AppState>,
+    division: NewDivision,
+) -> Result<Division, AppError> {
+    tracing::info!(
+        "division_create called for contest: {}, name: {}",
+        division.contest_id,
+        division.name
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    ensure_division(db_pool, &division).await?;
+    let created = queries::divisions::create_division(db_pool, &division).await?;
+    queries::results::update_all_rankings(db_pool, &division.contest_id).await?;
+    Ok(created)
+}
+
+/// Create a division for every combination of age category, weight class and equipment
+/// entered in the contest, for medals per weight class within each age group.
+/// Divisions that already exist by name are kept.
+#[tauri::command]
+pub async fn division_create_age_weight(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<Division>, AppError> {
+    tracing::info!(
+        "division_create_age_weight called for contest: {}",
+        contest_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    let combinations: BTreeSet<(String, String, Equipment)> =
+        queries::registrations::get_registrations_by_contest(db_pool, &contest_id)
+            .await?
+            .into_iter()
+            .map(|registration| {
+                let equipment = registration.equipment();
+                (
+                    registration.age_category_id,
+                    registration.weight_class_id,
+                    equipment,
+                )
+            })
+            .collect();
+    let existing: BTreeSet<String> =
+        queries::divisions::get_divisions_by_contest(db_pool, &contest_id)
+            .await?
+            .into_iter()
+            .map(|division| division.name)
+            .collect();
+
+    let mut created = Vec::new();
+    for (age_category_id, weight_class_id, equipment) in combinations {
+        let age_category =
+            queries::categories::get_age_category_by_id(db_pool, &age_category_id).await?;
+        let weight_class =
+            queries::categories::get_weight_class_by_id(db_pool, &weight_class_id).await?;
+        let (Some(age_category), Some(weight_class)) = (age_category, weight_class) else {
+            continue;
+        };
+
+        let mut name = format!("{} {}", age_category.name, weight_class.name);
+        if equipment != Equipment::Raw {
+            name = format!("{} {}", name, equipment);
+        }
+        if existing.contains(&name) {
+            continue;
+        }
+
+        let division = NewDivision {
+            contest_id: contest_id.clone(),
+            name,
+            gender: Some(weight_class.gender),
+            age_category_id: Some(age_category_id),
+            weight_class_id: Some(weight_class_id),
+            equipment: Some(equipment),
+            basis: RankingBasis::Total,
+        };
+        created.push(queries::divisions::create_division(db_pool, &division).await?);
+    }
+
+    queries::results::update_all_rankings(db_pool, &contest_id).await?;
+    tracing::info!(
+        "Created {} divisions for contest {}",
+        created.len(),
+        contest_id
+    );
+    Ok(created)
+}
+
+#[tauri::command]
+pub async fn division_delete(
+    state: State<'_, AppState>,
+    division_id: String,
+) -> Result<(), AppError> {
+    tracing::info!("division_delete called for division: {}", division_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    // Placings of the division are removed with it
+    queries::divisions::delete_division(db_pool, &division_id).await?;
+    Ok(())
+}
+
+/// Enter a registration in a further division, e.g. a master in the senior open. The same
+/// attempts are scored in every division the lifter is ranked in.
+#[tauri::command]
+pub async fn division_enter(
+    state: State<'_, AppState>,
+    registration_id: String,
+    division_id: String,
+) -> Result<(), AppError> {
+    tracing::info!(
+        "division_enter called for registration: {}, division: {}",
+        registration_id,
+        division_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    let registration =
+        queries::registrations::get_registration_by_id(db_pool, &registration_id).await?;
+    let division = queries::divisions::get_division_by_id(db_pool, &division_id)
+        .await?
+        .filter(|division| division.contest_id == registration.contest_id)
+        .ok_or_else(|| AppError::InvalidInput {
+            field: "division_id".to_string(),
+            reason: format!("Unknown division {}", division_id),
+        })?;
+    let competitor =
+        queries::competitors::get_competitor_by_id(db_pool, &registration.competitor_id).await?;
+    if !division.admits(
+        &competitor.gender,
+        &registration.weight_class_id,
+        registration.equipment(),
+    ) {
+        return Err(AppError::InvalidInput {
+            field: "division_id".to_string(),
+            reason: format!(
+                "Division {} is for another sex, weight class or equipment",
+                division.name
+            ),
+        });
+    }
+
+    queries::divisions::add_division_entry(db_pool, &division.id, &registration.id).await?;
+    queries::results::update_all_rankings(db_pool, &registration.contest_id).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn division_withdraw(
+    state: State<'_, AppState>,
+    registration_id: String,
+    division_id: String,
+) -> Result<(), AppError> {
+    tracing::info!(
+        "division_withdraw called for registration: {}, division: {}",
+        registration_id,
+        division_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    let registration =
+        queries::registrations::get_registration_by_id(db_pool, &registration_id).await?;
+    queries::divisions::remove_division_entry(db_pool, &division_id, &registration.id).await?;
+    queries::results::update_all_rankings(db_pool, &registration.contest_id).await?;
+    Ok(())
+}
+
+/// Divisions a registration was entered in
+#[tauri::command]
+pub async fn division_list_entered(
+    state: State<'_, AppState>,
+    registration_id: String,
+) -> Result<Vec<Division>, AppError> {
+    tracing::info!(
+        "division_list_entered called for registration: {}",
+        registration_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::divisions::get_entered_divisions(db_pool, &registration_id).await?)
+}
+
+/// Places of a registration in every division it is ranked in
+#[tauri::command]
+pub async fn division_get_placings(
+    state: State<'_, AppState>,
+    registration_id: String,
+) -> Result<Vec<RegistrationPlacing>, AppError> {
+    tracing::info!(
+        "division_get_placings called for registration: {}",
+        registration_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::divisions::get_placings_by_registration(db_pool, &registration_id).await?)
+}
+
+/// Reject divisions whose filters name unknown or contradicting categories
+async fn ensure_division(pool: &Pool<Sqlite>, division: &NewDivision) -> Result<(), AppError> {
+    if division.name.trim().is_empty() {
+        return Err(AppError::InvalidInput {
+            field: "name".to_string(),
+            reason: "Division name is required".to_string(),
+        });
+    }
+    if queries::contests::get_contest_by_id(pool, &division.contest_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::ContestNotFound {
+            id: division.contest_id.clone(),
+        });
+    }
+    let taken = queries::divisions::get_divisions_by_contest(pool, &division.contest_id)
+        .await?
+        .iter()
+        .any(|existing| existing.name == division.name.trim());
+    if taken {
+        return Err(AppError::InvalidInput {
+            field: "name".to_string(),
+            reason: format!("Division {} already exists", division.name.trim()),
+        });
+    }
+    if let Some(gender) = &division.gender {
+        if gender != "Male" && gender != "Female" {
+            return Err(AppError::InvalidInput {
+                field: "gender".to_string(),
+                reason: format!("Unknown gender {}", gender),
+            });
+        }
+    }
+    if let Some(age_category_id) = &division.age_category_id {
+        if queries::categories::get_age_category_by_id(pool, age_category_id)
+            .await?
+            .is_none()
+        {
+            return Err(AppError::InvalidInput {
+                field: "age_category_id".to_string(),
+                reason: format!("Unknown age category {}", age_category_id),
+            });
+        }
+    }
+    if let Some(weight_class_id) = &division.weight_class_id {
+        let class = queries::categories::get_weight_class_by_id(pool, weight_class_id)
+            .await?
+            .ok_or_else(|| AppError::InvalidInput {
+                field: "weight_class_id".to_string(),
+                reason: format!("Unknown weight class {}", weight_class_id),
+            })?;
+        if division
+            .gender
+            .as_ref()
+            .is_some_and(|gender| *gender != class.gender)
+        {
+            return Err(AppError::InvalidInput {
+                field: "gender".to_string(),
+                reason: format!("Weight class {} is for {} lifters", class.id, class.gender),
+            });
+        }
+    }
+    Ok(())
+}
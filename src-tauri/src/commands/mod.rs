@@ -3,7 +3,9 @@ pub mod categories;
 pub mod competitors;
 pub mod contest_state;
 pub mod contests;
+pub mod divisions;
 pub mod plate_sets;
+pub mod records;
 pub mod registrations;
 pub mod results;
 pub mod settings;
@@ -16,7 +18,9 @@ pub use categories::*;
 pub use competitors::*;
 pub use contest_state::*;
 pub use contests::*;
+pub use divisions::*;
 pub use plate_sets::*;
+pub use records::*;
 pub use registrations::*;
 pub use results::*;
 pub use settings::*;
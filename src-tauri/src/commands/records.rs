@@ -0,0 +1,80 @@
+use crate::database::queries;
+use crate::error::AppError;
+use crate::models::record::{Record, RecordEntry, RecordScope};
+use crate::records;
+use crate::AppState;
+use tauri::State;
+
+#[tauri::command]
+pub async fn record_list(
+    state: State<'_, AppState>,
+    scope: Option<RecordScope>,
+) -> Result<Vec<Record>, AppError> {
+    tracing::info!("record_list called with scope: {:?}", scope);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::records::get_current_records(db_pool, scope).await?)
+}
+
+#[tauri::command]
+pub async fn record_list_for_contest(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<Record>, AppError> {
+    tracing::info!("record_list_for_contest called for contest: {}", contest_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::records::get_records_by_contest(db_pool, &contest_id).await?)
+}
+
+/// Import a record table given as a JSON array of records
+#[tauri::command]
+pub async fn record_import(
+    state: State<'_, AppState>,
+    records_json: String,
+) -> Result<usize, AppError> {
+    tracing::info!("record_import called");
+    let entries: Vec<RecordEntry> =
+        serde_json::from_str(&records_json).map_err(|e| AppError::InvalidInput {
+            field: "records_json".to_string(),
+            reason: format!("Invalid record table: {}", e),
+        })?;
+
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    let imported = records::import_records(db_pool, &entries).await?;
+    tracing::info!("Imported {} records", imported);
+    Ok(imported)
+}
+
+/// Export the current records as a JSON record table, in the format `record_import` reads
+#[tauri::command]
+pub async fn record_export(
+    state: State<'_, AppState>,
+    scope: Option<RecordScope>,
+) -> Result<String, AppError> {
+    tracing::info!("record_export called with scope: {:?}", scope);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    let entries: Vec<RecordEntry> = queries::records::get_current_records(db_pool, scope)
+        .await?
+        .into_iter()
+        .map(RecordEntry::from)
+        .collect();
+    serde_json::to_string_pretty(&entries)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize records to JSON: {}", e)))
+}
+
+#[tauri::command]
+pub async fn record_delete(state: State<'_, AppState>, record_id: String) -> Result<(), AppError> {
+    tracing::info!("record_delete called for record: {}", record_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    queries::records::delete_record(db_pool, &record_id).await?;
+    Ok(())
+}
@@ -6,7 +6,12 @@ use tauri::State;
 // the authoritative results endpoints; the Tauri commands remain for
 // historical builds but are not actively maintained.
 
+use crate::best_lifter::BestLifterAward;
 use crate::database::queries;
+use crate::export;
+use crate::models::division::{AGE_CLASS_DIVISION, OPEN_DIVISION, WEIGHT_CLASS_DIVISION};
+use crate::models::registration::Equipment;
+use crate::team_ranking::TeamRanking;
 
 #[tauri::command]
 pub async fn result_calculate(
@@ -32,51 +37,59 @@ pub async fn result_calculate(
     Ok(())
 }
 
-use crate::database::queries::results::CompetitionResult;
-
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub enum RankingType {
-    Open,
-    Age,
-    Weight,
-}
+use crate::database::queries::divisions::DivisionRanking;
+use crate::database::queries::rankings::RankingRow;
+use crate::database::queries::results::{
+    CompetitionResult, ResultStatusAction, ResultStatusLogEntry,
+};
 
+/// Get the ranking of a division: one of the built-in rankings (`open`, `age`, `weight`)
+/// or the ID of a named division of the contest
 #[tauri::command]
 pub async fn result_get_rankings(
     state: State<'_, AppState>,
     contest_id: String,
-    ranking_type: RankingType,
-) -> Result<Vec<CompetitionResult>, AppError> {
+    division_id: String,
+    // Only lifters of this equipment division; all divisions when not given
+    equipment: Option<Equipment>,
+) -> Result<Vec<RankingRow>, AppError> {
     tracing::info!(
-        "result_get_rankings called for contest: {}, type: {}",
+        "result_get_rankings called for contest: {}, division: {}, equipment: {:?}",
         contest_id,
-        ranking_type.to_string()
+        division_id,
+        equipment
     );
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
 
-    let results = match ranking_type {
-        RankingType::Open => queries::results::get_open_ranking(db_pool, &contest_id).await?,
-        RankingType::Age => queries::results::get_age_class_ranking(db_pool, &contest_id).await?,
-        RankingType::Weight => {
-            queries::results::get_weight_class_ranking(db_pool, &contest_id).await?
-        }
-    };
+    let builtin = [OPEN_DIVISION, AGE_CLASS_DIVISION, WEIGHT_CLASS_DIVISION];
+    if !builtin.contains(&division_id.as_str()) {
+        queries::divisions::get_division_by_id(db_pool, &division_id)
+            .await?
+            .filter(|division| division.contest_id == contest_id)
+            .ok_or_else(|| AppError::InvalidInput {
+                field: "division_id".to_string(),
+                reason: format!("Unknown division {}", division_id),
+            })?;
+    }
+    let placings =
+        queries::divisions::get_division_ranking(db_pool, &contest_id, &division_id, equipment)
+            .await?;
 
-    Ok(results)
+    Ok(queries::rankings::get_ranking_rows(db_pool, &contest_id, placings).await?)
 }
 
-use std::fmt;
+/// Podium (places 1 to 3) of every named division of a contest
+#[tauri::command]
+pub async fn result_get_podiums(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<DivisionRanking>, AppError> {
+    tracing::info!("result_get_podiums called for contest: {}", contest_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
 
-impl fmt::Display for RankingType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            RankingType::Open => write!(f, "open"),
-            RankingType::Age => write!(f, "age"),
-            RankingType::Weight => write!(f, "weight"),
-        }
-    }
+    Ok(queries::divisions::get_division_rankings(db_pool, &contest_id, Some(3)).await?)
 }
 
 #[tauri::command]
@@ -103,11 +116,14 @@ pub async fn result_get_competitor_results(
     }
 }
 
+/// Export a contest's results. CSV and JSON are returned as text; an XLSX workbook or PDF
+/// protocol is written to `path`, which is returned.
 #[tauri::command]
 pub async fn result_export(
     state: State<'_, AppState>,
     contest_id: String,
-    format: String, // "csv", "json"
+    format: String, // "csv", "json", "xlsx", "pdf"
+    path: Option<String>,
 ) -> Result<String, AppError> {
     tracing::info!(
         "result_export called for contest: {}, format: {}",
@@ -117,58 +133,186 @@ pub async fn result_export(
     let db_pool = state.db.lock().await;
     let db_pool = &*db_pool;
 
+    let data = export::load_export_data(db_pool, &contest_id).await?;
+
     match format.as_str() {
-        "csv" => {
-            let rankings = queries::results::get_open_ranking(db_pool, &contest_id).await?;
-            export_to_csv(rankings).await
+        "csv" => export::export_to_csv(&data).await,
+        "json" => export::export_to_json(&data).await,
+        "xlsx" => {
+            let path = path.ok_or_else(|| AppError::InvalidInput {
+                field: "path".to_string(),
+                reason: "An XLSX export needs a file path".to_string(),
+            })?;
+            export::xlsx::save_workbook(&data, &path)?;
+            Ok(path)
         }
-        "json" => {
-            let rankings = queries::results::get_open_ranking(db_pool, &contest_id).await?;
-            export_to_json(rankings).await
+        "pdf" => {
+            let path = path.ok_or_else(|| AppError::InvalidInput {
+                field: "path".to_string(),
+                reason: "A PDF protocol needs a file path".to_string(),
+            })?;
+            export::pdf::save_protocol(&data, &path)?;
+            Ok(path)
         }
         _ => Err(AppError::InvalidInput {
             field: "format".to_string(),
             reason: format!(
-                "Unsupported export format: {}. Supported formats: csv, json",
+                "Unsupported export format: {}. Supported formats: csv, json, xlsx, pdf",
                 format
             ),
         }),
     }
 }
 
-async fn export_to_csv(rankings: Vec<CompetitionResult>) -> Result<String, AppError> {
-    let mut csv_content = String::new();
+/// Disqualify, mark as DNF or reinstate a lifter, then recompute the contest's rankings
+async fn apply_result_status(
+    db_pool: &sqlx::Pool<sqlx::Sqlite>,
+    registration_id: &str,
+    action: ResultStatusAction,
+    reason: Option<String>,
+) -> Result<CompetitionResult, AppError> {
+    let reason = reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason.is_none() && action != ResultStatusAction::Reinstate {
+        return Err(AppError::InvalidInput {
+            field: "reason".to_string(),
+            reason: format!("A reason is required to {}", action),
+        });
+    }
+
+    // The logged decision, the status and the recomputed rankings are stored together
+    let mut tx = db_pool.begin().await?;
+    // Lifters can be disqualified before any result was calculated for them
+    if !queries::results::result_exists(&mut *tx, registration_id).await? {
+        queries::results::calculate_results(&mut *tx, registration_id).await?;
+    }
 
-    // Header
-    csv_content.push_str(
-        "Place,Registration ID,Best Squat,Best Bench,Best Deadlift,Total,Coefficient Points\n",
+    let result =
+        queries::results::set_result_status(&mut *tx, registration_id, action, reason.as_deref())
+            .await?;
+    queries::results::update_all_rankings(&mut *tx, &result.contest_id).await?;
+    let result = queries::results::get_result_by_registration(&mut *tx, registration_id).await?;
+    tx.commit().await?;
+    tracing::info!(
+        "{} applied to registration {}: {:?}",
+        action,
+        registration_id,
+        reason
     );
 
-    // Data rows
-    for result in rankings {
-        csv_content.push_str(&format!(
-            "{},{},{:.1},{:.1},{:.1},{:.1},{:.2}\n",
-            result.place_open.unwrap_or(0),
-            result.registration_id,
-            result.best_squat.unwrap_or(0.0),
-            result.best_bench.unwrap_or(0.0),
-            result.best_deadlift.unwrap_or(0.0),
-            result.total_weight,
-            result.coefficient_points
-        ));
-    }
+    Ok(result)
+}
 
-    Ok(csv_content)
+#[tauri::command]
+pub async fn result_disqualify(
+    state: State<'_, AppState>,
+    registration_id: String,
+    reason: String,
+) -> Result<CompetitionResult, AppError> {
+    tracing::info!(
+        "result_disqualify called for registration: {}",
+        registration_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    apply_result_status(
+        db_pool,
+        &registration_id,
+        ResultStatusAction::Disqualify,
+        Some(reason),
+    )
+    .await
 }
 
-async fn export_to_json(rankings: Vec<CompetitionResult>) -> Result<String, AppError> {
-    serde_json::to_string_pretty(&rankings)
-        .map_err(|e| AppError::Internal(format!("Failed to serialize results to JSON: {}", e)))
+#[tauri::command]
+pub async fn result_mark_dnf(
+    state: State<'_, AppState>,
+    registration_id: String,
+    reason: String,
+) -> Result<CompetitionResult, AppError> {
+    tracing::info!(
+        "result_mark_dnf called for registration: {}",
+        registration_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    apply_result_status(
+        db_pool,
+        &registration_id,
+        ResultStatusAction::DidNotFinish,
+        Some(reason),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn result_reinstate(
+    state: State<'_, AppState>,
+    registration_id: String,
+    reason: Option<String>,
+) -> Result<CompetitionResult, AppError> {
+    tracing::info!(
+        "result_reinstate called for registration: {}",
+        registration_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    apply_result_status(
+        db_pool,
+        &registration_id,
+        ResultStatusAction::Reinstate,
+        reason,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn result_get_status_log(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<ResultStatusLogEntry>, AppError> {
+    tracing::info!("result_get_status_log called for contest: {}", contest_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::results::get_result_status_log(db_pool, &contest_id).await?)
+}
+
+#[tauri::command]
+pub async fn result_get_team_rankings(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<TeamRanking>, AppError> {
+    tracing::info!(
+        "result_get_team_rankings called for contest: {}",
+        contest_id
+    );
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::results::get_team_rankings(db_pool, &contest_id).await?)
+}
+
+/// Best lifter of every award group of a contest
+#[tauri::command]
+pub async fn result_get_best_lifters(
+    state: State<'_, AppState>,
+    contest_id: String,
+) -> Result<Vec<BestLifterAward>, AppError> {
+    tracing::info!("result_get_best_lifters called for contest: {}", contest_id);
+    let db_pool = state.db.lock().await;
+    let db_pool = &*db_pool;
+
+    Ok(queries::results::get_best_lifter_awards(db_pool, &contest_id).await?)
 }
 
 #[derive(serde::Serialize)]
 pub struct ScoreboardData {
-    pub rankings: Vec<CompetitionResult>,
+    pub rankings: Vec<RankingRow>,
     pub total_competitors: usize,
     pub contest_name: Option<String>,
     pub updated_at: String,
@@ -184,7 +328,9 @@ pub async fn result_get_scoreboard(
     let db_pool = &*db_pool;
 
     // Get current rankings (open ranking by default for scoreboard)
-    let rankings = queries::results::get_open_ranking(db_pool, &contest_id).await?;
+    let rankings =
+        queries::divisions::get_division_ranking(db_pool, &contest_id, OPEN_DIVISION, None).await?;
+    let rankings = queries::rankings::get_ranking_rows(db_pool, &contest_id, rankings).await?;
     let total_competitors = rankings.len();
 
     // Get contest name
@@ -58,7 +58,7 @@ impl Database {
 
     /// Get contest by ID
     pub async fn get_contest(&self, contest_id: &str) -> Result<Option<Contest>, sqlx::Error> {
-        contests::get_contest_by_id(&self.pool, contest_id).await
+        contests::get_contest_by_id(&*self.pool, contest_id).await
     }
 
     /// Get all contests (including archived)
@@ -78,7 +78,7 @@ impl Database {
         contest_id: &str,
         contest: Contest,
     ) -> Result<Contest, sqlx::Error> {
-        contests::update_contest(&self.pool, contest_id, contest).await
+        contests::update_contest(&*self.pool, contest_id, contest).await
     }
 
     /// Archive contest (soft delete)
@@ -10,11 +10,12 @@ use sqlx::{Pool, Sqlite};
 
 use crate::database::queries::{
     attempts::upsert_attempt_weight,
+    categories::find_weight_class,
     competitors::{create_competitor, Competitor, CreateCompetitorRequest},
     contests::create_contest,
     registrations::{create_registration, CreateRegistrationRequest},
 };
-use crate::models::contest::{Discipline, NewContest};
+use crate::models::contest::{AgeRule, Discipline, NewContest, ScoringFormulaKind};
 
 /// Demo competitor data with realistic Polish names and stats
 struct DemoCompetitor {
@@ -186,6 +187,14 @@ pub async fn generate_demo_data(
             competition_type: Some("Regional Championship".to_string()),
             organizer: Some("Demo Organizer".to_string()),
             notes: Some("Generated demo competition with 10 competitors".to_string()),
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         },
     )
     .await?;
@@ -229,7 +238,14 @@ pub async fn generate_demo_data(
                 competitor_id: competitor.id.clone(),
                 bodyweight: demo_competitor.bodyweight,
                 age_category_id: get_age_category_for_competitor(pool, &competitor).await?,
-                weight_class_id: get_weight_class_for_competitor(pool, &competitor).await?,
+                weight_class_id: get_weight_class_for_competitor(
+                    pool,
+                    &contest.weight_class_set,
+                    &competitor,
+                    demo_competitor.bodyweight,
+                )
+                .await?,
+                weight_class_manual: false,
                 equipment_m: true, // Raw powerlifting
                 equipment_sm: false,
                 equipment_t: false,
@@ -237,6 +253,9 @@ pub async fn generate_demo_data(
                 personal_record_at_entry: None,
                 reshel_coefficient: None,
                 mccullough_coefficient: None,
+                scoring_coefficient: None,
+                age_at_contest: None,
+                age_rule: Some(AgeRule::ExactDate),
                 rack_height_squat: Some(if demo_competitor.gender == "Male" {
                     12
                 } else {
@@ -369,23 +388,14 @@ async fn get_age_category_for_competitor(
 /// Get appropriate weight class for competitor
 async fn get_weight_class_for_competitor(
     pool: &Pool<Sqlite>,
+    class_set: &str,
     competitor: &Competitor,
+    bodyweight: f64,
 ) -> Result<String, sqlx::Error> {
-    // For registration, we need the bodyweight, but we don't have it in competitor model
-    // We'll use a default approach and find appropriate class during registration
-    let weight_class_id: String = sqlx::query_scalar(
-        r#"
-        SELECT id FROM weight_classes 
-        WHERE gender = ?
-        ORDER BY weight_max ASC
-        LIMIT 1
-        "#,
-    )
-    .bind(&competitor.gender)
-    .fetch_one(pool)
-    .await?;
-
-    Ok(weight_class_id)
+    find_weight_class(pool, class_set, &competitor.gender, bodyweight)
+        .await?
+        .map(|class| class.id)
+        .ok_or(sqlx::Error::RowNotFound)
 }
 
 /// Create 3 attempts for each lift (squat, bench, deadlift) for a competitor
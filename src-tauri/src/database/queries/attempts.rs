@@ -1,5 +1,6 @@
+use crate::models::attempt::RECORD_ATTEMPT_NUMBER;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Row, Sqlite};
+use sqlx::{Executor, FromRow, Pool, Row, Sqlite};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Attempt {
@@ -32,21 +33,25 @@ pub struct CreateAttemptRequest {
 
 /// Upsert an attempt's weight.
 /// Creates a new attempt if one doesn't exist for the given registration, lift type, and attempt number.
-/// Otherwise, it updates the weight of the existing attempt.
-pub async fn upsert_attempt_weight(
-    pool: &Pool<Sqlite>,
+/// Otherwise, it updates the weight of the existing attempt. Returns the attempt's ID.
+pub async fn upsert_attempt_weight<'e, E>(
+    executor: E,
     registration_id: &str,
     lift_type: &str,
     attempt_number: i32,
     weight: f64,
-) -> Result<(), sqlx::Error> {
+) -> Result<String, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let id = uuid::Uuid::new_v4().to_string();
-    sqlx::query(
+    sqlx::query_scalar(
         r#"
         INSERT INTO attempts (id, registration_id, lift_type, attempt_number, weight, status)
         VALUES (?1, ?2, ?3, ?4, ?5, 'Pending')
         ON CONFLICT(registration_id, lift_type, attempt_number) DO UPDATE SET
         weight = excluded.weight
+        RETURNING id
         "#,
     )
     .bind(&id)
@@ -54,10 +59,8 @@ pub async fn upsert_attempt_weight(
     .bind(lift_type)
     .bind(attempt_number)
     .bind(weight)
-    .execute(pool)
-    .await?;
-
-    Ok(())
+    .fetch_one(executor)
+    .await
 }
 
 /// Record a new attempt
@@ -185,14 +188,17 @@ pub async fn get_attempts_by_lift_type(
 }
 
 /// Update attempt status and judge decisions
-pub async fn update_attempt_result(
-    pool: &Pool<Sqlite>,
+pub async fn update_attempt_result<'e, E>(
+    executor: E,
     attempt_id: &str,
     status: &str,
     judge1: Option<bool>,
     judge2: Option<bool>,
     judge3: Option<bool>,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     sqlx::query(
         r#"
         UPDATE attempts 
@@ -205,28 +211,33 @@ pub async fn update_attempt_result(
     .bind(judge2)
     .bind(judge3)
     .bind(attempt_id)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
-/// Get best successful attempt for a lift type
-pub async fn get_best_attempt(
-    pool: &Pool<Sqlite>,
+/// Get best successful attempt for a lift type, leaving out 4th (record) attempts
+pub async fn get_best_attempt<'e, E>(
+    executor: E,
     registration_id: &str,
     lift_type: &str,
-) -> Result<Option<f64>, sqlx::Error> {
+) -> Result<Option<f64>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let row = sqlx::query(
         r#"
         SELECT MAX(weight) as best_weight
         FROM attempts 
         WHERE registration_id = ?1 AND lift_type = ?2 AND status = 'Successful'
+        AND attempt_number < ?3
         "#,
     )
     .bind(registration_id)
     .bind(lift_type)
-    .fetch_one(pool)
+    .bind(RECORD_ATTEMPT_NUMBER)
+    .fetch_one(executor)
     .await?;
 
     row.try_get("best_weight")
@@ -273,13 +284,16 @@ pub async fn get_contest_attempts(
 }
 
 /// Get a single attempt by its ID
-pub async fn get_attempt_by_id(
-    pool: &Pool<Sqlite>,
+pub async fn get_attempt_by_id<'e, E>(
+    executor: E,
     attempt_id: &str,
-) -> Result<Option<Attempt>, sqlx::Error> {
+) -> Result<Option<Attempt>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     sqlx::query_as::<_, Attempt>("SELECT * FROM attempts WHERE id = ?")
         .bind(attempt_id)
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await
 }
 
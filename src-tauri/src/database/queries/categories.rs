@@ -1,26 +1,102 @@
+use crate::federation::FederationProfile;
 use crate::models::category::{AgeCategory, WeightClass};
-use sqlx::{Pool, Sqlite};
+use sqlx::{Executor, Pool, Sqlite};
 
-pub async fn get_weight_classes(pool: &Pool<Sqlite>) -> Result<Vec<WeightClass>, sqlx::Error> {
+pub async fn get_weight_classes(
+    pool: &Pool<Sqlite>,
+    class_set: Option<&str>,
+) -> Result<Vec<WeightClass>, sqlx::Error> {
     let weight_classes = sqlx::query_as::<_, WeightClass>(
         r#"
-        SELECT id, gender, name, weight_min, weight_max 
+        SELECT id, class_set, gender, name, weight_min, weight_max 
         FROM weight_classes 
-        ORDER BY gender, weight_max ASC NULLS LAST
+        WHERE ?1 IS NULL OR class_set = ?1
+        ORDER BY class_set, gender, weight_max ASC NULLS LAST
         "#,
     )
+    .bind(class_set)
     .fetch_all(pool)
     .await?;
 
     Ok(weight_classes)
 }
 
+/// Get the names of all weight class sets
+pub async fn get_weight_class_sets(pool: &Pool<Sqlite>) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT DISTINCT class_set FROM weight_classes ORDER BY class_set")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn get_weight_class_by_id<'e, E>(
+    executor: E,
+    weight_class_id: &str,
+) -> Result<Option<WeightClass>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, WeightClass>(
+        r#"
+        SELECT id, class_set, gender, name, weight_min, weight_max 
+        FROM weight_classes 
+        WHERE id = ?1
+        "#,
+    )
+    .bind(weight_class_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Find the class of a set covering a bodyweight for the lifter's gender
+pub async fn find_weight_class<'e, E>(
+    executor: E,
+    class_set: &str,
+    gender: &str,
+    bodyweight: f64,
+) -> Result<Option<WeightClass>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, WeightClass>(
+        r#"
+        SELECT id, class_set, gender, name, weight_min, weight_max 
+        FROM weight_classes 
+        WHERE class_set = ?1 AND gender = ?2
+        AND (weight_min IS NULL OR ?3 >= weight_min)
+        AND (weight_max IS NULL OR ?3 <= weight_max)
+        ORDER BY weight_max ASC NULLS LAST
+        LIMIT 1
+        "#,
+    )
+    .bind(class_set)
+    .bind(gender)
+    .bind(bodyweight)
+    .fetch_optional(executor)
+    .await
+}
+
+pub async fn get_age_category_by_id(
+    pool: &Pool<Sqlite>,
+    age_category_id: &str,
+) -> Result<Option<AgeCategory>, sqlx::Error> {
+    sqlx::query_as::<_, AgeCategory>(
+        r#"
+        SELECT id, category_set, name, min_age, max_age 
+        FROM age_categories 
+        WHERE id = ?1
+        "#,
+    )
+    .bind(age_category_id)
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn get_age_categories(pool: &Pool<Sqlite>) -> Result<Vec<AgeCategory>, sqlx::Error> {
     let age_categories = sqlx::query_as::<_, AgeCategory>(
         r#"
-        SELECT id, name, min_age, max_age 
+        SELECT id, category_set, name, min_age, max_age 
         FROM age_categories 
-        ORDER BY min_age ASC NULLS FIRST
+        ORDER BY category_set, min_age ASC NULLS FIRST
         "#,
     )
     .fetch_all(pool)
@@ -28,3 +104,59 @@ pub async fn get_age_categories(pool: &Pool<Sqlite>) -> Result<Vec<AgeCategory>,
 
     Ok(age_categories)
 }
+
+/// Store a federation profile's weight classes and age categories so registrations can
+/// reference them. Existing rows with the same ID are updated to the profile's definition.
+pub async fn sync_profile_categories(
+    pool: &Pool<Sqlite>,
+    profile: &FederationProfile,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    for class in &profile.weight_classes {
+        sqlx::query(
+            r#"
+            INSERT INTO weight_classes (id, class_set, gender, name, weight_min, weight_max)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            ON CONFLICT(id) DO UPDATE SET
+                class_set = excluded.class_set,
+                gender = excluded.gender,
+                name = excluded.name,
+                weight_min = excluded.weight_min,
+                weight_max = excluded.weight_max
+            "#,
+        )
+        .bind(&class.id)
+        .bind(&profile.id)
+        .bind(&class.gender)
+        .bind(&class.name)
+        .bind(class.weight_min)
+        .bind(class.weight_max)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for category in &profile.age_categories {
+        sqlx::query(
+            r#"
+            INSERT INTO age_categories (id, category_set, name, min_age, max_age)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(id) DO UPDATE SET
+                category_set = excluded.category_set,
+                name = excluded.name,
+                min_age = excluded.min_age,
+                max_age = excluded.max_age
+            "#,
+        )
+        .bind(&category.id)
+        .bind(&profile.id)
+        .bind(&category.name)
+        .bind(category.min_age)
+        .bind(category.max_age)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::database::process_competitor_photo;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Sqlite};
+use sqlx::{Executor, FromRow, Pool, Sqlite};
 
 #[derive(Debug, thiserror::Error)]
 pub enum PhotoProcessError {
@@ -134,10 +134,13 @@ pub async fn create_competitor(
 }
 
 /// Get competitor by ID
-pub async fn get_competitor_by_id(
-    pool: &Pool<Sqlite>,
+pub async fn get_competitor_by_id<'e, E>(
+    executor: E,
     competitor_id: &str,
-) -> Result<Competitor, sqlx::Error> {
+) -> Result<Competitor, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     sqlx::query_as!(
         Competitor,
         r#"SELECT 
@@ -158,7 +161,7 @@ pub async fn get_competitor_by_id(
         FROM competitors WHERE id = ?"#,
         competitor_id
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
@@ -188,11 +191,14 @@ pub async fn get_all_competitors(pool: &Pool<Sqlite>) -> Result<Vec<Competitor>,
 }
 
 /// Update competitor
-pub async fn update_competitor(
-    pool: &Pool<Sqlite>,
+pub async fn update_competitor<'e, E>(
+    executor: E,
     competitor_id: &str,
     request: CreateCompetitorRequest,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     // Process photo if provided - propagate errors instead of silent failure
     let (photo_data, photo_format, photo_metadata) = process_photo_for_storage(
         request.photo_base64.as_deref(),
@@ -218,7 +224,7 @@ pub async fn update_competitor(
         photo_metadata,
         competitor_id
     )
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
@@ -1,6 +1,11 @@
-use crate::models::contest::{Contest, ContestStatus, Discipline, NewContest};
+use crate::federation::DEFAULT_FEDERATION;
+use crate::models::contest::{
+    AgeRule, BestLifterAwards, Contest, ContestStatus, Discipline, NewContest, RankingBasis,
+    RankingConfig, RankingRule, RankingSplit, ScoringFormulaKind, TeamScoring, TieBreak,
+    DEFAULT_TIE_BREAKS,
+};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Sqlite};
+use sqlx::{Acquire, Executor, FromRow, Pool, Sqlite};
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -17,6 +22,21 @@ pub struct DbContest {
     pub competition_type: Option<String>,
     pub organizer: Option<String>,
     pub notes: Option<String>,
+    pub scoring_formula: String, // SQLite stores as TEXT
+    pub federation_profile: String,
+    pub weight_class_set: String,
+    pub age_rule: String,   // SQLite stores as TEXT
+    pub tie_breaks: String, // Comma-separated TieBreak names
+    pub team_top_n: i64,
+    pub team_place_points: String, // Comma-separated points per place
+    pub open_ranking_basis: String,
+    pub open_ranking_split: String, // Comma-separated RankingSplit names
+    pub age_class_ranking_basis: String,
+    pub age_class_ranking_split: String,
+    pub weight_class_ranking_basis: String,
+    pub weight_class_ranking_split: String,
+    pub best_lifter_split: String, // Comma-separated RankingSplit names
+    pub best_lifter_min_entrants: i64,
     pub is_archived: bool,
     pub created_at: String,
     pub updated_at: String,
@@ -38,6 +58,35 @@ impl From<DbContest> for Contest {
             competition_type: db_contest.competition_type,
             organizer: db_contest.organizer,
             notes: db_contest.notes,
+            scoring_formula: ScoringFormulaKind::from_str(&db_contest.scoring_formula)
+                .unwrap_or_default(),
+            federation_profile: db_contest.federation_profile,
+            weight_class_set: db_contest.weight_class_set,
+            age_rule: AgeRule::from_str(&db_contest.age_rule).unwrap_or_default(),
+            tie_breaks: parse_tie_breaks(&db_contest.tie_breaks),
+            team_scoring: TeamScoring {
+                top_n: u32::try_from(db_contest.team_top_n).unwrap_or_default(),
+                place_points: parse_place_points(&db_contest.team_place_points),
+            },
+            ranking: RankingConfig {
+                open: parse_ranking_rule(
+                    &db_contest.open_ranking_basis,
+                    &db_contest.open_ranking_split,
+                ),
+                age_class: parse_ranking_rule(
+                    &db_contest.age_class_ranking_basis,
+                    &db_contest.age_class_ranking_split,
+                ),
+                weight_class: parse_ranking_rule(
+                    &db_contest.weight_class_ranking_basis,
+                    &db_contest.weight_class_ranking_split,
+                ),
+            },
+            best_lifter: BestLifterAwards {
+                split_by: parse_ranking_split(&db_contest.best_lifter_split),
+                min_entrants: u32::try_from(db_contest.best_lifter_min_entrants)
+                    .unwrap_or_default(),
+            },
             is_archived: db_contest.is_archived,
             created_at: db_contest.created_at,
             updated_at: db_contest.updated_at,
@@ -45,6 +94,58 @@ impl From<DbContest> for Contest {
     }
 }
 
+fn parse_tie_breaks(value: &str) -> Vec<TieBreak> {
+    value
+        .split(',')
+        .filter_map(|name| TieBreak::from_str(name.trim()).ok())
+        .collect()
+}
+
+fn format_tie_breaks(tie_breaks: &[TieBreak]) -> String {
+    tie_breaks
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_place_points(value: &str) -> Vec<u32> {
+    value
+        .split(',')
+        .filter_map(|points| points.trim().parse().ok())
+        .collect()
+}
+
+fn format_place_points(place_points: &[u32]) -> String {
+    place_points
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn parse_ranking_rule(basis: &str, split: &str) -> RankingRule {
+    RankingRule {
+        basis: RankingBasis::from_str(basis).unwrap_or(RankingBasis::Points),
+        split_by: parse_ranking_split(split),
+    }
+}
+
+fn parse_ranking_split(value: &str) -> Vec<RankingSplit> {
+    value
+        .split(',')
+        .filter_map(|name| RankingSplit::from_str(name.trim()).ok())
+        .collect()
+}
+
+fn format_ranking_split(split_by: &[RankingSplit]) -> String {
+    split_by
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
 /// Create a new contest and return the created record.
 pub async fn create_contest(
     pool: &Pool<Sqlite>,
@@ -52,11 +153,38 @@ pub async fn create_contest(
 ) -> Result<Contest, sqlx::Error> {
     let id = Uuid::new_v4().to_string();
     let discipline_str = new_contest.discipline.to_string();
+    let scoring_formula_str = new_contest.scoring_formula.unwrap_or_default().to_string();
+    let federation_profile = new_contest
+        .federation_profile
+        .unwrap_or_else(|| DEFAULT_FEDERATION.to_string());
+    let weight_class_set = new_contest
+        .weight_class_set
+        .unwrap_or_else(|| federation_profile.clone());
+    let age_rule_str = new_contest.age_rule.unwrap_or_default().to_string();
+    let tie_breaks_str = format_tie_breaks(
+        new_contest
+            .tie_breaks
+            .as_deref()
+            .unwrap_or(&DEFAULT_TIE_BREAKS),
+    );
+    let team_scoring = new_contest.team_scoring.unwrap_or_default();
+    let team_top_n = i64::from(team_scoring.top_n);
+    let team_place_points = format_place_points(&team_scoring.place_points);
+    let ranking = new_contest.ranking.unwrap_or_default();
+    let open_ranking_basis = ranking.open.basis.to_string();
+    let open_ranking_split = format_ranking_split(&ranking.open.split_by);
+    let age_class_ranking_basis = ranking.age_class.basis.to_string();
+    let age_class_ranking_split = format_ranking_split(&ranking.age_class.split_by);
+    let weight_class_ranking_basis = ranking.weight_class.basis.to_string();
+    let weight_class_ranking_split = format_ranking_split(&ranking.weight_class.split_by);
+    let best_lifter = new_contest.best_lifter.unwrap_or_default();
+    let best_lifter_split = format_ranking_split(&best_lifter.split_by);
+    let best_lifter_min_entrants = i64::from(best_lifter.min_entrants);
 
     sqlx::query!(
         r#"
-        INSERT INTO contests (id, name, date, location, discipline, federation_rules, competition_type, organizer, notes)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO contests (id, name, date, location, discipline, federation_rules, competition_type, organizer, notes, scoring_formula, federation_profile, weight_class_set, age_rule, tie_breaks, team_top_n, team_place_points, open_ranking_basis, open_ranking_split, age_class_ranking_basis, age_class_ranking_split, weight_class_ranking_basis, weight_class_ranking_split, best_lifter_split, best_lifter_min_entrants)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         id,
         new_contest.name,
@@ -66,7 +194,22 @@ pub async fn create_contest(
         new_contest.federation_rules,
         new_contest.competition_type,
         new_contest.organizer,
-        new_contest.notes
+        new_contest.notes,
+        scoring_formula_str,
+        federation_profile,
+        weight_class_set,
+        age_rule_str,
+        tie_breaks_str,
+        team_top_n,
+        team_place_points,
+        open_ranking_basis,
+        open_ranking_split,
+        age_class_ranking_basis,
+        age_class_ranking_split,
+        weight_class_ranking_basis,
+        weight_class_ranking_split,
+        best_lifter_split,
+        best_lifter_min_entrants
     )
     .execute(pool)
     .await?;
@@ -77,10 +220,13 @@ pub async fn create_contest(
 }
 
 /// Get a single contest by its ID.
-pub async fn get_contest_by_id(
-    pool: &Pool<Sqlite>,
+pub async fn get_contest_by_id<'e, E>(
+    executor: E,
     contest_id: &str,
-) -> Result<Option<Contest>, sqlx::Error> {
+) -> Result<Option<Contest>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let db_contest = sqlx::query_as!(
         DbContest,
         r#"
@@ -95,6 +241,21 @@ pub async fn get_contest_by_id(
             competition_type, 
             organizer, 
             notes, 
+            scoring_formula as "scoring_formula!", 
+            federation_profile as "federation_profile!", 
+            weight_class_set as "weight_class_set!", 
+            age_rule as "age_rule!", 
+            tie_breaks as "tie_breaks!", 
+            team_top_n as "team_top_n!", 
+            team_place_points as "team_place_points!", 
+            open_ranking_basis as "open_ranking_basis!", 
+            open_ranking_split as "open_ranking_split!", 
+            age_class_ranking_basis as "age_class_ranking_basis!", 
+            age_class_ranking_split as "age_class_ranking_split!", 
+            weight_class_ranking_basis as "weight_class_ranking_basis!", 
+            weight_class_ranking_split as "weight_class_ranking_split!", 
+            best_lifter_split as "best_lifter_split!", 
+            best_lifter_min_entrants as "best_lifter_min_entrants!", 
             is_archived as "is_archived!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
@@ -103,7 +264,7 @@ pub async fn get_contest_by_id(
         "#,
         contest_id
     )
-    .fetch_optional(pool)
+    .fetch_optional(executor)
     .await?;
 
     Ok(db_contest.map(|c| c.into()))
@@ -125,6 +286,21 @@ pub async fn get_all_contests(pool: &Pool<Sqlite>) -> Result<Vec<Contest>, sqlx:
             competition_type, 
             organizer, 
             notes, 
+            scoring_formula as "scoring_formula!", 
+            federation_profile as "federation_profile!", 
+            weight_class_set as "weight_class_set!", 
+            age_rule as "age_rule!", 
+            tie_breaks as "tie_breaks!", 
+            team_top_n as "team_top_n!", 
+            team_place_points as "team_place_points!", 
+            open_ranking_basis as "open_ranking_basis!", 
+            open_ranking_split as "open_ranking_split!", 
+            age_class_ranking_basis as "age_class_ranking_basis!", 
+            age_class_ranking_split as "age_class_ranking_split!", 
+            weight_class_ranking_basis as "weight_class_ranking_basis!", 
+            weight_class_ranking_split as "weight_class_ranking_split!", 
+            best_lifter_split as "best_lifter_split!", 
+            best_lifter_min_entrants as "best_lifter_min_entrants!", 
             is_archived as "is_archived!", 
             created_at as "created_at!", 
             updated_at as "updated_at!"
@@ -139,18 +315,35 @@ pub async fn get_all_contests(pool: &Pool<Sqlite>) -> Result<Vec<Contest>, sqlx:
 }
 
 /// Update an existing contest.
-pub async fn update_contest(
-    pool: &Pool<Sqlite>,
+pub async fn update_contest<'a, A>(
+    conn: A,
     contest_id: &str,
     contest: Contest,
-) -> Result<Contest, sqlx::Error> {
+) -> Result<Contest, sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
     let discipline_str = contest.discipline.to_string();
     let status_str = contest.status.to_string();
+    let scoring_formula_str = contest.scoring_formula.to_string();
+    let age_rule_str = contest.age_rule.to_string();
+    let tie_breaks_str = format_tie_breaks(&contest.tie_breaks);
+    let team_top_n = i64::from(contest.team_scoring.top_n);
+    let team_place_points = format_place_points(&contest.team_scoring.place_points);
+    let open_ranking_basis = contest.ranking.open.basis.to_string();
+    let open_ranking_split = format_ranking_split(&contest.ranking.open.split_by);
+    let age_class_ranking_basis = contest.ranking.age_class.basis.to_string();
+    let age_class_ranking_split = format_ranking_split(&contest.ranking.age_class.split_by);
+    let weight_class_ranking_basis = contest.ranking.weight_class.basis.to_string();
+    let weight_class_ranking_split = format_ranking_split(&contest.ranking.weight_class.split_by);
+    let best_lifter_split = format_ranking_split(&contest.best_lifter.split_by);
+    let best_lifter_min_entrants = i64::from(contest.best_lifter.min_entrants);
 
     sqlx::query!(
         r#"
         UPDATE contests
-        SET name = ?, date = ?, location = ?, discipline = ?, status = ?, federation_rules = ?, competition_type = ?, organizer = ?, notes = ?, is_archived = ?
+        SET name = ?, date = ?, location = ?, discipline = ?, status = ?, federation_rules = ?, competition_type = ?, organizer = ?, notes = ?, scoring_formula = ?, weight_class_set = ?, age_rule = ?, tie_breaks = ?, team_top_n = ?, team_place_points = ?, open_ranking_basis = ?, open_ranking_split = ?, age_class_ranking_basis = ?, age_class_ranking_split = ?, weight_class_ranking_basis = ?, weight_class_ranking_split = ?, best_lifter_split = ?, best_lifter_min_entrants = ?, is_archived = ?
         WHERE id = ?
         "#,
         contest.name,
@@ -162,13 +355,27 @@ pub async fn update_contest(
         contest.competition_type,
         contest.organizer,
         contest.notes,
+        scoring_formula_str,
+        contest.weight_class_set,
+        age_rule_str,
+        tie_breaks_str,
+        team_top_n,
+        team_place_points,
+        open_ranking_basis,
+        open_ranking_split,
+        age_class_ranking_basis,
+        age_class_ranking_split,
+        weight_class_ranking_basis,
+        weight_class_ranking_split,
+        best_lifter_split,
+        best_lifter_min_entrants,
         contest.is_archived,
         contest_id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
-    get_contest_by_id(pool, contest_id)
+    get_contest_by_id(&mut *conn, contest_id)
         .await?
         .ok_or_else(|| sqlx::Error::RowNotFound)
 }
@@ -0,0 +1,248 @@
+use crate::database::queries::rankings::{self, RankingRow};
+use crate::database::queries::results::CompetitionResult;
+use crate::models::division::{Division, NewDivision};
+use crate::models::registration::Equipment;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, FromRow, Pool, Sqlite};
+
+const DIVISION_COLUMNS: &str = r#"
+    id, contest_id, name, gender, age_category_id, weight_class_id, equipment, basis, created_at
+"#;
+
+/// A result with its place in one ranking
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DivisionPlacing {
+    pub place: Option<i64>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub result: CompetitionResult,
+}
+
+/// Placings of one named division
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivisionRanking {
+    pub division: Division,
+    pub placings: Vec<RankingRow>,
+}
+
+/// Place of a registration in one of the divisions it is ranked in
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RegistrationPlacing {
+    pub division_id: String,
+    pub division_name: String,
+    pub place: i64,
+}
+
+pub async fn create_division(
+    pool: &Pool<Sqlite>,
+    division: &NewDivision,
+) -> Result<Division, sqlx::Error> {
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query_as::<_, Division>(&format!(
+        r#"
+        INSERT INTO divisions
+        (id, contest_id, name, gender, age_category_id, weight_class_id, equipment, basis)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        RETURNING {}
+        "#,
+        DIVISION_COLUMNS
+    ))
+    .bind(&id)
+    .bind(&division.contest_id)
+    .bind(division.name.trim())
+    .bind(&division.gender)
+    .bind(&division.age_category_id)
+    .bind(&division.weight_class_id)
+    .bind(division.equipment)
+    .bind(division.basis)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_division_by_id(
+    pool: &Pool<Sqlite>,
+    division_id: &str,
+) -> Result<Option<Division>, sqlx::Error> {
+    sqlx::query_as::<_, Division>(&format!(
+        "SELECT {} FROM divisions WHERE id = ?1",
+        DIVISION_COLUMNS
+    ))
+    .bind(division_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_divisions_by_contest<'e, E>(
+    executor: E,
+    contest_id: &str,
+) -> Result<Vec<Division>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, Division>(&format!(
+        "SELECT {} FROM divisions WHERE contest_id = ?1 ORDER BY name",
+        DIVISION_COLUMNS
+    ))
+    .bind(contest_id)
+    .fetch_all(executor)
+    .await
+}
+
+pub async fn delete_division(pool: &Pool<Sqlite>, division_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM divisions WHERE id = ?1")
+        .bind(division_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Get the ranked results of a division in order of placing: one of the built-in divisions
+/// or a named division of the contest, optionally only the lifters of one equipment.
+/// Built-in divisions place each equipment on its own and list them one after another.
+pub async fn get_division_ranking(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+    division_id: &str,
+    equipment: Option<Equipment>,
+) -> Result<Vec<DivisionPlacing>, sqlx::Error> {
+    sqlx::query_as::<_, DivisionPlacing>(
+        r#"
+        SELECT division_placings.place, ranked_results.*
+        FROM division_placings
+        JOIN ranked_results ON ranked_results.id = division_placings.result_id
+        JOIN (
+            -- Equipment::from_flags
+            SELECT
+                id as reg_id,
+                CASE WHEN equipment_m THEN 'MultiPly'
+                     WHEN equipment_sm OR equipment_t THEN 'SinglePly'
+                     ELSE 'Raw' END as equipment,
+                CASE WHEN equipment_m THEN 2 WHEN equipment_sm OR equipment_t THEN 1 ELSE 0 END
+                    as sort_order
+            FROM registrations
+        ) division ON division.reg_id = ranked_results.registration_id
+        LEFT JOIN divisions ON divisions.id = division_placings.division_id
+        WHERE division_placings.division_id = ?1 AND ranked_results.contest_id = ?2
+        AND (?3 IS NULL OR division.equipment = ?3)
+        ORDER BY CASE WHEN divisions.id IS NULL THEN division.sort_order ELSE 0 END,
+                 division_placings.place, ranked_results.id
+        "#,
+    )
+    .bind(division_id)
+    .bind(contest_id)
+    .bind(equipment)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the rankings of every named division of a contest, optionally only down to a place
+/// (e.g. 3 for the podiums)
+pub async fn get_division_rankings(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+    max_place: Option<i64>,
+) -> Result<Vec<DivisionRanking>, sqlx::Error> {
+    let mut rankings = Vec::new();
+    for division in get_divisions_by_contest(pool, contest_id).await? {
+        let mut placings = get_division_ranking(pool, contest_id, &division.id, None).await?;
+        if let Some(max_place) = max_place {
+            placings.retain(|placing| placing.place.is_some_and(|place| place <= max_place));
+        }
+        let placings = rankings::get_ranking_rows(pool, contest_id, placings).await?;
+        rankings.push(DivisionRanking { division, placings });
+    }
+    Ok(rankings)
+}
+
+/// Get the places of a registration in every named division it is ranked in
+pub async fn get_placings_by_registration(
+    pool: &Pool<Sqlite>,
+    registration_id: &str,
+) -> Result<Vec<RegistrationPlacing>, sqlx::Error> {
+    sqlx::query_as::<_, RegistrationPlacing>(
+        r#"
+        SELECT divisions.id as division_id, divisions.name as division_name,
+               division_placings.place
+        FROM division_placings
+        JOIN divisions ON divisions.id = division_placings.division_id
+        JOIN results ON results.id = division_placings.result_id
+        WHERE results.registration_id = ?1
+        ORDER BY divisions.name
+        "#,
+    )
+    .bind(registration_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Enter a registration in a division it does not match by age category
+pub async fn add_division_entry(
+    pool: &Pool<Sqlite>,
+    division_id: &str,
+    registration_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT OR IGNORE INTO division_entries (division_id, registration_id) VALUES (?1, ?2)",
+    )
+    .bind(division_id)
+    .bind(registration_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn remove_division_entry(
+    pool: &Pool<Sqlite>,
+    division_id: &str,
+    registration_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM division_entries WHERE division_id = ?1 AND registration_id = ?2")
+        .bind(division_id)
+        .bind(registration_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Get the divisions a registration was entered in
+pub async fn get_entered_divisions(
+    pool: &Pool<Sqlite>,
+    registration_id: &str,
+) -> Result<Vec<Division>, sqlx::Error> {
+    sqlx::query_as::<_, Division>(&format!(
+        r#"
+        SELECT {}
+        FROM divisions
+        WHERE id IN (SELECT division_id FROM division_entries WHERE registration_id = ?1)
+        ORDER BY name
+        "#,
+        DIVISION_COLUMNS
+    ))
+    .bind(registration_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the (division ID, registration ID) entries of a contest's divisions
+pub async fn get_division_entries_by_contest<'e, E>(
+    executor: E,
+    contest_id: &str,
+) -> Result<Vec<(String, String)>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT division_entries.division_id, division_entries.registration_id
+        FROM division_entries
+        JOIN divisions ON divisions.id = division_entries.division_id
+        WHERE divisions.contest_id = ?1
+        "#,
+    )
+    .bind(contest_id)
+    .fetch_all(executor)
+    .await
+}
@@ -3,7 +3,10 @@ pub mod categories;
 pub mod competitors;
 pub mod contest_states;
 pub mod contests;
+pub mod divisions;
 pub mod plate_sets;
+pub mod rankings;
+pub mod records;
 pub mod registrations;
 pub mod results;
 
@@ -15,6 +18,9 @@ pub use attempts::*;
 pub use categories::*;
 pub use competitors::*;
 pub use contests::*;
+pub use divisions::*;
 pub use plate_sets::*;
+pub use rankings::*;
+pub use records::*;
 pub use registrations::*;
 pub use results::*;
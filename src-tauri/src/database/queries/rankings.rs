@@ -0,0 +1,189 @@
+use crate::database::queries::divisions::DivisionPlacing;
+use crate::database::queries::results::CompetitionResult;
+use crate::models::attempt::{AttemptStatus, LiftType};
+use crate::models::registration::Equipment;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, Sqlite};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// An attempt of a lifter as listed in a ranking
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingAttempt {
+    pub lift_type: LiftType,
+    pub attempt_number: i32,
+    pub weight: f64,
+    pub status: AttemptStatus,
+}
+
+/// A result in a ranking together with the lifter's details and attempts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingRow {
+    pub place: Option<i64>,
+    pub first_name: String,
+    pub last_name: String,
+    pub club: Option<String>,
+    pub city: Option<String>,
+    pub gender: String,
+    pub birth_year: Option<i32>,
+    pub bodyweight: f64,
+    pub equipment: Equipment,
+    pub lot_number: Option<String>,
+    pub weight_class_id: String,
+    pub weight_class_name: String,
+    pub age_category_id: String,
+    pub age_category_name: String,
+    pub reshel_coefficient: Option<f64>,
+    pub mccullough_coefficient: Option<f64>,
+    pub scoring_coefficient: Option<f64>,
+    // Every attempt by lift and number, 4th (record) attempts included
+    pub attempts: Vec<RankingAttempt>,
+    #[serde(flatten)]
+    pub result: CompetitionResult,
+}
+
+impl RankingRow {
+    pub fn name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+
+    /// The lifter's attempt of a lift with the given number, if declared
+    pub fn attempt(&self, lift_type: &LiftType, attempt_number: i32) -> Option<&RankingAttempt> {
+        self.attempts.iter().find(|attempt| {
+            &attempt.lift_type == lift_type && attempt.attempt_number == attempt_number
+        })
+    }
+}
+
+/// Lifter details of a registration
+#[derive(Clone, FromRow)]
+struct LifterDetails {
+    registration_id: String,
+    first_name: String,
+    last_name: String,
+    club: Option<String>,
+    city: Option<String>,
+    gender: String,
+    birth_date: String,
+    bodyweight: f64,
+    equipment_m: bool,
+    equipment_sm: bool,
+    equipment_t: bool,
+    lot_number: Option<String>,
+    weight_class_id: String,
+    weight_class_name: String,
+    age_category_id: String,
+    age_category_name: String,
+    reshel_coefficient: Option<f64>,
+    mccullough_coefficient: Option<f64>,
+    scoring_coefficient: Option<f64>,
+}
+
+#[derive(FromRow)]
+struct AttemptRow {
+    registration_id: String,
+    lift_type: String,
+    attempt_number: i32,
+    weight: f64,
+    status: String,
+}
+
+/// Add the lifter details and attempts to the placings of a contest's ranking
+pub async fn get_ranking_rows(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+    placings: Vec<DivisionPlacing>,
+) -> Result<Vec<RankingRow>, sqlx::Error> {
+    let lifters: HashMap<String, LifterDetails> = sqlx::query_as::<_, LifterDetails>(
+        r#"
+        SELECT r.id as registration_id, c.first_name, c.last_name, c.club, c.city, c.gender,
+               c.birth_date, r.bodyweight, r.equipment_m, r.equipment_sm, r.equipment_t,
+               r.lot_number, r.weight_class_id, wc.name as weight_class_name,
+               r.age_category_id, ac.name as age_category_name,
+               r.reshel_coefficient, r.mccullough_coefficient, r.scoring_coefficient
+        FROM registrations r
+        JOIN competitors c ON c.id = r.competitor_id
+        JOIN weight_classes wc ON wc.id = r.weight_class_id
+        JOIN age_categories ac ON ac.id = r.age_category_id
+        WHERE r.contest_id = ?1
+        "#,
+    )
+    .bind(contest_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|lifter| (lifter.registration_id.clone(), lifter))
+    .collect();
+
+    let mut attempts: HashMap<String, Vec<RankingAttempt>> = HashMap::new();
+    for row in sqlx::query_as::<_, AttemptRow>(
+        r#"
+        SELECT a.registration_id, a.lift_type, a.attempt_number, a.weight, a.status
+        FROM attempts a
+        JOIN registrations r ON r.id = a.registration_id
+        WHERE r.contest_id = ?1
+        ORDER BY CASE a.lift_type WHEN 'Squat' THEN 1 WHEN 'Bench' THEN 2 ELSE 3 END,
+                 a.attempt_number
+        "#,
+    )
+    .bind(contest_id)
+    .fetch_all(pool)
+    .await?
+    {
+        let attempt = RankingAttempt {
+            lift_type: LiftType::from_str(&row.lift_type)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            attempt_number: row.attempt_number,
+            weight: row.weight,
+            status: AttemptStatus::from_str(&row.status)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+        };
+        attempts
+            .entry(row.registration_id)
+            .or_default()
+            .push(attempt);
+    }
+
+    placings
+        .into_iter()
+        .map(|placing| {
+            let lifter = lifters
+                .get(&placing.result.registration_id)
+                .cloned()
+                .ok_or(sqlx::Error::RowNotFound)?;
+            Ok(RankingRow {
+                place: placing.place,
+                birth_year: lifter
+                    .birth_date
+                    .get(..4)
+                    .and_then(|year| year.parse().ok()),
+                equipment: Equipment::from_flags(
+                    lifter.equipment_m,
+                    lifter.equipment_sm,
+                    lifter.equipment_t,
+                ),
+                attempts: attempts
+                    .get(&placing.result.registration_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                first_name: lifter.first_name,
+                last_name: lifter.last_name,
+                club: lifter.club,
+                city: lifter.city,
+                gender: lifter.gender,
+                bodyweight: lifter.bodyweight,
+                lot_number: lifter.lot_number,
+                weight_class_id: lifter.weight_class_id,
+                weight_class_name: lifter.weight_class_name,
+                age_category_id: lifter.age_category_id,
+                age_category_name: lifter.age_category_name,
+                reshel_coefficient: lifter.reshel_coefficient,
+                mccullough_coefficient: lifter.mccullough_coefficient,
+                scoring_coefficient: lifter.scoring_coefficient,
+                result: placing.result,
+            })
+        })
+        .collect()
+}
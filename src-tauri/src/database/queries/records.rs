@@ -0,0 +1,201 @@
+use crate::models::record::{Record, RecordEntry, RecordLift, RecordScope};
+use crate::models::registration::Equipment;
+use sqlx::{Executor, Pool, Sqlite};
+
+const RECORD_COLUMNS: &str = r#"
+    id, scope, scope_name, gender, weight_class_id, age_category_id, equipment, lift,
+    weight, holder_name, set_on, competitor_id, contest_id, attempt_id, created_at
+"#;
+
+/// Category a record is held in, apart from its scope and lift
+#[derive(Debug, Clone)]
+pub struct RecordCategory<'a> {
+    pub gender: &'a str,
+    pub weight_class_id: &'a str,
+    pub age_category_id: &'a str,
+    pub equipment: Equipment,
+}
+
+/// Contest attempt that set a record
+#[derive(Debug, Clone)]
+pub struct RecordSource<'a> {
+    pub competitor_id: &'a str,
+    pub contest_id: &'a str,
+    pub attempt_id: &'a str,
+}
+
+/// Store a record, optionally linked to the contest attempt that set it
+pub async fn insert_record<'e, E>(
+    executor: E,
+    entry: &RecordEntry,
+    source: Option<RecordSource<'_>>,
+) -> Result<Record, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query_as::<_, Record>(&format!(
+        r#"
+        INSERT INTO records
+        (id, scope, scope_name, gender, weight_class_id, age_category_id, equipment, lift,
+         weight, holder_name, set_on, competitor_id, contest_id, attempt_id)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+        RETURNING {}
+        "#,
+        RECORD_COLUMNS
+    ))
+    .bind(&id)
+    .bind(entry.scope)
+    .bind(&entry.scope_name)
+    .bind(&entry.gender)
+    .bind(&entry.weight_class_id)
+    .bind(&entry.age_category_id)
+    .bind(entry.equipment)
+    .bind(entry.lift)
+    .bind(entry.weight)
+    .bind(&entry.holder_name)
+    .bind(&entry.set_on)
+    .bind(source.as_ref().map(|source| source.competitor_id))
+    .bind(source.as_ref().map(|source| source.contest_id))
+    .bind(source.as_ref().map(|source| source.attempt_id))
+    .fetch_one(executor)
+    .await
+}
+
+/// Import a record table; either every entry is stored or none
+pub async fn import_records(
+    pool: &Pool<Sqlite>,
+    entries: &[RecordEntry],
+) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for entry in entries {
+        sqlx::query(
+            r#"
+            INSERT INTO records
+            (id, scope, scope_name, gender, weight_class_id, age_category_id, equipment, lift,
+             weight, holder_name, set_on)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+        )
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(entry.scope)
+        .bind(&entry.scope_name)
+        .bind(&entry.gender)
+        .bind(&entry.weight_class_id)
+        .bind(&entry.age_category_id)
+        .bind(entry.equipment)
+        .bind(entry.lift)
+        .bind(entry.weight)
+        .bind(&entry.holder_name)
+        .bind(&entry.set_on)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(entries.len())
+}
+
+/// Get the current records, i.e. the heaviest entry of every record category.
+/// An equal weight does not break a record, so the earlier entry holds it.
+pub async fn get_current_records(
+    pool: &Pool<Sqlite>,
+    scope: Option<RecordScope>,
+) -> Result<Vec<Record>, sqlx::Error> {
+    sqlx::query_as::<_, Record>(&format!(
+        r#"
+        SELECT {}
+        FROM (
+            SELECT *, ROW_NUMBER() OVER (
+                PARTITION BY scope, IFNULL(scope_name, ''), gender, weight_class_id,
+                             age_category_id, equipment, lift
+                ORDER BY weight DESC, set_on, created_at, rowid
+            ) as position
+            FROM records
+            WHERE ?1 IS NULL OR scope = ?1
+        )
+        WHERE position = 1
+        ORDER BY scope, scope_name, gender, weight_class_id, age_category_id, equipment, lift
+        "#,
+        RECORD_COLUMNS
+    ))
+    .bind(scope)
+    .fetch_all(pool)
+    .await
+}
+
+/// Get the current record of one category and lift, leaving out any record set by
+/// `exclude_attempt_id`
+pub async fn get_current_record<'e, E>(
+    executor: E,
+    scope: RecordScope,
+    scope_name: Option<&str>,
+    category: &RecordCategory<'_>,
+    lift: RecordLift,
+    exclude_attempt_id: Option<&str>,
+) -> Result<Option<Record>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_as::<_, Record>(&format!(
+        r#"
+        SELECT {}
+        FROM records
+        WHERE scope = ?1 AND IFNULL(scope_name, '') = IFNULL(?2, '')
+        AND gender = ?3 AND weight_class_id = ?4 AND age_category_id = ?5
+        AND equipment = ?6 AND lift = ?7 AND (?8 IS NULL OR attempt_id IS NOT ?8)
+        ORDER BY weight DESC, set_on, created_at, rowid
+        LIMIT 1
+        "#,
+        RECORD_COLUMNS
+    ))
+    .bind(scope)
+    .bind(scope_name)
+    .bind(category.gender)
+    .bind(category.weight_class_id)
+    .bind(category.age_category_id)
+    .bind(category.equipment)
+    .bind(lift)
+    .bind(exclude_attempt_id)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Get the records set in a contest, in the order they were set
+pub async fn get_records_by_contest(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+) -> Result<Vec<Record>, sqlx::Error> {
+    sqlx::query_as::<_, Record>(&format!(
+        "SELECT {} FROM records WHERE contest_id = ?1 ORDER BY created_at, rowid",
+        RECORD_COLUMNS
+    ))
+    .bind(contest_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Remove the records set by an attempt, e.g. after the decision was reversed
+pub async fn delete_records_by_attempt<'e, E>(
+    executor: E,
+    attempt_id: &str,
+) -> Result<u64, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let result = sqlx::query("DELETE FROM records WHERE attempt_id = ?1")
+        .bind(attempt_id)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_record(pool: &Pool<Sqlite>, record_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM records WHERE id = ?1")
+        .bind(record_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
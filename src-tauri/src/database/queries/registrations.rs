@@ -1,5 +1,17 @@
+use crate::models::contest::AgeRule;
+use crate::models::registration::{Equipment, Registration as RegistrationModel};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Row, Sqlite};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Executor, FromRow, Pool, Row, Sqlite};
+
+/// Columns selected for every registration query, in `map_registration_row` order
+const REGISTRATION_COLUMNS: &str = r#"
+    id, contest_id, competitor_id, age_category_id, weight_class_id, weight_class_manual,
+    equipment_m, equipment_sm, equipment_t, bodyweight, lot_number,
+    personal_record_at_entry, reshel_coefficient, mccullough_coefficient,
+    scoring_coefficient, age_at_contest, age_rule, rack_height_squat, rack_height_bench,
+    created_at
+"#;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Registration {
@@ -8,6 +20,8 @@ pub struct Registration {
     pub competitor_id: String,
     pub age_category_id: String,
     pub weight_class_id: String,
+    // Chosen by hand rather than assigned from the bodyweight
+    pub weight_class_manual: bool,
     // Equipment flags
     pub equipment_m: bool,
     pub equipment_sm: bool,
@@ -19,18 +33,85 @@ pub struct Registration {
     // Calculated coefficients
     pub reshel_coefficient: Option<f64>,
     pub mccullough_coefficient: Option<f64>,
+    // Coefficient of the contest's scoring formula
+    pub scoring_coefficient: Option<f64>,
+    // Age in full years the McCullough factor was computed from
+    pub age_at_contest: Option<u32>,
+    // Rule the age category was assigned by; None when chosen by hand
+    pub age_rule: Option<AgeRule>,
     // Rack heights
     pub rack_height_squat: Option<i32>,
     pub rack_height_bench: Option<i32>,
     pub created_at: String,
 }
 
+impl Registration {
+    pub fn equipment(&self) -> Equipment {
+        Equipment::from_flags(self.equipment_m, self.equipment_sm, self.equipment_t)
+    }
+}
+
+// Convert database registration to model registration
+impl From<Registration> for RegistrationModel {
+    fn from(registration: Registration) -> Self {
+        RegistrationModel {
+            id: registration.id,
+            contest_id: registration.contest_id,
+            competitor_id: registration.competitor_id,
+            age_category_id: registration.age_category_id,
+            weight_class_id: registration.weight_class_id,
+            weight_class_manual: registration.weight_class_manual,
+            equipment_m: registration.equipment_m,
+            equipment_sm: registration.equipment_sm,
+            equipment_t: registration.equipment_t,
+            bodyweight: registration.bodyweight,
+            lot_number: registration.lot_number,
+            personal_record_at_entry: registration.personal_record_at_entry,
+            reshel_coefficient: registration.reshel_coefficient,
+            mccullough_coefficient: registration.mccullough_coefficient,
+            scoring_coefficient: registration.scoring_coefficient,
+            age_at_contest: registration.age_at_contest,
+            age_rule: registration.age_rule,
+            rack_height_squat: registration.rack_height_squat,
+            rack_height_bench: registration.rack_height_bench,
+            created_at: registration.created_at,
+        }
+    }
+}
+
+fn map_registration_row(row: &SqliteRow) -> Result<Registration, sqlx::Error> {
+    Ok(Registration {
+        id: row.try_get("id")?,
+        contest_id: row.try_get("contest_id")?,
+        competitor_id: row.try_get("competitor_id")?,
+        age_category_id: row.try_get("age_category_id")?,
+        weight_class_id: row.try_get("weight_class_id")?,
+        weight_class_manual: row.try_get("weight_class_manual")?,
+        equipment_m: row.try_get("equipment_m")?,
+        equipment_sm: row.try_get("equipment_sm")?,
+        equipment_t: row.try_get("equipment_t")?,
+        bodyweight: row.try_get("bodyweight")?,
+        lot_number: row.try_get("lot_number")?,
+        personal_record_at_entry: row.try_get("personal_record_at_entry")?,
+        reshel_coefficient: row.try_get("reshel_coefficient")?,
+        mccullough_coefficient: row.try_get("mccullough_coefficient")?,
+        scoring_coefficient: row.try_get("scoring_coefficient")?,
+        age_at_contest: row.try_get("age_at_contest")?,
+        age_rule: row.try_get("age_rule")?,
+        rack_height_squat: row.try_get("rack_height_squat")?,
+        rack_height_bench: row.try_get("rack_height_bench")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRegistrationRequest {
     pub contest_id: String,
     pub competitor_id: String,
     pub age_category_id: String,
     pub weight_class_id: String,
+    // Chosen by hand rather than assigned from the bodyweight
+    pub weight_class_manual: bool,
     // Equipment flags
     pub equipment_m: bool,
     pub equipment_sm: bool,
@@ -42,6 +123,11 @@ pub struct CreateRegistrationRequest {
     // Coefficients
     pub reshel_coefficient: Option<f64>,
     pub mccullough_coefficient: Option<f64>,
+    pub scoring_coefficient: Option<f64>,
+    // Age in full years the McCullough factor was computed from
+    pub age_at_contest: Option<u32>,
+    // Rule the age category was assigned by; None when chosen by hand
+    pub age_rule: Option<AgeRule>,
     // Rack heights
     pub rack_height_squat: Option<i32>,
     pub rack_height_bench: Option<i32>,
@@ -54,26 +140,26 @@ pub async fn create_registration(
 ) -> Result<Registration, sqlx::Error> {
     let id = uuid::Uuid::new_v4().to_string();
 
-    let row = sqlx::query(
+    let row = sqlx::query(&format!(
         r#"
         INSERT INTO registrations (
-            id, contest_id, competitor_id, age_category_id, weight_class_id,
+            id, contest_id, competitor_id, age_category_id, weight_class_id, weight_class_manual,
             equipment_m, equipment_sm, equipment_t, bodyweight, lot_number, 
             personal_record_at_entry, reshel_coefficient, mccullough_coefficient,
-            rack_height_squat, rack_height_bench
+            scoring_coefficient, age_at_contest, age_rule, rack_height_squat, rack_height_bench
         )
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
-        RETURNING id, contest_id, competitor_id, age_category_id, weight_class_id,
-                  equipment_m, equipment_sm, equipment_t, bodyweight, lot_number,
-                  personal_record_at_entry, reshel_coefficient, mccullough_coefficient,
-                  rack_height_squat, rack_height_bench, created_at
-        "#,
-    )
+        VALUES (
+            ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19
+        )
+        RETURNING {REGISTRATION_COLUMNS}
+        "#
+    ))
     .bind(&id)
     .bind(&request.contest_id)
     .bind(&request.competitor_id)
     .bind(&request.age_category_id)
     .bind(&request.weight_class_id)
+    .bind(request.weight_class_manual)
     .bind(request.equipment_m)
     .bind(request.equipment_sm)
     .bind(request.equipment_t)
@@ -82,132 +168,81 @@ pub async fn create_registration(
     .bind(request.personal_record_at_entry)
     .bind(request.reshel_coefficient)
     .bind(request.mccullough_coefficient)
+    .bind(request.scoring_coefficient)
+    .bind(request.age_at_contest)
+    .bind(request.age_rule)
     .bind(request.rack_height_squat)
     .bind(request.rack_height_bench)
     .fetch_one(pool)
     .await?;
 
-    Ok(Registration {
-        id: row.try_get("id")?,
-        contest_id: row.try_get("contest_id")?,
-        competitor_id: row.try_get("competitor_id")?,
-        age_category_id: row.try_get("age_category_id")?,
-        weight_class_id: row.try_get("weight_class_id")?,
-        equipment_m: row.try_get("equipment_m")?,
-        equipment_sm: row.try_get("equipment_sm")?,
-        equipment_t: row.try_get("equipment_t")?,
-        bodyweight: row.try_get("bodyweight")?,
-        lot_number: row.try_get("lot_number")?,
-        personal_record_at_entry: row.try_get("personal_record_at_entry")?,
-        reshel_coefficient: row.try_get("reshel_coefficient")?,
-        mccullough_coefficient: row.try_get("mccullough_coefficient")?,
-        rack_height_squat: row.try_get("rack_height_squat")?,
-        rack_height_bench: row.try_get("rack_height_bench")?,
-        created_at: row.try_get("created_at")?,
-    })
+    map_registration_row(&row)
 }
 
 /// Get registration by ID
-pub async fn get_registration_by_id(
-    pool: &Pool<Sqlite>,
+pub async fn get_registration_by_id<'e, E>(
+    executor: E,
     registration_id: &str,
-) -> Result<Registration, sqlx::Error> {
-    let row = sqlx::query(
-        r#"
-        SELECT id, contest_id, competitor_id, age_category_id, weight_class_id,
-               equipment_m, equipment_sm, equipment_t, bodyweight, lot_number,
-               personal_record_at_entry, reshel_coefficient, mccullough_coefficient,
-               rack_height_squat, rack_height_bench, created_at
-        FROM registrations WHERE id = ?1
-        "#,
-    )
+) -> Result<Registration, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let row = sqlx::query(&format!(
+        "SELECT {REGISTRATION_COLUMNS} FROM registrations WHERE id = ?1"
+    ))
     .bind(registration_id)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
-    Ok(Registration {
-        id: row.try_get("id")?,
-        contest_id: row.try_get("contest_id")?,
-        competitor_id: row.try_get("competitor_id")?,
-        age_category_id: row.try_get("age_category_id")?,
-        weight_class_id: row.try_get("weight_class_id")?,
-        equipment_m: row.try_get("equipment_m")?,
-        equipment_sm: row.try_get("equipment_sm")?,
-        equipment_t: row.try_get("equipment_t")?,
-        bodyweight: row.try_get("bodyweight")?,
-        lot_number: row.try_get("lot_number")?,
-        personal_record_at_entry: row.try_get("personal_record_at_entry")?,
-        reshel_coefficient: row.try_get("reshel_coefficient")?,
-        mccullough_coefficient: row.try_get("mccullough_coefficient")?,
-        rack_height_squat: row.try_get("rack_height_squat")?,
-        rack_height_bench: row.try_get("rack_height_bench")?,
-        created_at: row.try_get("created_at")?,
-    })
+    map_registration_row(&row)
 }
 
 /// Get all registrations for a contest
-pub async fn get_registrations_by_contest(
-    pool: &Pool<Sqlite>,
+pub async fn get_registrations_by_contest<'e, E>(
+    executor: E,
     contest_id: &str,
-) -> Result<Vec<Registration>, sqlx::Error> {
-    let rows = sqlx::query(
+) -> Result<Vec<Registration>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let rows = sqlx::query(&format!(
         r#"
-        SELECT r.id, r.contest_id, r.competitor_id, r.age_category_id, r.weight_class_id,
-               r.equipment_m, r.equipment_sm, r.equipment_t, r.bodyweight, r.lot_number,
-               r.personal_record_at_entry, r.reshel_coefficient, r.mccullough_coefficient,
-               r.rack_height_squat, r.rack_height_bench, r.created_at
-        FROM registrations r
-        WHERE r.contest_id = ?1
-        ORDER BY r.lot_number, r.bodyweight
-        "#,
-    )
+        SELECT {REGISTRATION_COLUMNS}
+        FROM registrations
+        WHERE contest_id = ?1
+        ORDER BY lot_number, bodyweight
+        "#
+    ))
     .bind(contest_id)
-    .fetch_all(pool)
+    .fetch_all(executor)
     .await?;
 
-    let mut registrations = Vec::new();
-    for row in rows {
-        registrations.push(Registration {
-            id: row.try_get("id")?,
-            contest_id: row.try_get("contest_id")?,
-            competitor_id: row.try_get("competitor_id")?,
-            age_category_id: row.try_get("age_category_id")?,
-            weight_class_id: row.try_get("weight_class_id")?,
-            equipment_m: row.try_get("equipment_m")?,
-            equipment_sm: row.try_get("equipment_sm")?,
-            equipment_t: row.try_get("equipment_t")?,
-            bodyweight: row.try_get("bodyweight")?,
-            lot_number: row.try_get("lot_number")?,
-            personal_record_at_entry: row.try_get("personal_record_at_entry")?,
-            reshel_coefficient: row.try_get("reshel_coefficient")?,
-            mccullough_coefficient: row.try_get("mccullough_coefficient")?,
-            rack_height_squat: row.try_get("rack_height_squat")?,
-            rack_height_bench: row.try_get("rack_height_bench")?,
-            created_at: row.try_get("created_at")?,
-        });
-    }
-
-    Ok(registrations)
+    rows.iter().map(map_registration_row).collect()
 }
 
 /// Update registration
-pub async fn update_registration(
-    pool: &Pool<Sqlite>,
+pub async fn update_registration<'e, E>(
+    executor: E,
     registration_id: &str,
     request: CreateRegistrationRequest,
-) -> Result<(), sqlx::Error> {
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     sqlx::query(
         r#"
         UPDATE registrations 
-        SET age_category_id = ?1, weight_class_id = ?2, equipment_m = ?3, equipment_sm = ?4,
-            equipment_t = ?5, bodyweight = ?6, lot_number = ?7, personal_record_at_entry = ?8,
-            reshel_coefficient = ?9, mccullough_coefficient = ?10, rack_height_squat = ?11,
-            rack_height_bench = ?12
-        WHERE id = ?13
+        SET age_category_id = ?1, weight_class_id = ?2, weight_class_manual = ?3,
+            equipment_m = ?4, equipment_sm = ?5, equipment_t = ?6, bodyweight = ?7,
+            lot_number = ?8, personal_record_at_entry = ?9, reshel_coefficient = ?10,
+            mccullough_coefficient = ?11, scoring_coefficient = ?12, age_at_contest = ?13,
+            age_rule = ?14, rack_height_squat = ?15, rack_height_bench = ?16
+        WHERE id = ?17
         "#,
     )
     .bind(&request.age_category_id)
     .bind(&request.weight_class_id)
+    .bind(request.weight_class_manual)
     .bind(request.equipment_m)
     .bind(request.equipment_sm)
     .bind(request.equipment_t)
@@ -216,56 +251,51 @@ pub async fn update_registration(
     .bind(request.personal_record_at_entry)
     .bind(request.reshel_coefficient)
     .bind(request.mccullough_coefficient)
+    .bind(request.scoring_coefficient)
+    .bind(request.age_at_contest)
+    .bind(request.age_rule)
     .bind(request.rack_height_squat)
     .bind(request.rack_height_bench)
     .bind(registration_id)
-    .execute(pool)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
+/// Get all registrations of a competitor
+pub async fn get_registrations_by_competitor<'e, E>(
+    executor: E,
+    competitor_id: &str,
+) -> Result<Vec<Registration>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    let rows = sqlx::query(&format!(
+        "SELECT {REGISTRATION_COLUMNS} FROM registrations WHERE competitor_id = ?1"
+    ))
+    .bind(competitor_id)
+    .fetch_all(executor)
+    .await?;
+
+    rows.iter().map(map_registration_row).collect()
+}
+
 /// Get registration by competitor and contest
 pub async fn get_registration_by_competitor_and_contest(
     pool: &Pool<Sqlite>,
     competitor_id: &str,
     contest_id: &str,
 ) -> Result<Option<Registration>, sqlx::Error> {
-    let row = sqlx::query(
-        r#"
-        SELECT id, contest_id, competitor_id, age_category_id, weight_class_id,
-               equipment_m, equipment_sm, equipment_t, bodyweight, lot_number,
-               personal_record_at_entry, reshel_coefficient, mccullough_coefficient,
-               rack_height_squat, rack_height_bench, created_at
-        FROM registrations WHERE competitor_id = ?1 AND contest_id = ?2
-        "#,
-    )
+    let row = sqlx::query(&format!(
+        "SELECT {REGISTRATION_COLUMNS} FROM registrations WHERE competitor_id = ?1 AND contest_id = ?2"
+    ))
     .bind(competitor_id)
     .bind(contest_id)
     .fetch_optional(pool)
     .await?;
 
-    match row {
-        Some(row) => Ok(Some(Registration {
-            id: row.try_get("id")?,
-            contest_id: row.try_get("contest_id")?,
-            competitor_id: row.try_get("competitor_id")?,
-            age_category_id: row.try_get("age_category_id")?,
-            weight_class_id: row.try_get("weight_class_id")?,
-            equipment_m: row.try_get("equipment_m")?,
-            equipment_sm: row.try_get("equipment_sm")?,
-            equipment_t: row.try_get("equipment_t")?,
-            bodyweight: row.try_get("bodyweight")?,
-            lot_number: row.try_get("lot_number")?,
-            personal_record_at_entry: row.try_get("personal_record_at_entry")?,
-            reshel_coefficient: row.try_get("reshel_coefficient")?,
-            mccullough_coefficient: row.try_get("mccullough_coefficient")?,
-            rack_height_squat: row.try_get("rack_height_squat")?,
-            rack_height_bench: row.try_get("rack_height_bench")?,
-            created_at: row.try_get("created_at")?,
-        })),
-        None => Ok(None),
-    }
+    row.as_ref().map(map_registration_row).transpose()
 }
 
 /// Delete registration
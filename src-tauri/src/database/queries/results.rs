@@ -1,5 +1,21 @@
+use crate::best_lifter::{self, BestLifterAward, BestLifterEntry};
+use crate::coefficients;
+use crate::database::queries::{contests, divisions};
+use crate::error::AppError;
+use crate::models::attempt::{LiftType, RECORD_ATTEMPT_NUMBER};
+use crate::models::contest::{
+    Discipline, RankingBasis, RankingRule, RankingSplit, ScoringFormulaKind, TieBreak,
+};
+use crate::models::division::{AGE_CLASS_DIVISION, OPEN_DIVISION, WEIGHT_CLASS_DIVISION};
+use crate::models::registration::Equipment;
+use crate::ranking::{self, RankEntry};
+use crate::scoring::{self, LifterProfile};
+use crate::team_ranking::{self, TeamEntry, TeamRanking};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Pool, Sqlite};
+use sqlx::{Acquire, Executor, FromRow, Pool, Sqlite};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use strum::{Display, EnumString};
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CompetitionResult {
@@ -10,15 +26,26 @@ pub struct CompetitionResult {
     pub best_bench: Option<f64>,
     pub best_squat: Option<f64>,
     pub best_deadlift: Option<f64>,
+    // Successful 4th (record) attempts; not part of the best lifts or the total
+    pub record_attempt_squat: Option<f64>,
+    pub record_attempt_bench: Option<f64>,
+    pub record_attempt_deadlift: Option<f64>,
     pub total_weight: f64,
     pub coefficient_points: f64,
-    // Triple ranking support (based on CSV files)
+    // Formula that produced `coefficient_points`
+    pub scoring_formula: ScoringFormulaKind,
+    // Places in the built-in divisions, derived from their division placings
     pub place_open: Option<i64>,
     pub place_in_age_class: Option<i64>,
     pub place_in_weight_class: Option<i64>,
     // Competition flags
     pub is_disqualified: bool,
+    // Reason for a disqualification or a DNF
     pub disqualification_reason: Option<String>,
+    pub did_not_finish: bool,
+    pub status_changed_at: Option<String>,
+    // No successful attempt in a lift the discipline requires; no total or place
+    pub is_bombed_out: bool,
     // Record tracking
     pub broke_record: bool,
     pub record_type: Option<String>,
@@ -38,43 +65,151 @@ pub struct CreateResultRequest {
     pub record_type: Option<String>,
 }
 
+/// Decision of an official on a lifter's result
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, EnumString, Display, sqlx::Type, PartialEq,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum ResultStatusAction {
+    Disqualify,
+    DidNotFinish,
+    Reinstate,
+}
+
+/// Logged disqualification, DNF or reinstatement
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ResultStatusLogEntry {
+    pub id: String,
+    pub registration_id: String,
+    pub contest_id: String,
+    pub action: ResultStatusAction,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
 /// Calculate and create/update results for a registration
-pub async fn calculate_results(
-    pool: &Pool<Sqlite>,
+pub async fn calculate_results<'a, A>(
+    conn: A,
     registration_id: &str,
-) -> Result<CompetitionResult, sqlx::Error> {
-    // Get registration with coefficients
+) -> Result<CompetitionResult, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+
+    // Get registration with coefficients and the data needed by the contest's scoring formula
     let reg_data = sqlx::query!(
-        "SELECT contest_id, reshel_coefficient, mccullough_coefficient FROM registrations WHERE id = ?",
+        r#"
+        SELECT
+            r.contest_id as "contest_id!",
+            r.bodyweight as "bodyweight!",
+            r.scoring_coefficient,
+            r.equipment_m as "equipment_m!: bool",
+            r.equipment_sm as "equipment_sm!: bool",
+            r.equipment_t as "equipment_t!: bool",
+            c.gender as "gender!",
+            c.birth_date as "birth_date!",
+            ct.date as "contest_date!",
+            ct.discipline as "discipline!",
+            ct.scoring_formula as "scoring_formula!"
+        FROM registrations r
+        JOIN competitors c ON c.id = r.competitor_id
+        JOIN contests ct ON ct.id = r.contest_id
+        WHERE r.id = ?
+        "#,
         registration_id
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await?;
 
     let contest_id = reg_data.contest_id;
-    let reshel = reg_data.reshel_coefficient;
-    let mccullough = reg_data.mccullough_coefficient;
+    // Scoring a contest by a formula or discipline it does not have would publish wrong points
+    let invalid = |field: &str, value: &str| AppError::InvalidContestData {
+        contest_id: contest_id.clone(),
+        field: field.to_string(),
+        value: value.to_string(),
+    };
+    let scoring_formula = ScoringFormulaKind::from_str(&reg_data.scoring_formula)
+        .map_err(|_| invalid("scoring formula", &reg_data.scoring_formula))?;
+    let discipline = Discipline::from_str(&reg_data.discipline)
+        .map_err(|_| invalid("discipline", &reg_data.discipline))?;
+    let equipped = Equipment::from_flags(
+        reg_data.equipment_m,
+        reg_data.equipment_sm,
+        reg_data.equipment_t,
+    ) != Equipment::Raw;
 
     // Get best lifts from attempts
-    let best_bench = get_best_lift_weight(pool, registration_id, "Bench").await?;
-    let best_squat = get_best_lift_weight(pool, registration_id, "Squat").await?;
-    let best_deadlift = get_best_lift_weight(pool, registration_id, "Deadlift").await?;
+    let best_bench = get_best_lift_weight(&mut *conn, registration_id, "Bench").await?;
+    let best_squat = get_best_lift_weight(&mut *conn, registration_id, "Squat").await?;
+    let best_deadlift = get_best_lift_weight(&mut *conn, registration_id, "Deadlift").await?;
+    let record_attempt_squat =
+        get_record_attempt_weight(&mut *conn, registration_id, "Squat").await?;
+    let record_attempt_bench =
+        get_record_attempt_weight(&mut *conn, registration_id, "Bench").await?;
+    let record_attempt_deadlift =
+        get_record_attempt_weight(&mut *conn, registration_id, "Deadlift").await?;
 
-    // Calculate total
-    let total =
-        (best_bench.unwrap_or(0.0)) + (best_squat.unwrap_or(0.0)) + (best_deadlift.unwrap_or(0.0));
+    // Calculate total from the lifts of the contest's discipline; failing every attempt
+    // of one of them is a bomb-out and leaves the lifter without a total
+    let mut total = 0.0;
+    let mut is_bombed_out = false;
+    for lift in discipline.lifts() {
+        let best = match lift {
+            LiftType::Squat => best_squat,
+            LiftType::Bench => best_bench,
+            LiftType::Deadlift => best_deadlift,
+        };
+        total += best.unwrap_or(0.0);
+        if best.is_none() && lift_failed(&mut *conn, registration_id, &lift.to_string()).await? {
+            is_bombed_out = true;
+        }
+    }
+    if is_bombed_out {
+        total = 0.0;
+    }
 
-    // Calculate coefficient points
-    let coeff_points = total * reshel.unwrap_or(1.0) * mccullough.unwrap_or(1.0);
+    // Calculate coefficient points using the contest's scoring formula; registrations
+    // saved without a coefficient get it computed from the formula here
+    let coefficient = match reg_data.scoring_coefficient {
+        Some(coefficient) => coefficient,
+        None => {
+            let age = coefficients::age_on_date(&reg_data.birth_date, &reg_data.contest_date)?;
+            let lifter = LifterProfile {
+                bodyweight: reg_data.bodyweight,
+                gender: &reg_data.gender,
+                age,
+                equipped,
+                discipline: &discipline,
+            };
+            scoring::formula(scoring_formula).coefficient(&lifter)?
+        }
+    };
+    let coeff_points = coefficients::calculate_points(total, coefficient, 1.0);
+    let scoring_formula = scoring_formula.to_string();
 
-    // Create or update result
+    // Create or update result; disqualifications and DNFs set by officials are kept
     let result_id = uuid::Uuid::new_v4().to_string();
 
     sqlx::query!(
         r#"
-        INSERT OR REPLACE INTO results 
-        (id, registration_id, contest_id, best_bench, best_squat, best_deadlift, total_weight, coefficient_points)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO results 
+        (id, registration_id, contest_id, best_bench, best_squat, best_deadlift, record_attempt_squat, record_attempt_bench, record_attempt_deadlift, total_weight, coefficient_points, scoring_formula, is_bombed_out)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(registration_id) DO UPDATE SET
+            best_bench = excluded.best_bench,
+            best_squat = excluded.best_squat,
+            best_deadlift = excluded.best_deadlift,
+            record_attempt_squat = excluded.record_attempt_squat,
+            record_attempt_bench = excluded.record_attempt_bench,
+            record_attempt_deadlift = excluded.record_attempt_deadlift,
+            total_weight = excluded.total_weight,
+            coefficient_points = excluded.coefficient_points,
+            scoring_formula = excluded.scoring_formula,
+            is_bombed_out = excluded.is_bombed_out,
+            calculated_at = CURRENT_TIMESTAMP
         "#,
         result_id,
         registration_id,
@@ -82,20 +217,75 @@ pub async fn calculate_results(
         best_bench,
         best_squat,
         best_deadlift,
+        record_attempt_squat,
+        record_attempt_bench,
+        record_attempt_deadlift,
         total,
-        coeff_points
+        coeff_points,
+        scoring_formula,
+        is_bombed_out
+    )
+    .execute(&mut *conn)
+    .await?;
+    update_record_flag(&mut *conn, registration_id).await?;
+
+    Ok(get_result_by_registration(&mut *conn, registration_id).await?)
+}
+
+/// Flag a result with the highest scope of the records its attempts set
+pub async fn update_record_flag<'a, A>(conn: A, registration_id: &str) -> Result<(), sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+    sqlx::query!(
+        r#"
+        UPDATE results
+        SET record_type = (
+            SELECT rec.scope
+            FROM records rec
+            JOIN attempts a ON a.id = rec.attempt_id
+            WHERE a.registration_id = results.registration_id
+            ORDER BY CASE rec.scope WHEN 'National' THEN 3 WHEN 'Regional' THEN 2 ELSE 1 END DESC
+            LIMIT 1
+        )
+        WHERE registration_id = ?
+        "#,
+        registration_id
+    )
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query!(
+        "UPDATE results SET broke_record = record_type IS NOT NULL WHERE registration_id = ?",
+        registration_id
     )
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
-    get_result_by_registration(pool, registration_id).await
+    Ok(())
+}
+
+/// Whether results have been calculated for a registration
+pub async fn result_exists<'e, E>(executor: E, registration_id: &str) -> Result<bool, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM results WHERE registration_id = ?) as "exists!: bool""#,
+        registration_id
+    )
+    .fetch_one(executor)
+    .await
 }
 
 /// Get result by registration ID
-pub async fn get_result_by_registration(
-    pool: &Pool<Sqlite>,
+pub async fn get_result_by_registration<'e, E>(
+    executor: E,
     registration_id: &str,
-) -> Result<CompetitionResult, sqlx::Error> {
+) -> Result<CompetitionResult, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     sqlx::query_as!(
         CompetitionResult,
         r#"
@@ -106,21 +296,28 @@ pub async fn get_result_by_registration(
             best_bench, 
             best_squat, 
             best_deadlift,
+            record_attempt_squat,
+            record_attempt_bench,
+            record_attempt_deadlift,
             total_weight as "total_weight!", 
             coefficient_points as "coefficient_points!", 
-            place_open, 
-            place_in_age_class, 
-            place_in_weight_class,
+            scoring_formula as "scoring_formula!: ScoringFormulaKind",
+            place_open as "place_open?",
+            place_in_age_class as "place_in_age_class?",
+            place_in_weight_class as "place_in_weight_class?",
             is_disqualified as "is_disqualified!", 
             disqualification_reason, 
+            did_not_finish as "did_not_finish!", 
+            status_changed_at, 
+            is_bombed_out as "is_bombed_out!", 
             broke_record as "broke_record!", 
             record_type, 
             calculated_at as "calculated_at!"
-        FROM results WHERE registration_id = ?
+        FROM ranked_results WHERE registration_id = ?
         "#,
         registration_id
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
@@ -139,17 +336,24 @@ pub async fn get_contest_results(
             best_bench, 
             best_squat, 
             best_deadlift,
+            record_attempt_squat,
+            record_attempt_bench,
+            record_attempt_deadlift,
             total_weight as "total_weight!", 
             coefficient_points as "coefficient_points!", 
-            place_open, 
-            place_in_age_class, 
-            place_in_weight_class,
+            scoring_formula as "scoring_formula!: ScoringFormulaKind",
+            place_open as "place_open?",
+            place_in_age_class as "place_in_age_class?",
+            place_in_weight_class as "place_in_weight_class?",
             is_disqualified as "is_disqualified!", 
             disqualification_reason, 
+            did_not_finish as "did_not_finish!", 
+            status_changed_at, 
+            is_bombed_out as "is_bombed_out!", 
             broke_record as "broke_record!", 
             record_type, 
             calculated_at as "calculated_at!"
-        FROM results 
+        FROM ranked_results
         WHERE contest_id = ?
         ORDER BY coefficient_points DESC
         "#,
@@ -159,33 +363,385 @@ pub async fn get_contest_results(
     .await
 }
 
-pub async fn get_age_class_ranking(
+/// Update all rankings for a contest: the built-in open, age class and weight class
+/// divisions and the contest's named divisions
+pub async fn update_all_rankings<'a, A>(conn: A, contest_id: &str) -> Result<(), sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    update_rankings(conn, contest_id, None).await
+}
+
+/// Update only the rankings a registration is placed in, i.e. the groups sharing its
+/// categories and the divisions it belongs to, e.g. after one of its attempts was judged
+pub async fn update_registration_rankings<'a, A>(
+    conn: A,
+    contest_id: &str,
+    registration_id: &str,
+) -> Result<(), sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    update_rankings(conn, contest_id, Some(registration_id)).await
+}
+
+/// Re-place every ranking group of a contest, or only the groups containing the given
+/// registration
+async fn update_rankings<'a, A>(
+    conn: A,
+    contest_id: &str,
+    registration_id: Option<&str>,
+) -> Result<(), sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+
+    let contest = contests::get_contest_by_id(&mut *conn, contest_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    let divisions = divisions::get_divisions_by_contest(&mut *conn, contest_id).await?;
+    let entries: BTreeSet<(String, String)> =
+        divisions::get_division_entries_by_contest(&mut *conn, contest_id)
+            .await?
+            .into_iter()
+            .collect();
+
+    // All results with the data the tie-breaks need; disqualified, unfinished and bombed-out
+    // ones stay in their groups without a place. The total is achieved with the last of the
+    // lifter's best lifts, so the judging order of that lift orders equal totals.
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            r.id as "id!",
+            r.registration_id as "registration_id!",
+            r.total_weight as "total_weight!",
+            r.coefficient_points as "coefficient_points!",
+            (NOT r.is_disqualified AND NOT r.did_not_finish AND NOT r.is_bombed_out)
+                as "ranked!: bool",
+            c.gender as "gender!",
+            reg.bodyweight as "bodyweight!",
+            reg.lot_number,
+            reg.age_category_id as "age_category_id!",
+            reg.weight_class_id as "weight_class_id!",
+            reg.equipment_m as "equipment_m!: bool",
+            reg.equipment_sm as "equipment_sm!: bool",
+            reg.equipment_t as "equipment_t!: bool",
+            (
+                SELECT MAX(a.achieved_at)
+                FROM attempts a
+                WHERE a.registration_id = r.registration_id
+                AND a.status = 'Successful' AND a.attempt_number < ?
+                AND a.weight = (
+                    SELECT MAX(a2.weight)
+                    FROM attempts a2
+                    WHERE a2.registration_id = a.registration_id
+                    AND a2.lift_type = a.lift_type
+                    AND a2.status = 'Successful' AND a2.attempt_number < ?
+                )
+            ) as "achieved_at: i64"
+        FROM results r
+        JOIN registrations reg ON reg.id = r.registration_id
+        JOIN competitors c ON c.id = reg.competitor_id
+        WHERE r.contest_id = ?
+        "#,
+        RECORD_ATTEMPT_NUMBER,
+        RECORD_ATTEMPT_NUMBER,
+        contest_id
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let lifters: Vec<RankedLifter> = rows
+        .into_iter()
+        .map(|row| RankedLifter {
+            result_id: row.id,
+            registration_id: row.registration_id,
+            ranked: row.ranked,
+            total: row.total_weight,
+            points: row.coefficient_points,
+            bodyweight: row.bodyweight,
+            achieved_at: row.achieved_at,
+            lot_number: row.lot_number,
+            gender: row.gender,
+            equipment: Equipment::from_flags(row.equipment_m, row.equipment_sm, row.equipment_t),
+            age_category_id: row.age_category_id,
+            weight_class_id: row.weight_class_id,
+        })
+        .collect();
+
+    // Groups without the registration keep their places
+    let affected = |group: &[&RankedLifter]| {
+        registration_id.map_or(true, |registration_id| {
+            group
+                .iter()
+                .any(|lifter| lifter.registration_id == registration_id)
+        })
+    };
+    let tie_breaks = &contest.tie_breaks;
+
+    let mut tx = conn.begin().await?;
+
+    // 1. Built-in open, age class and weight class rankings, placed within their groups
+    let builtin: [(&str, &RankingRule, GroupCategory); 3] = [
+        (OPEN_DIVISION, &contest.ranking.open, |_| None),
+        (AGE_CLASS_DIVISION, &contest.ranking.age_class, |lifter| {
+            Some(&lifter.age_category_id)
+        }),
+        (
+            WEIGHT_CLASS_DIVISION,
+            &contest.ranking.weight_class,
+            |lifter| Some(&lifter.weight_class_id),
+        ),
+    ];
+    for (division_id, rule, category) in builtin {
+        for group in group_lifters(&lifters, rule, category) {
+            if !affected(&group) {
+                continue;
+            }
+            for (result_id, place) in place_group(&group, rule.basis, tie_breaks) {
+                store_placing(&mut *tx, division_id, &result_id, place).await?;
+            }
+        }
+    }
+
+    // 2. Named divisions of the contest, with the lifters matching them or entered in them
+    for division in &divisions {
+        let members: Vec<&RankedLifter> = lifters
+            .iter()
+            .filter(|lifter| {
+                division.matches(
+                    &lifter.gender,
+                    &lifter.age_category_id,
+                    &lifter.weight_class_id,
+                    lifter.equipment,
+                ) || (entries.contains(&(division.id.clone(), lifter.registration_id.clone()))
+                    && division.admits(&lifter.gender, &lifter.weight_class_id, lifter.equipment))
+            })
+            .collect();
+        if !affected(&members) {
+            continue;
+        }
+
+        sqlx::query!(
+            "DELETE FROM division_placings WHERE division_id = ?",
+            division.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (result_id, place) in place_group(&members, division.basis, tie_breaks) {
+            store_placing(&mut *tx, &division.id, &result_id, place).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// A result with the categories it is ranked in
+struct RankedLifter {
+    result_id: String,
+    registration_id: String,
+    /// Whether the result takes a place, i.e. is not disqualified, unfinished or bombed out
+    ranked: bool,
+    total: f64,
+    points: f64,
+    bodyweight: f64,
+    achieved_at: Option<i64>,
+    lot_number: Option<String>,
+    gender: String,
+    equipment: Equipment,
+    age_category_id: String,
+    weight_class_id: String,
+}
+
+impl RankedLifter {
+    /// Entry of the lifter in a ranking decided by the given basis
+    fn rank_entry(&self, basis: RankingBasis) -> RankEntry {
+        RankEntry {
+            result_id: self.result_id.clone(),
+            score: match basis {
+                RankingBasis::Total => self.total,
+                RankingBasis::Points => self.points,
+            },
+            bodyweight: self.bodyweight,
+            achieved_at: self.achieved_at,
+            lot_number: self.lot_number.clone(),
+        }
+    }
+}
+
+/// Category a built-in ranking is split into on top of its rule's splits
+type GroupCategory = fn(&RankedLifter) -> Option<&String>;
+
+/// Split lifters into the groups of one ranking, i.e. by its category and the rule's splits
+fn group_lifters<'l>(
+    lifters: &'l [RankedLifter],
+    rule: &RankingRule,
+    category: GroupCategory,
+) -> Vec<Vec<&'l RankedLifter>> {
+    let mut groups: BTreeMap<Vec<String>, Vec<&RankedLifter>> = BTreeMap::new();
+    for lifter in lifters {
+        let mut key: Vec<String> = rule
+            .split_by
+            .iter()
+            .map(|split| match split {
+                RankingSplit::Sex => lifter.gender.clone(),
+                RankingSplit::Equipment => lifter.equipment.to_string(),
+                RankingSplit::AgeCategory => lifter.age_category_id.clone(),
+            })
+            .collect();
+        key.extend(category(lifter).cloned());
+
+        groups.entry(key).or_default().push(lifter);
+    }
+    groups.into_values().collect()
+}
+
+/// Store a result's place in a division, or remove it when the result has no place
+async fn store_placing<'e, E>(
+    executor: E,
+    division_id: &str,
+    result_id: &str,
+    place: Option<i64>,
+) -> Result<(), sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    match place {
+        Some(place) => {
+            sqlx::query!(
+                r#"
+            INSERT INTO division_placings (division_id, result_id, place) VALUES (?, ?, ?)
+            ON CONFLICT(division_id, result_id) DO UPDATE SET place = excluded.place
+            "#,
+                division_id,
+                result_id,
+                place
+            )
+            .execute(executor)
+            .await?
+        }
+        None => {
+            sqlx::query!(
+                "DELETE FROM division_placings WHERE division_id = ? AND result_id = ?",
+                division_id,
+                result_id
+            )
+            .execute(executor)
+            .await?
+        }
+    };
+
+    Ok(())
+}
+
+/// Places of a group's results scored by the given basis; results out of the rankings
+/// get no place
+fn place_group(
+    group: &[&RankedLifter],
+    basis: RankingBasis,
+    tie_breaks: &[TieBreak],
+) -> Vec<(String, Option<i64>)> {
+    let entries = group
+        .iter()
+        .filter(|lifter| lifter.ranked)
+        .map(|lifter| lifter.rank_entry(basis))
+        .collect();
+    let places: BTreeMap<String, i64> = ranking::assign_places(entries, tie_breaks)
+        .into_iter()
+        .collect();
+    group
+        .iter()
+        .map(|lifter| {
+            (
+                lifter.result_id.clone(),
+                places.get(&lifter.result_id).copied(),
+            )
+        })
+        .collect()
+}
+
+/// Disqualify, mark as DNF or reinstate a result and log the decision.
+/// Rankings are not updated here; callers recompute them afterwards, in the same transaction
+/// when they pass one.
+pub async fn set_result_status<'a, A>(
+    conn: A,
+    registration_id: &str,
+    action: ResultStatusAction,
+    reason: Option<&str>,
+) -> Result<CompetitionResult, sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let is_disqualified = action == ResultStatusAction::Disqualify;
+    let did_not_finish = action == ResultStatusAction::DidNotFinish;
+    // A reinstatement's reason only goes to the log
+    let status_reason = if is_disqualified || did_not_finish {
+        reason
+    } else {
+        None
+    };
+    let log_id = uuid::Uuid::new_v4().to_string();
+    let action = action.to_string();
+
+    let mut tx = conn.begin().await?;
+
+    let contest_id = sqlx::query_scalar!(
+        r#"
+        UPDATE results
+        SET is_disqualified = ?, did_not_finish = ?, disqualification_reason = ?,
+            status_changed_at = CURRENT_TIMESTAMP
+        WHERE registration_id = ?
+        RETURNING contest_id as "contest_id!"
+        "#,
+        is_disqualified,
+        did_not_finish,
+        status_reason,
+        registration_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO result_status_log (id, registration_id, contest_id, action, reason)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+        log_id,
+        registration_id,
+        contest_id,
+        action,
+        reason
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let result = get_result_by_registration(&mut *tx, registration_id).await?;
+    tx.commit().await?;
+
+    Ok(result)
+}
+
+/// Get the logged status decisions of a contest, oldest first
+pub async fn get_result_status_log(
     pool: &Pool<Sqlite>,
     contest_id: &str,
-) -> Result<Vec<CompetitionResult>, sqlx::Error> {
+) -> Result<Vec<ResultStatusLogEntry>, sqlx::Error> {
     sqlx::query_as!(
-        CompetitionResult,
+        ResultStatusLogEntry,
         r#"
-        SELECT 
-            id as "id!", 
-            registration_id as "registration_id!", 
-            contest_id as "contest_id!", 
-            best_bench, 
-            best_squat, 
-            best_deadlift,
-            total_weight as "total_weight!", 
-            coefficient_points as "coefficient_points!", 
-            place_open, 
-            place_in_age_class, 
-            place_in_weight_class,
-            is_disqualified as "is_disqualified!", 
-            disqualification_reason, 
-            broke_record as "broke_record!", 
-            record_type, 
-            calculated_at as "calculated_at!"
-        FROM results
-        WHERE contest_id = ? AND NOT is_disqualified
-        ORDER BY place_in_age_class
+        SELECT
+            id as "id!",
+            registration_id as "registration_id!",
+            contest_id as "contest_id!",
+            action as "action!: ResultStatusAction",
+            reason,
+            created_at as "created_at!"
+        FROM result_status_log
+        WHERE contest_id = ?
+        ORDER BY created_at, rowid
         "#,
         contest_id
     )
@@ -193,7 +749,8 @@ pub async fn get_age_class_ranking(
     .await
 }
 
-pub async fn get_weight_class_ranking(
+/// Get disqualified and DNF results for a contest
+pub async fn get_excluded_results(
     pool: &Pool<Sqlite>,
     contest_id: &str,
 ) -> Result<Vec<CompetitionResult>, sqlx::Error> {
@@ -207,19 +764,26 @@ pub async fn get_weight_class_ranking(
             best_bench, 
             best_squat, 
             best_deadlift,
+            record_attempt_squat,
+            record_attempt_bench,
+            record_attempt_deadlift,
             total_weight as "total_weight!", 
             coefficient_points as "coefficient_points!", 
-            place_open, 
-            place_in_age_class, 
-            place_in_weight_class,
+            scoring_formula as "scoring_formula!: ScoringFormulaKind",
+            place_open as "place_open?",
+            place_in_age_class as "place_in_age_class?",
+            place_in_weight_class as "place_in_weight_class?",
             is_disqualified as "is_disqualified!", 
             disqualification_reason, 
+            did_not_finish as "did_not_finish!", 
+            status_changed_at, 
+            is_bombed_out as "is_bombed_out!", 
             broke_record as "broke_record!", 
             record_type, 
             calculated_at as "calculated_at!"
-        FROM results
-        WHERE contest_id = ? AND NOT is_disqualified
-        ORDER BY place_in_weight_class
+        FROM ranked_results
+        WHERE contest_id = ? AND (is_disqualified OR did_not_finish)
+        ORDER BY is_disqualified DESC, status_changed_at
         "#,
         contest_id
     )
@@ -227,100 +791,131 @@ pub async fn get_weight_class_ranking(
     .await
 }
 
-/// Update all rankings for a contest (implements triple ranking system)
-pub async fn update_all_rankings(pool: &Pool<Sqlite>, contest_id: &str) -> Result<(), sqlx::Error> {
-    // 1. Update open rankings (overall)
-    update_open_rankings(pool, contest_id).await?;
-
-    // 2. Update age class rankings
-    update_age_class_rankings(pool, contest_id).await?;
-
-    // 3. Update weight class rankings
-    update_weight_class_rankings(pool, contest_id).await?;
-
-    Ok(())
-}
-
-/// Update open rankings (OPEN.csv equivalent)
-async fn update_open_rankings(pool: &Pool<Sqlite>, contest_id: &str) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        r#"
-        UPDATE results 
-        SET place_open = (
-            SELECT COUNT(*) + 1 
-            FROM results r2 
-            WHERE r2.contest_id = results.contest_id 
-            AND r2.coefficient_points > results.coefficient_points
-            AND NOT r2.is_disqualified
-        )
-        WHERE contest_id = ? AND NOT is_disqualified
-        "#,
-        contest_id
-    )
-    .execute(pool)
-    .await?;
-
-    Ok(())
-}
-
-/// Update age class rankings (KATEGORIE WIEKOWE.csv equivalent)
-async fn update_age_class_rankings(
+/// Get the team classification of a contest from the lifters' weight class placings
+pub async fn get_team_rankings(
     pool: &Pool<Sqlite>,
     contest_id: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+) -> Result<Vec<TeamRanking>, sqlx::Error> {
+    let team_scoring = contests::get_contest_by_id(pool, contest_id)
+        .await?
+        .map(|contest| contest.team_scoring)
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    let entries = sqlx::query!(
         r#"
-        UPDATE results 
-        SET place_in_age_class = (
-            SELECT COUNT(*) + 1 
-            FROM results r2
-            JOIN registrations reg2 ON reg2.id = r2.registration_id
-            JOIN registrations reg1 ON reg1.id = results.registration_id
-            WHERE r2.contest_id = results.contest_id 
-            AND reg2.age_category_id = reg1.age_category_id
-            AND r2.coefficient_points > results.coefficient_points
-            AND NOT r2.is_disqualified
-        )
-        WHERE contest_id = ? AND NOT is_disqualified
+        SELECT
+            r.registration_id as "registration_id!",
+            c.first_name || ' ' || c.last_name as "competitor_name!: String",
+            c.club as "club!",
+            p.place as "place!"
+        FROM results r
+        JOIN division_placings p ON p.result_id = r.id AND p.division_id = ?
+        JOIN registrations reg ON reg.id = r.registration_id
+        JOIN competitors c ON c.id = reg.competitor_id
+        WHERE r.contest_id = ? AND c.club IS NOT NULL
         "#,
+        WEIGHT_CLASS_DIVISION,
         contest_id
     )
-    .execute(pool)
-    .await?;
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| TeamEntry {
+        registration_id: row.registration_id,
+        competitor_name: row.competitor_name,
+        club: row.club,
+        place: row.place,
+    })
+    .collect();
 
-    Ok(())
+    Ok(team_ranking::rank_teams(entries, &team_scoring))
 }
 
-/// Update weight class rankings (KATEGORIE WAGOWE.csv equivalent)
-async fn update_weight_class_rankings(
+/// Get the best lifter awards of a contest, decided by coefficient points
+pub async fn get_best_lifter_awards(
     pool: &Pool<Sqlite>,
     contest_id: &str,
-) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+) -> Result<Vec<BestLifterAward>, sqlx::Error> {
+    let contest = contests::get_contest_by_id(pool, contest_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    // Every registration is an entrant; lifters without a place cannot win
+    let entries = sqlx::query!(
         r#"
-        UPDATE results 
-        SET place_in_weight_class = (
-            SELECT COUNT(*) + 1 
-            FROM results r2
-            JOIN registrations reg2 ON reg2.id = r2.registration_id
-            JOIN registrations reg1 ON reg1.id = results.registration_id
-            WHERE r2.contest_id = results.contest_id 
-            AND reg2.weight_class_id = reg1.weight_class_id
-            AND r2.coefficient_points > results.coefficient_points
-            AND NOT r2.is_disqualified
-        )
-        WHERE contest_id = ? AND NOT is_disqualified
+        SELECT
+            reg.id as "registration_id!",
+            c.first_name || ' ' || c.last_name as "competitor_name!: String",
+            c.gender as "gender!",
+            ac.name as "age_category!",
+            reg.equipment_m as "equipment_m!: bool",
+            reg.equipment_sm as "equipment_sm!: bool",
+            reg.equipment_t as "equipment_t!: bool",
+            reg.bodyweight as "bodyweight!",
+            reg.lot_number,
+            r.id as "result_id?",
+            r.coefficient_points as "coefficient_points?",
+            p.place as "place?",
+            (
+                SELECT MAX(a.achieved_at)
+                FROM attempts a
+                WHERE a.registration_id = reg.id
+                AND a.status = 'Successful' AND a.attempt_number < ?
+                AND a.weight = (
+                    SELECT MAX(a2.weight)
+                    FROM attempts a2
+                    WHERE a2.registration_id = a.registration_id
+                    AND a2.lift_type = a.lift_type
+                    AND a2.status = 'Successful' AND a2.attempt_number < ?
+                )
+            ) as "achieved_at: i64"
+        FROM registrations reg
+        JOIN competitors c ON c.id = reg.competitor_id
+        JOIN age_categories ac ON ac.id = reg.age_category_id
+        LEFT JOIN results r ON r.registration_id = reg.id
+        LEFT JOIN division_placings p ON p.result_id = r.id AND p.division_id = ?
+        WHERE reg.contest_id = ?
         "#,
+        RECORD_ATTEMPT_NUMBER,
+        RECORD_ATTEMPT_NUMBER,
+        OPEN_DIVISION,
         contest_id
     )
-    .execute(pool)
-    .await?;
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| {
+        // Placed lifters are exactly the ones in the rankings
+        let ranked = match (row.result_id, row.coefficient_points, row.place) {
+            (Some(result_id), Some(points), Some(_)) => Some(RankEntry {
+                result_id,
+                score: points,
+                bodyweight: row.bodyweight,
+                achieved_at: row.achieved_at,
+                lot_number: row.lot_number,
+            }),
+            _ => None,
+        };
+        BestLifterEntry {
+            registration_id: row.registration_id,
+            competitor_name: row.competitor_name,
+            gender: row.gender,
+            age_category: row.age_category,
+            equipment: Equipment::from_flags(row.equipment_m, row.equipment_sm, row.equipment_t),
+            ranked,
+        }
+    })
+    .collect();
 
-    Ok(())
+    Ok(best_lifter::award_best_lifters(
+        entries,
+        &contest.best_lifter,
+        &contest.tie_breaks,
+    ))
 }
 
-/// Get rankings by type (for generating CSV-like reports)
-pub async fn get_open_ranking(
+/// Get results without a total (bomb-outs) for a contest
+pub async fn get_bombed_out_results(
     pool: &Pool<Sqlite>,
     contest_id: &str,
 ) -> Result<Vec<CompetitionResult>, sqlx::Error> {
@@ -334,19 +929,26 @@ pub async fn get_open_ranking(
             best_bench, 
             best_squat, 
             best_deadlift,
+            record_attempt_squat,
+            record_attempt_bench,
+            record_attempt_deadlift,
             total_weight as "total_weight!", 
             coefficient_points as "coefficient_points!", 
-            place_open, 
-            place_in_age_class, 
-            place_in_weight_class,
+            scoring_formula as "scoring_formula!: ScoringFormulaKind",
+            place_open as "place_open?",
+            place_in_age_class as "place_in_age_class?",
+            place_in_weight_class as "place_in_weight_class?",
             is_disqualified as "is_disqualified!", 
             disqualification_reason, 
+            did_not_finish as "did_not_finish!", 
+            status_changed_at, 
+            is_bombed_out as "is_bombed_out!", 
             broke_record as "broke_record!", 
             record_type, 
             calculated_at as "calculated_at!"
-        FROM results 
-        WHERE contest_id = ? AND NOT is_disqualified
-        ORDER BY place_open
+        FROM ranked_results
+        WHERE contest_id = ? AND is_bombed_out AND NOT is_disqualified AND NOT did_not_finish
+        ORDER BY registration_id
         "#,
         contest_id
     )
@@ -354,23 +956,76 @@ pub async fn get_open_ranking(
     .await
 }
 
-/// Helper function to get best lift weight
-async fn get_best_lift_weight(
-    pool: &Pool<Sqlite>,
+/// Whether every attempt of a lift has been judged without a successful one
+async fn lift_failed<'e, E>(
+    executor: E,
     registration_id: &str,
     lift_type: &str,
-) -> Result<Option<f64>, sqlx::Error> {
+) -> Result<bool, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) > 0 AND COALESCE(SUM(status IN ('Successful', 'Pending')), 0) = 0
+            as "failed!: bool"
+        FROM attempts
+        WHERE registration_id = ? AND lift_type = ? AND attempt_number < ?
+        "#,
+        registration_id,
+        lift_type,
+        RECORD_ATTEMPT_NUMBER
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// Helper function to get best lift weight; 4th (record) attempts do not count
+async fn get_best_lift_weight<'e, E>(
+    executor: E,
+    registration_id: &str,
+    lift_type: &str,
+) -> Result<Option<f64>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
     let best_weight: Option<f64> = sqlx::query_scalar!(
         r#"
         SELECT MAX(weight) as "max_weight: f64"
         FROM attempts 
         WHERE registration_id = ? AND lift_type = ? AND status = 'Successful'
+        AND attempt_number < ?
         "#,
         registration_id,
-        lift_type
+        lift_type,
+        RECORD_ATTEMPT_NUMBER
     )
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(best_weight)
 }
+
+/// Weight of a successful 4th (record) attempt
+async fn get_record_attempt_weight<'e, E>(
+    executor: E,
+    registration_id: &str,
+    lift_type: &str,
+) -> Result<Option<f64>, sqlx::Error>
+where
+    E: Executor<'e, Database = Sqlite>,
+{
+    sqlx::query_scalar!(
+        r#"
+        SELECT MAX(weight) as "max_weight: f64"
+        FROM attempts
+        WHERE registration_id = ? AND lift_type = ? AND status = 'Successful'
+        AND attempt_number = ?
+        "#,
+        registration_id,
+        lift_type,
+        RECORD_ATTEMPT_NUMBER
+    )
+    .fetch_one(executor)
+    .await
+}
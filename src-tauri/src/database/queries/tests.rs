@@ -2,7 +2,9 @@
 mod tests {
     use crate::database::queries::*;
     use crate::database::DatabasePool;
-    use crate::models::contest::{ContestStatus, Discipline, NewContest};
+    use crate::models::contest::{
+        AgeRule, ContestStatus, Discipline, NewContest, ScoringFormulaKind,
+    };
     use chrono::NaiveDate;
 
     async fn setup_test_db() -> DatabasePool {
@@ -296,6 +298,14 @@ mod tests {
             competition_type: Some("Regional".to_string()),
             organizer: Some("Test Organizer".to_string()),
             notes: Some("Test contest notes".to_string()),
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = create_contest(&pool, new_contest)
@@ -323,6 +333,14 @@ mod tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let created = create_contest(&pool, new_contest)
@@ -355,6 +373,14 @@ mod tests {
                 competition_type: None,
                 organizer: None,
                 notes: None,
+                scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
             },
             NewContest {
                 name: "Contest B".to_string(),
@@ -365,6 +391,14 @@ mod tests {
                 competition_type: None,
                 organizer: None,
                 notes: None,
+                scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
             },
         ];
 
@@ -397,6 +431,14 @@ mod tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = create_contest(&pool, new_contest)
@@ -412,6 +454,7 @@ mod tests {
         updated_contest.competition_type = Some("National".to_string());
         updated_contest.organizer = Some("New Organizer".to_string());
         updated_contest.notes = Some("Updated notes".to_string());
+        updated_contest.scoring_formula = ScoringFormulaKind::IpfGl;
 
         update_contest(&pool, &contest.id, updated_contest)
             .await
@@ -430,6 +473,7 @@ mod tests {
         assert_eq!(updated.competition_type, Some("National".to_string()));
         assert_eq!(updated.organizer, Some("New Organizer".to_string()));
         assert_eq!(updated.notes, Some("Updated notes".to_string()));
+        assert_eq!(updated.scoring_formula, ScoringFormulaKind::IpfGl);
     }
 
     #[tokio::test]
@@ -445,6 +489,14 @@ mod tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = create_contest(&pool, new_contest)
@@ -482,6 +534,14 @@ mod tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = create_contest(&pool, new_contest)
@@ -553,6 +613,14 @@ mod tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = create_contest(&pool, new_contest)
@@ -717,6 +785,14 @@ mod tests {
                 competition_type: None,
                 organizer: None,
                 notes: None,
+                scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
             };
 
             create_contest(&pool, new_contest)
@@ -743,4 +819,87 @@ mod tests {
             NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
         );
     }
+
+    // Category tests
+    #[tokio::test]
+    async fn test_find_weight_class_uses_class_set() {
+        let pool = setup_test_db().await;
+
+        let class = find_weight_class(&pool, "PZKFiTS", "Male", 82.5)
+            .await
+            .expect("Failed to find weight class")
+            .expect("No class for 82.5 kg");
+        assert_eq!(class.id, "M_82_5");
+
+        let class = find_weight_class(&pool, "PZKFiTS", "Female", 63.0)
+            .await
+            .expect("Failed to find weight class")
+            .expect("No class for 63 kg");
+        assert_eq!(class.id, "F_63");
+
+        let class = find_weight_class(&pool, "IPF", "Male", 82.5)
+            .await
+            .expect("Failed to find weight class")
+            .expect("No IPF class for 82.5 kg");
+        assert_eq!(class.id, "IPF_M_83");
+
+        let class = find_weight_class(&pool, "IPF", "Female", 90.0)
+            .await
+            .expect("Failed to find weight class")
+            .expect("No IPF class for 90 kg");
+        assert_eq!(class.id, "IPF_F_84_PLUS");
+
+        let class = find_weight_class(&pool, "IPF", "Unknown", 82.5)
+            .await
+            .expect("Failed to query weight class");
+        assert!(class.is_none());
+
+        let sets = get_weight_class_sets(&pool)
+            .await
+            .expect("Failed to get class sets");
+        assert_eq!(sets, vec!["IPF".to_string(), "PZKFiTS".to_string()]);
+
+        let ipf_classes = get_weight_classes(&pool, Some("IPF"))
+            .await
+            .expect("Failed to get IPF classes");
+        assert_eq!(ipf_classes.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_contest_weight_class_set_and_age_rule() {
+        let pool = setup_test_db().await;
+
+        let new_contest = NewContest {
+            name: "IPF Classic".to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 5, 4).unwrap(),
+            location: "Warsaw".to_string(),
+            discipline: Discipline::Powerlifting,
+            federation_rules: None,
+            competition_type: None,
+            organizer: None,
+            notes: None,
+            scoring_formula: None,
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
+        };
+        let contest = create_contest(&pool, new_contest)
+            .await
+            .expect("Failed to create contest");
+        assert_eq!(contest.weight_class_set, "PZKFiTS");
+        assert_eq!(contest.age_rule, AgeRule::ExactDate);
+
+        let mut contest = contest;
+        contest.weight_class_set = "IPF".to_string();
+        contest.age_rule = AgeRule::BirthYear;
+        let updated = update_contest(&pool, &contest.id.clone(), contest)
+            .await
+            .expect("Failed to update contest");
+        assert_eq!(updated.weight_class_set, "IPF");
+        assert_eq!(updated.age_rule, AgeRule::BirthYear);
+    }
 }
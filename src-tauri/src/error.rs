@@ -17,6 +17,13 @@ pub enum AppError {
     #[error("Contest not found: {id}")]
     ContestNotFound { id: String },
 
+    #[error("Contest {contest_id} has an invalid {field}: {value}")]
+    InvalidContestData {
+        contest_id: String,
+        field: String,
+        value: String,
+    },
+
     #[error("Contest state not found for contest: {contest_id}")]
     ContestStateNotFound { contest_id: String },
 
@@ -41,6 +48,18 @@ pub enum AppError {
     #[error("Photo processing error: {0}")]
     PhotoProcessing(#[from] crate::database::queries::competitors::PhotoProcessError),
 
+    #[error("Coefficient error: {0}")]
+    Coefficient(#[from] crate::coefficients::CoefficientError),
+
+    #[error("Federation error: {0}")]
+    Federation(#[from] crate::federation::FederationError),
+
+    #[error("Excel export error: {0}")]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+
+    #[error("PDF export error: {0}")]
+    Pdf(#[from] printpdf::Error),
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
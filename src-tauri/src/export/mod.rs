@@ -0,0 +1,557 @@
+//! Results exports.
+//!
+//! An export holds a contest's rankings as rows with the lifters' details and attempts,
+//! together with its named divisions, team classification, best lifter awards and the
+//! records broken in it. It can be narrowed to one of the rankings or to one named division,
+//! and is rendered as CSV, JSON, an XLSX workbook or a PDF protocol; the Tauri commands and
+//! the CLI share it.
+
+pub mod pdf;
+pub mod xlsx;
+
+use crate::best_lifter::BestLifterAward;
+use crate::database::queries;
+use crate::database::queries::divisions::{DivisionPlacing, DivisionRanking};
+use crate::database::queries::rankings::{RankingAttempt, RankingRow};
+use crate::database::queries::results::CompetitionResult;
+use crate::error::AppError;
+use crate::models::attempt::{AttemptStatus, LiftType, RECORD_ATTEMPT_NUMBER};
+use crate::models::contest::{Contest, RankingConfig, RankingRule, RankingSplit};
+use crate::models::division::{Division, OPEN_DIVISION};
+use crate::models::record::Record;
+use crate::scoring;
+use crate::team_ranking::TeamRanking;
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+
+/// Contents of a results export
+#[derive(serde::Serialize)]
+pub struct ExportData {
+    pub contest: Contest,
+    pub rankings: Vec<RankingRow>,
+    pub divisions: Vec<DivisionRanking>,
+    // Disqualified and DNF lifters
+    pub excluded: Vec<RankingRow>,
+    pub teams: Vec<TeamRanking>,
+    pub best_lifters: Vec<BestLifterAward>,
+    pub records: Vec<BrokenRecord>,
+    // Set when the export was narrowed to one ranking or one named division
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<RankingKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub division: Option<Division>,
+}
+
+/// One of the built-in rankings of a contest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingKind {
+    Open,
+    AgeClass,
+    WeightClass,
+}
+
+/// A record set in the contest with the names of its categories
+#[derive(serde::Serialize)]
+pub struct BrokenRecord {
+    #[serde(flatten)]
+    pub record: Record,
+    pub weight_class_name: String,
+    pub age_category_name: String,
+}
+
+/// Load everything a results export of a contest lists
+pub async fn load_export_data(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+) -> Result<ExportData, AppError> {
+    let contest = queries::contests::get_contest_by_id(pool, contest_id)
+        .await?
+        .ok_or_else(|| AppError::ContestNotFound {
+            id: contest_id.to_string(),
+        })?;
+
+    // Bomb-outs have no place but are listed after the ranked lifters;
+    // disqualified and DNF lifters follow in a block of their own
+    let mut rankings =
+        queries::divisions::get_division_ranking(pool, contest_id, OPEN_DIVISION, None).await?;
+    rankings.extend(
+        queries::results::get_bombed_out_results(pool, contest_id)
+            .await?
+            .into_iter()
+            .map(|result| DivisionPlacing {
+                place: None,
+                result,
+            }),
+    );
+    let excluded = queries::results::get_excluded_results(pool, contest_id)
+        .await?
+        .into_iter()
+        .map(|result| DivisionPlacing {
+            place: None,
+            result,
+        })
+        .collect();
+
+    let mut records = Vec::new();
+    for record in queries::records::get_records_by_contest(pool, contest_id).await? {
+        let weight_class =
+            queries::categories::get_weight_class_by_id(pool, &record.weight_class_id)
+                .await?
+                .map(|class| class.name)
+                .unwrap_or_else(|| record.weight_class_id.clone());
+        let age_category =
+            queries::categories::get_age_category_by_id(pool, &record.age_category_id)
+                .await?
+                .map(|category| category.name)
+                .unwrap_or_else(|| record.age_category_id.clone());
+        records.push(BrokenRecord {
+            record,
+            weight_class_name: weight_class,
+            age_category_name: age_category,
+        });
+    }
+
+    Ok(ExportData {
+        contest,
+        rankings: queries::rankings::get_ranking_rows(pool, contest_id, rankings).await?,
+        // A lifter appears in every division they match or entered
+        divisions: queries::divisions::get_division_rankings(pool, contest_id, None).await?,
+        excluded: queries::rankings::get_ranking_rows(pool, contest_id, excluded).await?,
+        teams: queries::results::get_team_rankings(pool, contest_id).await?,
+        best_lifters: queries::results::get_best_lifter_awards(pool, contest_id).await?,
+        records,
+        ranking: None,
+        division: None,
+    })
+}
+
+/// Find a contest by its ID or, failing that, by its name
+pub async fn find_contest(pool: &Pool<Sqlite>, reference: &str) -> Result<Contest, AppError> {
+    if let Some(contest) = queries::contests::get_contest_by_id(pool, reference).await? {
+        return Ok(contest);
+    }
+
+    let mut named: Vec<Contest> = queries::contests::get_all_contests(pool)
+        .await?
+        .into_iter()
+        .filter(|contest| contest.name.trim().eq_ignore_ascii_case(reference.trim()))
+        .collect();
+    match named.len() {
+        0 => Err(AppError::ContestNotFound {
+            id: reference.to_string(),
+        }),
+        1 => Ok(named.remove(0)),
+        _ => Err(AppError::InvalidInput {
+            field: "contest".to_string(),
+            reason: format!(
+                "{} contests are named {}; use one of their IDs: {}",
+                named.len(),
+                reference,
+                named
+                    .iter()
+                    .map(|contest| format!("{} ({})", contest.id, contest.date))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }),
+    }
+}
+
+impl ExportData {
+    /// List only one of the built-in rankings, its lifters placed and ordered within its
+    /// groups
+    pub fn select_ranking(&mut self, kind: RankingKind) {
+        let section = RankingSection::of(&self.contest.ranking, kind);
+        let rows: Vec<&RankingRow> = self.rankings.iter().collect();
+        let rankings = ranking_groups(&rows, &section)
+            .into_iter()
+            .flat_map(|(_, members)| members)
+            .map(|row| RankingRow {
+                place: (section.place)(row),
+                ..row.clone()
+            })
+            .collect();
+        self.rankings = rankings;
+        self.ranking = Some(kind);
+    }
+
+    /// List only the lifters of a named division, given by its ID or name, with their
+    /// places in it
+    pub fn select_division(&mut self, reference: &str) -> Result<(), AppError> {
+        let Some(index) = self.divisions.iter().position(|ranking| {
+            ranking.division.id == reference
+                || ranking
+                    .division
+                    .name
+                    .trim()
+                    .eq_ignore_ascii_case(reference.trim())
+        }) else {
+            let names = self
+                .divisions
+                .iter()
+                .map(|ranking| ranking.division.name.as_str())
+                .collect::<Vec<_>>();
+            return Err(AppError::InvalidInput {
+                field: "division".to_string(),
+                reason: if names.is_empty() {
+                    format!("Contest {} has no divisions", self.contest.name)
+                } else {
+                    format!(
+                        "Contest {} has no division {}; its divisions are: {}",
+                        self.contest.name,
+                        reference,
+                        names.join(", ")
+                    )
+                },
+            });
+        };
+
+        let ranking = self.divisions.swap_remove(index);
+        self.rankings = ranking.placings.clone();
+        self.division = Some(ranking.division.clone());
+        self.divisions = vec![ranking];
+        // Contest-wide classifications do not apply to a single division
+        self.excluded.clear();
+        self.teams.clear();
+        self.best_lifters.clear();
+        Ok(())
+    }
+}
+
+pub async fn export_to_csv(data: &ExportData) -> Result<String, AppError> {
+    let mut csv_content = String::new();
+    let lifts = data.contest.discipline.lifts();
+
+    // Header; lifters are placed within their equipment division
+    csv_content.push_str(
+        "Place,Name,Club,City,Sex,Birth Year,Bodyweight,Weight Class,Age Category,Equipment",
+    );
+    for lift in lifts {
+        for attempt_number in 1..RECORD_ATTEMPT_NUMBER {
+            csv_content.push_str(&format!(",{} {}", lift, attempt_number));
+        }
+    }
+    csv_content.push_str(
+        ",Best Squat,Best Bench,Best Deadlift,Total,Coefficient,Coefficient Points,Formula\n",
+    );
+
+    // Data rows
+    for row in &data.rankings {
+        let result = &row.result;
+        let place = if result.is_bombed_out {
+            "-".to_string()
+        } else {
+            row.place.unwrap_or(0).to_string()
+        };
+        csv_content.push_str(&format!(
+            "{},{},{},{},{},{},{:.2},{},{},{}",
+            place,
+            csv_field(&row.name()),
+            csv_field(row.club.as_deref().unwrap_or("")),
+            csv_field(row.city.as_deref().unwrap_or("")),
+            row.gender,
+            row.birth_year
+                .map(|year| year.to_string())
+                .unwrap_or_default(),
+            row.bodyweight,
+            csv_field(&row.weight_class_name),
+            csv_field(&row.age_category_name),
+            row.equipment
+        ));
+        for lift in lifts {
+            for attempt_number in 1..RECORD_ATTEMPT_NUMBER {
+                csv_content.push(',');
+                csv_content.push_str(&attempt_cell(row.attempt(lift, attempt_number)));
+            }
+        }
+        csv_content.push_str(&format!(
+            ",{:.1},{:.1},{:.1},{:.1},{},{:.2},{}\n",
+            result.best_squat.unwrap_or(0.0),
+            result.best_bench.unwrap_or(0.0),
+            result.best_deadlift.unwrap_or(0.0),
+            result.total_weight,
+            row.scoring_coefficient
+                .map(|coefficient| format!("{:.4}", coefficient))
+                .unwrap_or_default(),
+            result.coefficient_points,
+            scoring::formula(result.scoring_formula).name()
+        ));
+    }
+
+    // Record attempts block; 4th attempts are not part of the totals
+    let record_attempts: Vec<&RankingRow> = data
+        .rankings
+        .iter()
+        .chain(&data.excluded)
+        .filter(|row| {
+            row.result.record_attempt_squat.is_some()
+                || row.result.record_attempt_bench.is_some()
+                || row.result.record_attempt_deadlift.is_some()
+        })
+        .collect();
+    if !record_attempts.is_empty() {
+        csv_content.push_str("\nRecord Attempts\nLifter,4th Squat,4th Bench,4th Deadlift\n");
+        for row in record_attempts {
+            let weight = |lift: Option<f64>| lift.map(|w| format!("{:.1}", w)).unwrap_or_default();
+            csv_content.push_str(&format!(
+                "{},{},{},{}\n",
+                csv_field(&row.name()),
+                weight(row.result.record_attempt_squat),
+                weight(row.result.record_attempt_bench),
+                weight(row.result.record_attempt_deadlift)
+            ));
+        }
+    }
+
+    // DSQ/DNF block
+    if !data.excluded.is_empty() {
+        csv_content.push_str("\nStatus,Lifter,Club,Best Squat,Best Bench,Best Deadlift,Reason\n");
+        for row in &data.excluded {
+            let result = &row.result;
+            csv_content.push_str(&format!(
+                "{},{},{},{:.1},{:.1},{:.1},{}\n",
+                status_label(result),
+                csv_field(&row.name()),
+                csv_field(row.club.as_deref().unwrap_or("")),
+                result.best_squat.unwrap_or(0.0),
+                result.best_bench.unwrap_or(0.0),
+                result.best_deadlift.unwrap_or(0.0),
+                csv_field(result.disqualification_reason.as_deref().unwrap_or(""))
+            ));
+        }
+    }
+
+    // Named divisions block
+    if data
+        .divisions
+        .iter()
+        .any(|division| !division.placings.is_empty())
+    {
+        csv_content.push_str("\nDivision,Place,Lifter,Club,Total,Coefficient Points\n");
+        for division in &data.divisions {
+            for row in &division.placings {
+                csv_content.push_str(&format!(
+                    "{},{},{},{},{:.1},{:.2}\n",
+                    csv_field(&division.division.name),
+                    row.place.unwrap_or(0),
+                    csv_field(&row.name()),
+                    csv_field(row.club.as_deref().unwrap_or("")),
+                    row.result.total_weight,
+                    row.result.coefficient_points
+                ));
+            }
+        }
+    }
+
+    // Team classification block
+    if !data.teams.is_empty() {
+        csv_content.push_str("\nTeam Place,Club,Team Points,Scoring Lifters\n");
+        for team in &data.teams {
+            let scorers = team
+                .scorers
+                .iter()
+                .map(|scorer| format!("{} ({})", scorer.competitor_name, scorer.points))
+                .collect::<Vec<_>>()
+                .join("; ");
+            csv_content.push_str(&format!(
+                "{},{},{},{}\n",
+                team.place,
+                csv_field(&team.club),
+                team.points,
+                csv_field(&scorers)
+            ));
+        }
+    }
+
+    // Best lifter block; groups with too few entrants are not awarded
+    if data.best_lifters.iter().any(|award| award.awarded) {
+        csv_content.push_str("\nBest Lifter,Entrants,Lifter,Coefficient Points\n");
+        for award in &data.best_lifters {
+            for winner in &award.winners {
+                csv_content.push_str(&format!(
+                    "{},{},{},{:.2}\n",
+                    csv_field(&award.group.label()),
+                    award.entrants,
+                    csv_field(&winner.competitor_name),
+                    winner.points
+                ));
+            }
+        }
+    }
+
+    Ok(csv_content)
+}
+
+// Weight of a judged attempt; failed attempts are negative as on federation result sheets,
+// pending and skipped ones are left empty
+fn attempt_cell(attempt: Option<&RankingAttempt>) -> String {
+    match attempt {
+        Some(attempt) if attempt.status == AttemptStatus::Successful => {
+            format!("{:.1}", attempt.weight)
+        }
+        Some(attempt) if attempt.status == AttemptStatus::Failed => {
+            format!("{:.1}", -attempt.weight)
+        }
+        _ => String::new(),
+    }
+}
+
+fn status_label(result: &CompetitionResult) -> &'static str {
+    if result.is_disqualified {
+        "DSQ"
+    } else {
+        "DNF"
+    }
+}
+
+// Quote free text so commas and quotes in it cannot break the row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub async fn export_to_json(data: &ExportData) -> Result<String, AppError> {
+    serde_json::to_string_pretty(data)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize results to JSON: {}", e)))
+}
+
+/// One of the built-in rankings as listed in the workbook and the protocol
+struct RankingSection<'a> {
+    rule: &'a RankingRule,
+    // Category the ranking is split into on top of the rule's splits, by the ID lifters are
+    // placed by and the name it is titled with
+    category: fn(&RankingRow) -> Option<(&str, &str)>,
+    place: fn(&RankingRow) -> Option<i64>,
+    // Weight classes are listed from the lightest up rather than by name
+    order_by_bodyweight: bool,
+}
+
+impl<'a> RankingSection<'a> {
+    fn of(ranking: &'a RankingConfig, kind: RankingKind) -> Self {
+        match kind {
+            RankingKind::Open => Self {
+                rule: &ranking.open,
+                category: |_| None,
+                place: |row| row.result.place_open,
+                order_by_bodyweight: false,
+            },
+            RankingKind::AgeClass => Self {
+                rule: &ranking.age_class,
+                category: |row| Some((&row.age_category_id, &row.age_category_name)),
+                place: |row| row.result.place_in_age_class,
+                order_by_bodyweight: false,
+            },
+            RankingKind::WeightClass => Self {
+                rule: &ranking.weight_class,
+                category: |row| Some((&row.weight_class_id, &row.weight_class_name)),
+                place: |row| row.result.place_in_weight_class,
+                order_by_bodyweight: true,
+            },
+        }
+    }
+}
+
+// IDs of a group's splits and its category, which lifters are grouped by
+type GroupKey = (Vec<String>, String);
+// Labels of a group's splits and its category
+type GroupTitle = (Vec<String>, String);
+
+/// Split the rows of a section into its ranking groups, titled by their splits and category.
+/// Lifters are grouped by the IDs they are placed by, as categories of different sexes can
+/// share a name.
+fn ranking_groups<'r>(
+    rows: &[&'r RankingRow],
+    section: &RankingSection,
+) -> Vec<(String, Vec<&'r RankingRow>)> {
+    let mut groups: BTreeMap<GroupKey, (GroupTitle, Vec<&RankingRow>)> = BTreeMap::new();
+    for &row in rows {
+        let (split_ids, split_labels): (Vec<String>, Vec<String>) = section
+            .rule
+            .split_by
+            .iter()
+            .map(|split| match split {
+                RankingSplit::Sex => (row.gender.clone(), sex_label(&row.gender).to_string()),
+                RankingSplit::Equipment => (row.equipment.to_string(), row.equipment.to_string()),
+                RankingSplit::AgeCategory => {
+                    (row.age_category_id.clone(), row.age_category_name.clone())
+                }
+            })
+            .unzip();
+        let (category_id, category) = (section.category)(row).unwrap_or_default();
+        groups
+            .entry((split_ids, category_id.to_string()))
+            .or_insert_with(|| ((split_labels, category.to_string()), Vec::new()))
+            .1
+            .push(row);
+    }
+
+    let mut groups: Vec<(GroupTitle, Vec<&RankingRow>)> = groups.into_values().collect();
+    if section.order_by_bodyweight {
+        let lightest = |members: &[&RankingRow]| {
+            members
+                .iter()
+                .map(|row| row.bodyweight)
+                .fold(f64::INFINITY, f64::min)
+        };
+        groups.sort_by(|(a_title, a), (b_title, b)| {
+            a_title
+                .0
+                .cmp(&b_title.0)
+                .then(lightest(a).total_cmp(&lightest(b)))
+        });
+    } else {
+        groups.sort_by(|(a_title, _), (b_title, _)| a_title.cmp(b_title));
+    }
+
+    groups
+        .into_iter()
+        .map(|((splits, category), mut members)| {
+            // Placed lifters first; unplaced ones keep their order below them
+            members.sort_by_key(|row| (section.place)(row).unwrap_or(i64::MAX));
+            let title = splits
+                .into_iter()
+                .chain(Some(category).filter(|category| !category.is_empty()))
+                .collect::<Vec<_>>()
+                .join(" / ");
+            (title, members)
+        })
+        .collect()
+}
+
+fn lift_label(lift: &LiftType) -> &'static str {
+    match lift {
+        LiftType::Squat => "Przysiad",
+        LiftType::Bench => "Wyciskanie",
+        LiftType::Deadlift => "Martwy ciąg",
+    }
+}
+
+fn sex_label(gender: &str) -> &str {
+    match gender {
+        "Male" => "Mężczyźni",
+        "Female" => "Kobiety",
+        other => other,
+    }
+}
+
+/// Labelled details of a contest for the header of an export
+fn contest_details(contest: &Contest) -> Vec<(&'static str, String)> {
+    [
+        ("Data", Some(contest.date.to_string())),
+        ("Miejsce", Some(contest.location.clone())),
+        ("Organizator", contest.organizer.clone()),
+        ("Dyscyplina", Some(contest.discipline.to_string())),
+        ("Federacja", Some(contest.federation_profile.clone())),
+        (
+            "Formuła",
+            Some(scoring::formula(contest.scoring_formula).name().to_string()),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(label, value)| value.map(|value| (label, value)))
+    .collect()
+}
@@ -0,0 +1,438 @@
+//! Contest protocol (protokół) as a PDF.
+//!
+//! The protocol opens with the contest's details and lists every weight category, or the
+//! groups of the ranking or division the export was narrowed to, as a table with the lifters'
+//! attempts, failed ones struck through, their totals, points and places. The records broken
+//! in the contest and the lines the jury signs the protocol on follow.
+//! Text is set in DejaVu Sans, embedded into the file, so Polish letters keep their
+//! diacritics.
+
+use super::{
+    contest_details, lift_label, ranking_groups, sex_label, status_label, ExportData, RankingKind,
+    RankingSection,
+};
+use crate::database::queries::rankings::RankingRow;
+use crate::models::attempt::{AttemptStatus, LiftType, RECORD_ATTEMPT_NUMBER};
+use crate::models::record::{RecordLift, RecordScope};
+use printpdf::{
+    Error, IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point,
+};
+
+// A4 landscape, in millimetres
+const PAGE_WIDTH: f32 = 297.0;
+const PAGE_HEIGHT: f32 = 210.0;
+const MARGIN: f32 = 12.0;
+const ROW_HEIGHT: f32 = 5.0;
+const FONT_SIZE: f32 = 8.0;
+const MM_PER_PT: f32 = 0.3528;
+const REGULAR_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans.ttf");
+const BOLD_FONT: &[u8] = include_bytes!("../../fonts/DejaVuSans-Bold.ttf");
+
+/// A table column; numbers are right-aligned
+struct Column {
+    header: &'static str,
+    width: f32,
+    numeric: bool,
+}
+
+const fn column(header: &'static str, width: f32, numeric: bool) -> Column {
+    Column {
+        header,
+        width,
+        numeric,
+    }
+}
+
+const LIFTER_COLUMNS: [Column; 6] = [
+    column("M-ce", 9.0, true),
+    column("Zawodnik", 40.0, false),
+    column("Klub", 27.0, false),
+    column("Rocznik", 13.0, true),
+    column("Waga", 12.0, true),
+    column("Kat. wiekowa", 22.0, false),
+];
+const ATTEMPT_COLUMN_WIDTH: f32 = 12.0;
+const RESULT_COLUMNS: [Column; 3] = [
+    column("Wynik", 14.0, true),
+    column("Wsp.", 14.0, true),
+    column("Punkty", 14.0, true),
+];
+const RECORD_COLUMNS: [Column; 8] = [
+    column("Zasięg", 40.0, false),
+    column("Bój", 24.0, false),
+    column("Ciężar", 16.0, true),
+    column("Zawodnik", 44.0, false),
+    column("Płeć", 18.0, false),
+    column("Kat. wagowa", 22.0, false),
+    column("Kat. wiekowa", 24.0, false),
+    column("Sprzęt", 16.0, false),
+];
+const SIGNATURES: [&str; 4] = [
+    "Sędzia główny",
+    "Przewodniczący jury",
+    "Członek jury",
+    "Sekretarz zawodów",
+];
+
+/// Write the protocol of a contest to a file
+pub fn save_protocol(data: &ExportData, path: &str) -> Result<(), Error> {
+    std::fs::write(path, build_protocol(data)?)?;
+    Ok(())
+}
+
+/// Build the protocol of a contest as the bytes of a PDF file
+pub fn build_protocol(data: &ExportData) -> Result<Vec<u8>, Error> {
+    let mut protocol = Protocol::new(&data.contest.name)?;
+    protocol.write_header(data);
+
+    let lifts = data.contest.discipline.lifts();
+    let rows: Vec<&RankingRow> = data.rankings.iter().chain(&data.excluded).collect();
+    if let Some(division) = &data.division {
+        protocol.write_ranking_table(&division.name, &rows, |row| row.place, lifts);
+    } else {
+        // Categories are weight classes unless the export was narrowed to another ranking
+        let kind = data.ranking.unwrap_or(RankingKind::WeightClass);
+        let section = RankingSection::of(&data.contest.ranking, kind);
+        for (title, members) in ranking_groups(&rows, &section) {
+            protocol.write_ranking_table(&title, &members, section.place, lifts);
+        }
+    }
+
+    protocol.write_records(data);
+    protocol.write_signatures();
+    protocol.doc.save_to_bytes()
+}
+
+/// A protocol being laid out, page by page from the top down
+struct Protocol {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    pages: usize,
+    // Top of the next line, from the bottom of the page
+    y: f32,
+}
+
+impl Protocol {
+    fn new(title: &str) -> Result<Self, Error> {
+        let (doc, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Warstwa 1");
+        let regular = doc.add_external_font(REGULAR_FONT)?;
+        let bold = doc.add_external_font(BOLD_FONT)?;
+        let layer = doc.get_page(page).get_layer(layer);
+        let protocol = Self {
+            doc,
+            layer,
+            regular,
+            bold,
+            pages: 1,
+            y: PAGE_HEIGHT - MARGIN,
+        };
+        protocol.write_page_number();
+        Ok(protocol)
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Warstwa 1");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.pages += 1;
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.write_page_number();
+    }
+
+    /// Start a new page unless the given height still fits on this one
+    fn reserve(&mut self, height: f32) -> bool {
+        if self.y - height < MARGIN {
+            self.new_page();
+            return true;
+        }
+        false
+    }
+
+    fn write_page_number(&self) {
+        let label = format!("Strona {}", self.pages);
+        let x = PAGE_WIDTH - MARGIN - text_width(&label, FONT_SIZE);
+        self.text(&label, x, MARGIN / 2.0, FONT_SIZE, false);
+    }
+
+    fn text(&self, text: &str, x: f32, baseline: f32, size: f32, bold: bool) {
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size, Mm(x), Mm(baseline), font);
+    }
+
+    fn line(&self, from: (f32, f32), to: (f32, f32), thickness: f32) {
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(from.0), Mm(from.1)), false),
+                (Point::new(Mm(to.0), Mm(to.1)), false),
+            ],
+            is_closed: false,
+        });
+    }
+
+    /// Write a line of text and move below it
+    fn write_line(&mut self, text: &str, size: f32, bold: bool) {
+        let height = size * MM_PER_PT * 1.4;
+        self.reserve(height);
+        self.y -= height;
+        self.text(text, MARGIN, self.y + height * 0.25, size, bold);
+    }
+
+    /// Write a row of cells and move below it; returns the x of each cell
+    fn write_row(&mut self, columns: &[Column], cells: &[String], bold: bool) -> Vec<f32> {
+        self.y -= ROW_HEIGHT;
+        let baseline = self.y + ROW_HEIGHT * 0.3;
+        let mut x = MARGIN;
+        let mut positions = Vec::with_capacity(columns.len());
+        for (column, cell) in columns.iter().zip(cells) {
+            let cell = fit(cell, column.width - 1.0, FONT_SIZE);
+            let cell_x = if column.numeric {
+                x + column.width - 1.0 - text_width(&cell, FONT_SIZE)
+            } else {
+                x
+            };
+            self.text(&cell, cell_x, baseline, FONT_SIZE, bold);
+            positions.push(cell_x);
+            x += column.width;
+        }
+        positions
+    }
+
+    fn write_rule(&self, columns: &[Column]) {
+        let width: f32 = columns.iter().map(|column| column.width).sum();
+        self.line((MARGIN, self.y), (MARGIN + width, self.y), 0.5);
+    }
+
+    fn write_header(&mut self, data: &ExportData) {
+        self.write_line("PROTOKÓŁ", 16.0, true);
+        self.write_line(&data.contest.name, 12.0, true);
+        self.y -= 1.0;
+        for (label, value) in contest_details(&data.contest) {
+            self.write_line(&format!("{}: {}", label, value), FONT_SIZE + 1.0, false);
+        }
+        self.write_line("Podejścia nieudane są przekreślone.", FONT_SIZE, false);
+    }
+
+    /// Table of one weight category; its header is repeated on every page it spans
+    fn write_ranking_table(
+        &mut self,
+        title: &str,
+        members: &[&RankingRow],
+        place: fn(&RankingRow) -> Option<i64>,
+        lifts: &[LiftType],
+    ) {
+        let attempt_columns = lifts.iter().flat_map(|_| {
+            (1..RECORD_ATTEMPT_NUMBER).map(|_| column("", ATTEMPT_COLUMN_WIDTH, true))
+        });
+        let columns: Vec<Column> = LIFTER_COLUMNS
+            .into_iter()
+            .chain(attempt_columns)
+            .chain(RESULT_COLUMNS)
+            .collect();
+        let title = if title.is_empty() { "OPEN" } else { title };
+
+        // Title, both header lines and the first lifter stay together
+        self.y -= ROW_HEIGHT;
+        self.reserve(4.0 * ROW_HEIGHT);
+        self.write_line(title, FONT_SIZE + 2.0, true);
+        self.write_table_header(&columns, lifts);
+
+        for lifter in members {
+            if self.reserve(ROW_HEIGHT) {
+                self.write_line(&format!("{} (cd.)", title), FONT_SIZE + 2.0, true);
+                self.write_table_header(&columns, lifts);
+            }
+
+            let result = &lifter.result;
+            let mut cells = vec![
+                match place(lifter) {
+                    Some(place) => place.to_string(),
+                    None if result.is_disqualified || result.did_not_finish => {
+                        status_label(result).to_string()
+                    }
+                    None => "-".to_string(),
+                },
+                lifter.name(),
+                lifter.club.clone().unwrap_or_default(),
+                lifter
+                    .birth_year
+                    .map(|year| year.to_string())
+                    .unwrap_or_default(),
+                format!("{:.2}", lifter.bodyweight),
+                lifter.age_category_name.clone(),
+            ];
+            let mut failed = Vec::new();
+            for lift in lifts {
+                for number in 1..RECORD_ATTEMPT_NUMBER {
+                    let attempt = lifter.attempt(lift, number).filter(|attempt| {
+                        matches!(
+                            attempt.status,
+                            AttemptStatus::Successful | AttemptStatus::Failed
+                        )
+                    });
+                    if attempt.is_some_and(|attempt| attempt.status == AttemptStatus::Failed) {
+                        failed.push(cells.len());
+                    }
+                    cells.push(
+                        attempt
+                            .map(|attempt| format!("{:.1}", attempt.weight))
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            cells.push(format!("{:.1}", result.total_weight));
+            cells.push(
+                lifter
+                    .scoring_coefficient
+                    .map(|coefficient| format!("{:.4}", coefficient))
+                    .unwrap_or_default(),
+            );
+            cells.push(format!("{:.2}", result.coefficient_points));
+
+            let positions = self.write_row(&columns, &cells, false);
+            let strike_y = self.y + ROW_HEIGHT * 0.3 + FONT_SIZE * MM_PER_PT * 0.3;
+            for index in failed {
+                let x = positions[index];
+                let width = text_width(&cells[index], FONT_SIZE);
+                self.line((x - 0.5, strike_y), (x + width + 0.5, strike_y), 0.6);
+            }
+        }
+    }
+
+    fn write_table_header(&mut self, columns: &[Column], lifts: &[LiftType]) {
+        // Lift names span their attempt columns above the attempt numbers
+        let attempts_x: f32 = LIFTER_COLUMNS.iter().map(|column| column.width).sum();
+        let span = ATTEMPT_COLUMN_WIDTH * (RECORD_ATTEMPT_NUMBER - 1) as f32;
+        self.y -= ROW_HEIGHT;
+        for (index, lift) in lifts.iter().enumerate() {
+            let label = lift_label(lift);
+            let x = MARGIN + attempts_x + span * index as f32;
+            let centered = x + (span - text_width(label, FONT_SIZE)) / 2.0;
+            self.text(label, centered, self.y + ROW_HEIGHT * 0.3, FONT_SIZE, true);
+        }
+
+        let headers: Vec<String> = columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                if column.header.is_empty() {
+                    let number =
+                        (index - LIFTER_COLUMNS.len()) as i32 % (RECORD_ATTEMPT_NUMBER - 1) + 1;
+                    number.to_string()
+                } else {
+                    column.header.to_string()
+                }
+            })
+            .collect();
+        self.write_row(columns, &headers, true);
+        self.write_rule(columns);
+    }
+
+    fn write_records(&mut self, data: &ExportData) {
+        self.y -= ROW_HEIGHT;
+        self.reserve(3.0 * ROW_HEIGHT);
+        self.write_line("Rekordy pobite na zawodach", FONT_SIZE + 2.0, true);
+        if data.records.is_empty() {
+            self.write_line("Nie pobito rekordów.", FONT_SIZE, false);
+            return;
+        }
+
+        let headers: Vec<String> = RECORD_COLUMNS
+            .iter()
+            .map(|column| column.header.to_string())
+            .collect();
+        self.write_row(&RECORD_COLUMNS, &headers, true);
+        self.write_rule(&RECORD_COLUMNS);
+        for broken in &data.records {
+            if self.reserve(ROW_HEIGHT) {
+                self.write_row(&RECORD_COLUMNS, &headers, true);
+                self.write_rule(&RECORD_COLUMNS);
+            }
+            let record = &broken.record;
+            let scope = match &record.scope_name {
+                Some(name) => format!("{} ({})", scope_label(record.scope), name),
+                None => scope_label(record.scope).to_string(),
+            };
+            let cells = [
+                scope,
+                record_lift_label(record.lift).to_string(),
+                format!("{:.1}", record.weight),
+                record.holder_name.clone(),
+                sex_label(&record.gender).to_string(),
+                broken.weight_class_name.clone(),
+                broken.age_category_name.clone(),
+                record.equipment.to_string(),
+            ];
+            self.write_row(&RECORD_COLUMNS, &cells, false);
+        }
+    }
+
+    /// Signature lines of the jury, side by side below the protocol
+    fn write_signatures(&mut self) {
+        let height = 6.0 * ROW_HEIGHT;
+        self.y -= ROW_HEIGHT;
+        self.reserve(height);
+        self.write_line("Podpisy jury", FONT_SIZE + 2.0, true);
+        self.y -= height - 2.0 * ROW_HEIGHT;
+
+        let slot = (PAGE_WIDTH - 2.0 * MARGIN) / SIGNATURES.len() as f32;
+        for (index, label) in SIGNATURES.iter().enumerate() {
+            let x = MARGIN + slot * index as f32 + 4.0;
+            let width = slot - 8.0;
+            self.line((x, self.y), (x + width, self.y), 0.4);
+            let centered = x + (width - text_width(label, FONT_SIZE)) / 2.0;
+            self.text(label, centered, self.y - ROW_HEIGHT * 0.8, FONT_SIZE, false);
+        }
+        self.y -= ROW_HEIGHT;
+    }
+}
+
+fn scope_label(scope: RecordScope) -> &'static str {
+    match scope {
+        RecordScope::Club => "Rekord klubu",
+        RecordScope::Regional => "Rekord okręgu",
+        RecordScope::National => "Rekord Polski",
+    }
+}
+
+fn record_lift_label(lift: RecordLift) -> &'static str {
+    match lift {
+        RecordLift::Squat => lift_label(&LiftType::Squat),
+        RecordLift::Bench => lift_label(&LiftType::Bench),
+        RecordLift::Deadlift => lift_label(&LiftType::Deadlift),
+        RecordLift::Total => "Suma",
+    }
+}
+
+/// Approximate width of a text in DejaVu Sans, in millimetres
+fn text_width(text: &str, size: f32) -> f32 {
+    let em: f32 = text
+        .chars()
+        .map(|c| match c {
+            '0'..='9' => 0.636,
+            '.' | ',' | ' ' | ':' => 0.32,
+            'i' | 'j' | 'l' | 'I' => 0.28,
+            '-' | '(' | ')' | 'f' | 't' | 'r' => 0.38,
+            'm' | 'w' | 'M' | 'W' => 0.9,
+            c if c.is_uppercase() => 0.72,
+            _ => 0.6,
+        })
+        .sum();
+    em * size * MM_PER_PT
+}
+
+/// Shorten a text to fit a cell, marking the cut with a dot
+fn fit(text: &str, width: f32, size: f32) -> String {
+    if text_width(text, size) <= width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&fitted, size) + text_width(".", size) > width {
+        fitted.pop();
+    }
+    format!("{}.", fitted.trim_end())
+}
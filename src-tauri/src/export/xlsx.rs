@@ -0,0 +1,305 @@
+//! Federation-style results workbook.
+//!
+//! The OPEN, KATEGORIE WIEKOWE and KATEGORIE WAGOWE sheets list the open, age class and
+//! weight class rankings below a header with the contest's details, one table per ranking
+//! group. Every attempt has a column of its own and failed attempts are struck through.
+//! Named divisions, the team classification and the best lifter awards get sheets of their
+//! own when the contest has them.
+
+use super::{
+    contest_details, lift_label, ranking_groups, status_label, ExportData, RankingKind,
+    RankingSection,
+};
+use crate::database::queries::rankings::RankingRow;
+use crate::models::attempt::{AttemptStatus, LiftType, RECORD_ATTEMPT_NUMBER};
+use rust_xlsxwriter::{Format, FormatBorder, Workbook, Worksheet, XlsxError};
+
+const FIXED_COLUMNS: [(&str, f64); 9] = [
+    ("M-ce", 6.0),
+    ("Zawodnik", 26.0),
+    ("Klub", 22.0),
+    ("Miejscowość", 16.0),
+    ("Rocznik", 8.0),
+    ("Waga ciała", 10.0),
+    ("Kat. wiekowa", 14.0),
+    ("Kat. wagowa", 14.0),
+    ("Sprzęt", 10.0),
+];
+const RESULT_COLUMNS: [(&str, f64); 3] = [("Wynik", 9.0), ("Współczynnik", 13.0), ("Punkty", 9.0)];
+const ATTEMPT_COLUMN_WIDTH: f64 = 11.0;
+
+struct Formats {
+    title: Format,
+    bold: Format,
+    header: Format,
+    weight: Format,
+    failed: Format,
+    coefficient: Format,
+    points: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        let weight = Format::new().set_num_format("0.0");
+        Self {
+            title: Format::new().set_bold().set_font_size(14),
+            bold: Format::new().set_bold(),
+            header: Format::new()
+                .set_bold()
+                .set_border_bottom(FormatBorder::Thin),
+            failed: weight.clone().set_font_strikethrough(),
+            weight,
+            coefficient: Format::new().set_num_format("0.0000"),
+            points: Format::new().set_num_format("0.00"),
+        }
+    }
+}
+
+/// Write the results workbook of a contest to a file
+pub fn save_workbook(data: &ExportData, path: &str) -> Result<(), XlsxError> {
+    build_workbook(data)?.save(path)
+}
+
+/// Build the results workbook of a contest
+pub fn build_workbook(data: &ExportData) -> Result<Workbook, XlsxError> {
+    let formats = Formats::new();
+    let lifts = data.contest.discipline.lifts();
+    let rows: Vec<&RankingRow> = data.rankings.iter().chain(&data.excluded).collect();
+
+    // A division export only has the division's sheet, a ranking export only that ranking's
+    let sheets = [
+        ("OPEN", RankingKind::Open),
+        ("KATEGORIE WIEKOWE", RankingKind::AgeClass),
+        ("KATEGORIE WAGOWE", RankingKind::WeightClass),
+    ]
+    .into_iter()
+    .filter(|(_, kind)| data.division.is_none() && data.ranking.map_or(true, |only| only == *kind));
+
+    let mut workbook = Workbook::new();
+    for (name, kind) in sheets {
+        let section = RankingSection::of(&data.contest.ranking, kind);
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(name)?;
+        set_ranking_column_widths(worksheet, lifts)?;
+        let mut row = write_contest_header(worksheet, data, &formats)?;
+        for (title, members) in ranking_groups(&rows, &section) {
+            row = write_ranking_table(
+                worksheet,
+                row,
+                &title,
+                &members,
+                section.place,
+                lifts,
+                &formats,
+            )?;
+        }
+    }
+
+    if data
+        .divisions
+        .iter()
+        .any(|division| !division.placings.is_empty())
+    {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("DYWIZJE")?;
+        set_ranking_column_widths(worksheet, lifts)?;
+        let mut row = write_contest_header(worksheet, data, &formats)?;
+        for division in &data.divisions {
+            let members: Vec<&RankingRow> = division.placings.iter().collect();
+            row = write_ranking_table(
+                worksheet,
+                row,
+                &division.division.name,
+                &members,
+                |row| row.place,
+                lifts,
+                &formats,
+            )?;
+        }
+    }
+
+    if !data.teams.is_empty() {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("DRUŻYNY")?;
+        let mut row = write_contest_header(worksheet, data, &formats)?;
+        for (col, (header, width)) in [
+            ("M-ce", 6.0),
+            ("Klub", 26.0),
+            ("Punkty", 9.0),
+            ("Punktujący zawodnicy", 60.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            worksheet.set_column_width(col as u16, width)?;
+            worksheet.write_string_with_format(row, col as u16, header, &formats.header)?;
+        }
+        row += 1;
+        for team in &data.teams {
+            let scorers = team
+                .scorers
+                .iter()
+                .map(|scorer| format!("{} ({})", scorer.competitor_name, scorer.points))
+                .collect::<Vec<_>>()
+                .join(", ");
+            worksheet.write_number(row, 0, team.place as f64)?;
+            worksheet.write_string(row, 1, &team.club)?;
+            worksheet.write_number(row, 2, team.points)?;
+            worksheet.write_string(row, 3, scorers)?;
+            row += 1;
+        }
+    }
+
+    if data.best_lifters.iter().any(|award| award.awarded) {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("NAJLEPSI ZAWODNICY")?;
+        let mut row = write_contest_header(worksheet, data, &formats)?;
+        for (col, (header, width)) in [
+            ("Grupa", 30.0),
+            ("Startujących", 13.0),
+            ("Zawodnik", 26.0),
+            ("Punkty", 9.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            worksheet.set_column_width(col as u16, width)?;
+            worksheet.write_string_with_format(row, col as u16, header, &formats.header)?;
+        }
+        row += 1;
+        for award in data.best_lifters.iter().filter(|award| award.awarded) {
+            for winner in &award.winners {
+                worksheet.write_string(row, 0, award.group.label())?;
+                worksheet.write_number(row, 1, award.entrants as f64)?;
+                worksheet.write_string(row, 2, &winner.competitor_name)?;
+                worksheet.write_number_with_format(row, 3, winner.points, &formats.points)?;
+                row += 1;
+            }
+        }
+    }
+
+    Ok(workbook)
+}
+
+/// Header block with the contest's details; returns the first row below it
+fn write_contest_header(
+    worksheet: &mut Worksheet,
+    data: &ExportData,
+    formats: &Formats,
+) -> Result<u32, XlsxError> {
+    let contest = &data.contest;
+    worksheet.write_string_with_format(0, 0, &contest.name, &formats.title)?;
+
+    let mut row = 2;
+    for (label, value) in contest_details(contest) {
+        worksheet.write_string_with_format(row, 0, label, &formats.bold)?;
+        worksheet.write_string(row, 1, value)?;
+        row += 1;
+    }
+    Ok(row + 1)
+}
+
+fn set_ranking_column_widths(
+    worksheet: &mut Worksheet,
+    lifts: &[LiftType],
+) -> Result<(), XlsxError> {
+    let attempt_columns = lifts.len() * (RECORD_ATTEMPT_NUMBER as usize - 1);
+    let widths = FIXED_COLUMNS
+        .iter()
+        .map(|(_, width)| *width)
+        .chain(std::iter::repeat(ATTEMPT_COLUMN_WIDTH).take(attempt_columns))
+        .chain(RESULT_COLUMNS.iter().map(|(_, width)| *width));
+    for (col, width) in widths.enumerate() {
+        worksheet.set_column_width(col as u16, width)?;
+    }
+    Ok(())
+}
+
+/// Table of one ranking group; returns the first row below it
+fn write_ranking_table(
+    worksheet: &mut Worksheet,
+    mut row: u32,
+    title: &str,
+    members: &[&RankingRow],
+    place: fn(&RankingRow) -> Option<i64>,
+    lifts: &[LiftType],
+    formats: &Formats,
+) -> Result<u32, XlsxError> {
+    let title = if title.is_empty() { "OPEN" } else { title };
+    worksheet.write_string_with_format(row, 0, title, &formats.bold)?;
+    row += 1;
+
+    let attempt_headers = lifts.iter().flat_map(|lift| {
+        (1..RECORD_ATTEMPT_NUMBER).map(move |number| format!("{} {}", lift_label(lift), number))
+    });
+    let headers = FIXED_COLUMNS
+        .iter()
+        .map(|(header, _)| header.to_string())
+        .chain(attempt_headers)
+        .chain(RESULT_COLUMNS.iter().map(|(header, _)| header.to_string()));
+    for (col, header) in headers.enumerate() {
+        worksheet.write_string_with_format(row, col as u16, header, &formats.header)?;
+    }
+    row += 1;
+
+    for lifter in members {
+        let result = &lifter.result;
+        match place(lifter) {
+            Some(place) => worksheet.write_number(row, 0, place as f64)?,
+            None if result.is_disqualified || result.did_not_finish => {
+                worksheet.write_string(row, 0, status_label(result))?
+            }
+            None => worksheet.write_string(row, 0, "-")?,
+        };
+        worksheet.write_string(row, 1, lifter.name())?;
+        worksheet.write_string(row, 2, lifter.club.as_deref().unwrap_or(""))?;
+        worksheet.write_string(row, 3, lifter.city.as_deref().unwrap_or(""))?;
+        if let Some(year) = lifter.birth_year {
+            worksheet.write_number(row, 4, year)?;
+        }
+        worksheet.write_number_with_format(row, 5, lifter.bodyweight, &formats.weight)?;
+        worksheet.write_string(row, 6, &lifter.age_category_name)?;
+        worksheet.write_string(row, 7, &lifter.weight_class_name)?;
+        worksheet.write_string(row, 8, lifter.equipment.to_string())?;
+
+        let mut col = FIXED_COLUMNS.len() as u16;
+        for lift in lifts {
+            for number in 1..RECORD_ATTEMPT_NUMBER {
+                match lifter.attempt(lift, number) {
+                    Some(attempt) if attempt.status == AttemptStatus::Successful => {
+                        worksheet.write_number_with_format(
+                            row,
+                            col,
+                            attempt.weight,
+                            &formats.weight,
+                        )?;
+                    }
+                    Some(attempt) if attempt.status == AttemptStatus::Failed => {
+                        worksheet.write_number_with_format(
+                            row,
+                            col,
+                            attempt.weight,
+                            &formats.failed,
+                        )?;
+                    }
+                    _ => {}
+                }
+                col += 1;
+            }
+        }
+
+        worksheet.write_number_with_format(row, col, result.total_weight, &formats.weight)?;
+        if let Some(coefficient) = lifter.scoring_coefficient {
+            worksheet.write_number_with_format(row, col + 1, coefficient, &formats.coefficient)?;
+        }
+        worksheet.write_number_with_format(
+            row,
+            col + 2,
+            result.coefficient_points,
+            &formats.points,
+        )?;
+        row += 1;
+    }
+
+    Ok(row + 1)
+}
@@ -0,0 +1,366 @@
+//! Federation rule profiles.
+//!
+//! A profile bundles the rules a federation runs its contests by: weight classes, age
+//! categories and the rule assigning them, scoring formula, bar weights and attempt rules.
+//! PZKFiTS, IPF and WRPF ship built-in; additional profiles are read from `*.toml` files in
+//! the `federations` directory next to `settings.toml`, and override a built-in profile
+//! with the same ID.
+
+use crate::models::attempt::RECORD_ATTEMPT_NUMBER;
+use crate::models::contest::{AgeRule, ScoringFormulaKind};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Profile used when neither the contest nor the settings name one
+pub const DEFAULT_FEDERATION: &str = "PZKFiTS";
+
+const BUILTIN_PROFILES: [&str; 3] = [
+    include_str!("../federations/pzkfits.toml"),
+    include_str!("../federations/ipf.toml"),
+    include_str!("../federations/wrpf.toml"),
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum FederationError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("TOML deserialization error: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    #[error("Invalid federation profile {profile}: {reason}")]
+    InvalidProfile { profile: String, reason: String },
+    #[error("Unknown federation profile: {0}")]
+    UnknownProfile(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederationProfile {
+    pub id: String,
+    pub name: String,
+    pub scoring_formula: ScoringFormulaKind,
+    #[serde(default)]
+    pub age_rule: AgeRule,
+    pub bar_weights: BarWeights,
+    pub attempts: AttemptRules,
+    pub age_categories: Vec<AgeCategoryRule>,
+    pub weight_classes: Vec<WeightClassRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BarWeights {
+    pub men: f64,
+    pub women: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptRules {
+    pub attempts_per_lift: i32,
+    pub weight_increment: f64,
+    // 4th attempts are only allowed for record attempts
+    #[serde(default)]
+    pub record_attempts: bool,
+    pub record_attempt_increment: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgeCategoryRule {
+    pub id: String,
+    pub name: String,
+    pub min_age: Option<i64>,
+    pub max_age: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeightClassRule {
+    pub id: String,
+    pub gender: String, // "Male" or "Female"
+    pub name: String,
+    pub weight_min: Option<f64>,
+    pub weight_max: Option<f64>,
+}
+
+impl FederationProfile {
+    /// Parse and validate a profile from TOML
+    pub fn from_toml(content: &str) -> Result<Self, FederationError> {
+        let profile: FederationProfile = toml::from_str(content)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    fn validate(&self) -> Result<(), FederationError> {
+        let invalid = |reason: String| FederationError::InvalidProfile {
+            profile: self.id.clone(),
+            reason,
+        };
+
+        if self.id.trim().is_empty() {
+            return Err(invalid("id must not be empty".to_string()));
+        }
+        if self.bar_weights.men <= 0.0 || self.bar_weights.women <= 0.0 {
+            return Err(invalid("bar weights must be positive".to_string()));
+        }
+        if self.attempts.attempts_per_lift < 1 {
+            return Err(invalid(
+                "at least one attempt per lift is required".to_string(),
+            ));
+        }
+        if self.attempts.attempts_per_lift >= RECORD_ATTEMPT_NUMBER {
+            return Err(invalid(format!(
+                "at most {} attempts per lift are allowed; attempt {} is the record attempt",
+                RECORD_ATTEMPT_NUMBER - 1,
+                RECORD_ATTEMPT_NUMBER
+            )));
+        }
+        if self.attempts.weight_increment <= 0.0 || self.attempts.record_attempt_increment <= 0.0 {
+            return Err(invalid("weight increments must be positive".to_string()));
+        }
+        if self.age_categories.is_empty() {
+            return Err(invalid("no age categories defined".to_string()));
+        }
+        for gender in ["Male", "Female"] {
+            if !self.weight_classes.iter().any(|c| c.gender == gender) {
+                return Err(invalid(format!("no {} weight classes defined", gender)));
+            }
+        }
+        if let Some(class) = self
+            .weight_classes
+            .iter()
+            .find(|c| c.gender != "Male" && c.gender != "Female")
+        {
+            return Err(invalid(format!(
+                "weight class {} has unknown gender {}",
+                class.id, class.gender
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Bar weight for a lifter's gender
+    pub fn bar_weight(&self, gender: &str) -> f64 {
+        if gender.eq_ignore_ascii_case("female") {
+            self.bar_weights.women
+        } else {
+            self.bar_weights.men
+        }
+    }
+
+    /// Age category covering an age in full years, as determined by the age rule
+    pub fn age_category_for(&self, age: u32) -> Option<&AgeCategoryRule> {
+        let age = i64::from(age);
+        self.age_categories.iter().find(|category| {
+            category.min_age.map_or(true, |min| age >= min)
+                && category.max_age.map_or(true, |max| age <= max)
+        })
+    }
+
+    /// Check a declared attempt against the profile's attempt rules
+    pub fn validate_attempt(&self, attempt_number: i32, weight: f64) -> Result<(), String> {
+        let rules = &self.attempts;
+        let is_record_attempt = attempt_number == RECORD_ATTEMPT_NUMBER;
+
+        if attempt_number < 1
+            || (attempt_number > rules.attempts_per_lift
+                && !(is_record_attempt && rules.record_attempts))
+        {
+            return Err(format!(
+                "{} allows attempts 1-{}{}",
+                self.id,
+                rules.attempts_per_lift,
+                if rules.record_attempts {
+                    " and a record attempt"
+                } else {
+                    ""
+                }
+            ));
+        }
+
+        let increment = if is_record_attempt {
+            rules.record_attempt_increment
+        } else {
+            rules.weight_increment
+        };
+        let steps = weight / increment;
+        if weight <= 0.0 || (steps - steps.round()).abs() > 1e-9 {
+            return Err(format!(
+                "Attempt weight must be a positive multiple of {} kg",
+                increment
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// All federation profiles known to the application
+#[derive(Debug, Clone)]
+pub struct FederationRegistry {
+    profiles: Vec<FederationProfile>,
+}
+
+impl Default for FederationRegistry {
+    fn default() -> Self {
+        Self::load()
+    }
+}
+
+impl FederationRegistry {
+    /// Built-in profiles only
+    pub fn builtin() -> Self {
+        let profiles = BUILTIN_PROFILES
+            .iter()
+            .map(|content| {
+                FederationProfile::from_toml(content).expect("built-in federation profile is valid")
+            })
+            .collect();
+        Self { profiles }
+    }
+
+    /// Built-in profiles plus those found in the config directory
+    /// This method is infallible - invalid profile files are logged and skipped
+    pub fn load() -> Self {
+        let mut registry = Self::builtin();
+        if let Some(dir) = Self::get_profiles_dir() {
+            registry.load_dir(&dir);
+        }
+        registry
+    }
+
+    /// Directory holding user supplied profiles
+    pub fn get_profiles_dir() -> Option<PathBuf> {
+        ProjectDirs::from("com", "ribelo", "werewolf")
+            .map(|dirs| dirs.config_dir().join("federations"))
+    }
+
+    /// Load every `*.toml` profile in a directory, replacing profiles with the same ID
+    pub fn load_dir(&mut self, dir: &Path) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::debug!("No federation profiles loaded from {:?}: {}", dir, e);
+                return;
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match Self::load_file(&path) {
+                Ok(profile) => {
+                    tracing::info!("Loaded federation profile {} from {:?}", profile.id, path);
+                    self.insert(profile);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to load federation profile {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    fn load_file(path: &Path) -> Result<FederationProfile, FederationError> {
+        let content = fs::read_to_string(path)?;
+        FederationProfile::from_toml(&content)
+    }
+
+    /// Add a profile, replacing any profile with the same ID
+    pub fn insert(&mut self, profile: FederationProfile) {
+        match self.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn profiles(&self) -> &[FederationProfile] {
+        &self.profiles
+    }
+
+    pub fn get(&self, id: &str) -> Result<&FederationProfile, FederationError> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.id.eq_ignore_ascii_case(id))
+            .ok_or_else(|| FederationError::UnknownProfile(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_builtin_profiles() {
+        let registry = FederationRegistry::builtin();
+        let ids: Vec<&str> = registry.profiles().iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["PZKFiTS", "IPF", "WRPF"]);
+
+        let ipf = registry.get("ipf").unwrap();
+        assert_eq!(ipf.scoring_formula, ScoringFormulaKind::IpfGl);
+        assert_eq!(ipf.age_rule, AgeRule::BirthYear);
+        assert_eq!(ipf.age_category_for(18).unwrap().id, "IPF_SUB_JUNIOR");
+        assert_eq!(ipf.age_category_for(40).unwrap().id, "IPF_MASTERS1");
+        assert_eq!(ipf.age_category_for(75).unwrap().id, "IPF_MASTERS4");
+        assert_eq!(ipf.bar_weight("Female"), 20.0);
+        assert_eq!(ipf.weight_classes.len(), 16);
+
+        let pzkfits = registry.get(DEFAULT_FEDERATION).unwrap();
+        assert_eq!(pzkfits.age_rule, AgeRule::ExactDate);
+        assert_eq!(pzkfits.age_category_for(19).unwrap().id, "JUNIOR19");
+        assert!(pzkfits.age_category_for(10).is_none());
+
+        assert!(registry.get("USAPL").is_err());
+    }
+
+    #[test]
+    fn test_attempt_rules() {
+        let registry = FederationRegistry::builtin();
+        let profile = registry.get("PZKFiTS").unwrap();
+
+        assert!(profile.validate_attempt(1, 100.0).is_ok());
+        assert!(profile.validate_attempt(3, 102.5).is_ok());
+        assert!(profile.validate_attempt(2, 101.0).is_err());
+        assert!(profile.validate_attempt(4, 100.5).is_ok());
+        assert!(profile.validate_attempt(5, 100.0).is_err());
+        assert!(profile.validate_attempt(1, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_config_dir_profiles_override_builtin() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let custom = include_str!("../federations/ipf.toml")
+            .replace("scoring_formula = \"IpfGl\"", "scoring_formula = \"Dots\"");
+        fs::write(temp_dir.path().join("ipf.toml"), custom).unwrap();
+        fs::write(temp_dir.path().join("broken.toml"), "id = ").unwrap();
+
+        let mut registry = FederationRegistry::builtin();
+        registry.load_dir(temp_dir.path());
+
+        assert_eq!(registry.profiles().len(), 3);
+        assert_eq!(
+            registry.get("IPF").unwrap().scoring_formula,
+            ScoringFormulaKind::Dots
+        );
+    }
+
+    #[test]
+    fn test_invalid_profile_is_rejected() {
+        let content =
+            include_str!("../federations/wrpf.toml").replace("women = 20.0", "women = 0.0");
+        assert!(matches!(
+            FederationProfile::from_toml(&content),
+            Err(FederationError::InvalidProfile { .. })
+        ));
+
+        // The 4th attempt is reserved for record attempts
+        let content = include_str!("../federations/wrpf.toml")
+            .replace("attempts_per_lift = 3", "attempts_per_lift = 4");
+        assert!(matches!(
+            FederationProfile::from_toml(&content),
+            Err(FederationError::InvalidProfile { .. })
+        ));
+    }
+}
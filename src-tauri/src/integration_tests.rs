@@ -1,10 +1,19 @@
 use crate::database;
+use crate::database::queries::competitors::{Competitor, CreateCompetitorRequest};
+use crate::database::queries::registrations::{CreateRegistrationRequest, Registration};
+use crate::export;
+use crate::federation::FederationRegistry;
 use crate::models::attempt::{AttemptStatus, AttemptUpsert, LiftType};
 use crate::models::competitor::CompetitorCreate;
-use crate::models::contest::{Discipline, NewContest};
+use crate::models::contest::{
+    AgeRule, Contest, Discipline, NewContest, ScoringFormulaKind, TeamScoring, DEFAULT_TIE_BREAKS,
+};
 use crate::models::contest_state::{ContestState, ContestStatus};
+use crate::recalculation;
+use crate::records;
 use chrono::NaiveDate;
 use sqlx::SqlitePool;
+use tempfile::tempdir;
 
 /// Integration tests for the complete contest workflow
 /// These tests exercise the full stack: Database -> Queries -> Commands
@@ -22,6 +31,199 @@ mod integration_tests {
         Ok(pool)
     }
 
+    /// Create a Reshel/McCullough powerlifting contest on 2024-12-15 with the given changes
+    async fn create_test_contest(
+        pool: &SqlitePool,
+        name: &str,
+        configure: impl FnOnce(&mut NewContest),
+    ) -> Contest {
+        let mut contest = NewContest {
+            name: name.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+            location: "Test Gym".to_string(),
+            discipline: Discipline::Powerlifting,
+            federation_rules: None,
+            competition_type: None,
+            organizer: None,
+            notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
+        };
+        configure(&mut contest);
+        database::queries::contests::create_contest(pool, contest)
+            .await
+            .expect("Failed to create contest")
+    }
+
+    /// Create a male competitor surnamed Lifter, born 1990-01-01, with the given changes
+    async fn create_test_competitor(
+        pool: &SqlitePool,
+        first_name: &str,
+        configure: impl FnOnce(&mut CreateCompetitorRequest),
+    ) -> Competitor {
+        let mut competitor = CreateCompetitorRequest {
+            first_name: first_name.to_string(),
+            last_name: "Lifter".to_string(),
+            birth_date: "1990-01-01".to_string(),
+            gender: "Male".to_string(),
+            club: None,
+            city: None,
+            notes: None,
+            photo_base64: None,
+            photo_filename: None,
+        };
+        configure(&mut competitor);
+        database::queries::competitors::create_competitor(pool, competitor)
+            .await
+            .expect("Failed to create competitor")
+    }
+
+    /// Register a competitor as a raw senior of 82 kg in the 82.5 kg class, with the given
+    /// changes
+    async fn register_lifter(
+        pool: &SqlitePool,
+        contest_id: &str,
+        competitor_id: &str,
+        configure: impl FnOnce(&mut CreateRegistrationRequest),
+    ) -> Registration {
+        let mut registration = CreateRegistrationRequest {
+            contest_id: contest_id.to_string(),
+            competitor_id: competitor_id.to_string(),
+            age_category_id: "SENIOR".to_string(),
+            weight_class_id: "M_82_5".to_string(),
+            weight_class_manual: false,
+            equipment_m: false,
+            equipment_sm: false,
+            equipment_t: false,
+            bodyweight: 82.0,
+            lot_number: None,
+            personal_record_at_entry: None,
+            reshel_coefficient: None,
+            mccullough_coefficient: None,
+            scoring_coefficient: Some(1.0),
+            age_at_contest: None,
+            age_rule: None,
+            rack_height_squat: None,
+            rack_height_bench: None,
+        };
+        configure(&mut registration);
+        database::queries::registrations::create_registration(pool, registration)
+            .await
+            .expect("Failed to create registration")
+    }
+
+    /// Declare an attempt and give it a status; returns the attempt's ID
+    async fn judge_attempt(
+        pool: &SqlitePool,
+        registration_id: &str,
+        lift_type: &LiftType,
+        attempt_number: i32,
+        weight: f64,
+        status: AttemptStatus,
+    ) -> String {
+        let attempt_id = database::queries::attempts::upsert_attempt_weight(
+            pool,
+            registration_id,
+            &lift_type.to_string(),
+            attempt_number,
+            weight,
+        )
+        .await
+        .expect("Failed to add attempt");
+        database::queries::attempts::update_attempt_result(
+            pool,
+            &attempt_id,
+            &status.to_string(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to judge attempt");
+        attempt_id
+    }
+
+    /// Text of a PDF page; the embedded fonts show glyph IDs, which are mapped back to
+    /// characters through each font's ToUnicode map
+    fn pdf_page_text(document: &printpdf::lopdf::Document, page_id: (u32, u16)) -> String {
+        use printpdf::lopdf::Object;
+        use std::collections::HashMap;
+
+        // printpdf stores the font dictionary as a reference, which get_page_fonts skips
+        fn object<'a>(
+            document: &'a printpdf::lopdf::Document,
+            object: &'a Object,
+        ) -> printpdf::lopdf::Result<&'a Object> {
+            document.dereference(object).map(|(_, object)| object)
+        }
+        let object = |value| object(document, value);
+        let fonts = document
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(b"Resources"))
+            .and_then(object)
+            .and_then(Object::as_dict)
+            .and_then(|resources| resources.get(b"Font"))
+            .and_then(object)
+            .and_then(Object::as_dict)
+            .expect("Page without fonts");
+
+        let mut glyphs: HashMap<Vec<u8>, HashMap<u16, char>> = HashMap::new();
+        for (name, font) in fonts {
+            let Ok(stream) = object(font)
+                .and_then(Object::as_dict)
+                .and_then(|font| font.get(b"ToUnicode"))
+                .and_then(object)
+                .and_then(Object::as_stream)
+            else {
+                continue;
+            };
+            let cmap = stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone());
+            let map = String::from_utf8_lossy(&cmap)
+                .lines()
+                .filter_map(|line| {
+                    let (glyph, unicode) = line.trim().split_once(' ')?;
+                    let glyph =
+                        u16::from_str_radix(glyph.strip_prefix('<')?.strip_suffix('>')?, 16);
+                    let unicode =
+                        u32::from_str_radix(unicode.strip_prefix('<')?.strip_suffix('>')?, 16);
+                    Some((glyph.ok()?, char::from_u32(unicode.ok()?)?))
+                })
+                .collect();
+            glyphs.insert(name.clone(), map);
+        }
+
+        let content = document
+            .get_and_decode_page_content(page_id)
+            .expect("Invalid page content");
+        let mut font = None;
+        let mut text = String::new();
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Tf" => font = operation.operands[0].as_name().ok().map(<[u8]>::to_vec),
+                "Tj" => {
+                    let map = &glyphs[font.as_ref().expect("Text without a font")];
+                    let bytes = operation.operands[0].as_str().expect("Invalid text");
+                    text.extend(
+                        bytes
+                            .chunks(2)
+                            .map(|glyph| map[&u16::from_be_bytes([glyph[0], glyph[1]])]),
+                    );
+                    text.push('\n');
+                }
+                _ => {}
+            }
+        }
+        text
+    }
+
     #[tokio::test]
     async fn test_complete_contest_workflow() {
         let pool = setup_test_db()
@@ -38,6 +240,14 @@ mod integration_tests {
             competition_type: Some("Local".to_string()),
             organizer: Some("Test Organizer".to_string()),
             notes: Some("Integration test contest".to_string()),
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         let contest = database::queries::contests::create_contest(&pool, new_contest)
@@ -108,6 +318,7 @@ mod integration_tests {
                 competitor_id: comp1.id.clone(),
                 age_category_id: "SENIOR".to_string(),
                 weight_class_id: "M_75".to_string(),
+                weight_class_manual: false,
                 equipment_m: false,
                 equipment_sm: false,
                 equipment_t: false,
@@ -116,6 +327,9 @@ mod integration_tests {
                 personal_record_at_entry: None,
                 reshel_coefficient: Some(1.0),
                 mccullough_coefficient: Some(1.0),
+                scoring_coefficient: None,
+                age_at_contest: None,
+                age_rule: None,
                 rack_height_squat: None,
                 rack_height_bench: None,
             },
@@ -130,6 +344,7 @@ mod integration_tests {
                 competitor_id: comp2.id.clone(),
                 age_category_id: "SENIOR".to_string(),
                 weight_class_id: "F_63".to_string(),
+                weight_class_manual: false,
                 equipment_m: false,
                 equipment_sm: false,
                 equipment_t: false,
@@ -138,6 +353,9 @@ mod integration_tests {
                 personal_record_at_entry: None,
                 reshel_coefficient: Some(1.0),
                 mccullough_coefficient: Some(1.0),
+                scoring_coefficient: None,
+                age_at_contest: None,
+                age_rule: None,
                 rack_height_squat: None,
                 rack_height_bench: None,
             },
@@ -225,9 +443,14 @@ mod integration_tests {
             .await
             .expect("Failed to update rankings");
 
-        let open_ranking = database::queries::results::get_open_ranking(&pool, &contest.id)
-            .await
-            .expect("Failed to get open ranking");
+        let open_ranking = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get open ranking");
 
         assert!(!open_ranking.is_empty(), "Should have results in ranking");
 
@@ -266,6 +489,14 @@ mod integration_tests {
                 competition_type: None,
                 organizer: None,
                 notes: None,
+                scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
             },
         )
         .await
@@ -296,6 +527,7 @@ mod integration_tests {
                 competitor_id: competitor.id,
                 age_category_id: "SENIOR".to_string(),
                 weight_class_id: "M_75".to_string(),
+                weight_class_manual: false,
                 equipment_m: false,
                 equipment_sm: false,
                 equipment_t: false,
@@ -304,6 +536,9 @@ mod integration_tests {
                 personal_record_at_entry: None,
                 reshel_coefficient: Some(1.0),
                 mccullough_coefficient: Some(1.0),
+                scoring_coefficient: None,
+                age_at_contest: None,
+                age_rule: None,
                 rack_height_squat: None,
                 rack_height_bench: None,
             },
@@ -349,6 +584,2272 @@ mod integration_tests {
         println!("✅ Attempt queue management test passed!");
     }
 
+    #[tokio::test]
+    async fn test_ipf_gl_contest_scoring() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = database::queries::contests::create_contest(
+            &pool,
+            NewContest {
+                name: "GL Test".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                location: "Test Gym".to_string(),
+                discipline: Discipline::Powerlifting,
+                federation_rules: Some("IPF".to_string()),
+                competition_type: None,
+                organizer: None,
+                notes: None,
+                scoring_formula: Some(ScoringFormulaKind::IpfGl),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
+            },
+        )
+        .await
+        .expect("Failed to create contest");
+
+        let competitor = database::queries::competitors::create_competitor(
+            &pool,
+            database::queries::competitors::CreateCompetitorRequest {
+                first_name: "Test".to_string(),
+                last_name: "Lifter".to_string(),
+                birth_date: "1990-01-01".to_string(),
+                gender: "Male".to_string(),
+                club: None,
+                city: None,
+                notes: None,
+                photo_base64: None,
+                photo_filename: None,
+            },
+        )
+        .await
+        .expect("Failed to create competitor");
+
+        let registration = database::queries::registrations::create_registration(
+            &pool,
+            database::queries::registrations::CreateRegistrationRequest {
+                contest_id: contest.id.clone(),
+                competitor_id: competitor.id,
+                age_category_id: "SENIOR".to_string(),
+                weight_class_id: "M_100".to_string(),
+                weight_class_manual: false,
+                equipment_m: false,
+                equipment_sm: false,
+                equipment_t: false,
+                bodyweight: 93.0,
+                lot_number: None,
+                personal_record_at_entry: None,
+                reshel_coefficient: Some(1.0),
+                mccullough_coefficient: Some(1.0),
+                scoring_coefficient: None,
+                age_at_contest: None,
+                age_rule: None,
+                rack_height_squat: None,
+                rack_height_bench: None,
+            },
+        )
+        .await
+        .expect("Failed to create registration");
+
+        for (lift_type, weight) in [
+            (LiftType::Squat, 250.0),
+            (LiftType::Bench, 170.0),
+            (LiftType::Deadlift, 280.0),
+        ] {
+            database::queries::attempts::upsert_attempt_weight(
+                &pool,
+                &registration.id,
+                &lift_type.to_string(),
+                1,
+                weight,
+            )
+            .await
+            .expect("Failed to add attempt");
+        }
+
+        let attempts =
+            database::queries::attempts::get_attempts_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get attempts");
+        for attempt in &attempts {
+            database::queries::attempts::update_attempt_result(
+                &pool,
+                &attempt.id,
+                &AttemptStatus::Successful.to_string(),
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("Failed to update attempt result");
+        }
+
+        let result = database::queries::results::calculate_results(&pool, &registration.id)
+            .await
+            .expect("Failed to calculate results");
+
+        assert_eq!(result.total_weight, 700.0);
+        // 700 kg classic total at 93 kg is worth ~91.57 IPF GL points
+        assert!((result.coefficient_points - 91.575).abs() < 0.01);
+
+        // An unknown formula fails loudly rather than scoring by the default one
+        sqlx::query("UPDATE contests SET scoring_formula = 'Bogus' WHERE id = ?")
+            .bind(&contest.id)
+            .execute(&pool)
+            .await
+            .expect("Failed to corrupt formula");
+        assert!(matches!(
+            database::queries::results::calculate_results(&pool, &registration.id).await,
+            Err(crate::error::AppError::InvalidContestData { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_recalculation_after_weigh_in_and_birth_date_changes() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+        let federations = FederationRegistry::builtin();
+
+        let contest = database::queries::contests::create_contest(
+            &pool,
+            NewContest {
+                name: "Recalculation Test".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                location: "Test Gym".to_string(),
+                discipline: Discipline::Powerlifting,
+                federation_rules: None,
+                competition_type: None,
+                organizer: None,
+                notes: None,
+                scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
+            },
+        )
+        .await
+        .expect("Failed to create contest");
+
+        let competitor = database::queries::competitors::create_competitor(
+            &pool,
+            database::queries::competitors::CreateCompetitorRequest {
+                first_name: "Test".to_string(),
+                last_name: "Lifter".to_string(),
+                birth_date: "1990-01-01".to_string(),
+                gender: "Male".to_string(),
+                club: None,
+                city: None,
+                notes: None,
+                photo_base64: None,
+                photo_filename: None,
+            },
+        )
+        .await
+        .expect("Failed to create competitor");
+
+        // Registered at 80 kg, stored with the values derived at that bodyweight
+        let registration = database::queries::registrations::create_registration(
+            &pool,
+            database::queries::registrations::CreateRegistrationRequest {
+                contest_id: contest.id.clone(),
+                competitor_id: competitor.id.clone(),
+                age_category_id: "SENIOR".to_string(),
+                weight_class_id: "M_82_5".to_string(),
+                weight_class_manual: false,
+                equipment_m: false,
+                equipment_sm: false,
+                equipment_t: false,
+                bodyweight: 80.0,
+                lot_number: None,
+                personal_record_at_entry: None,
+                reshel_coefficient: None,
+                mccullough_coefficient: None,
+                scoring_coefficient: Some(1.0),
+                age_at_contest: Some(34),
+                age_rule: Some(AgeRule::ExactDate),
+                rack_height_squat: None,
+                rack_height_bench: None,
+            },
+        )
+        .await
+        .expect("Failed to create registration");
+
+        database::queries::attempts::upsert_attempt_weight(
+            &pool,
+            &registration.id,
+            &LiftType::Squat.to_string(),
+            1,
+            200.0,
+        )
+        .await
+        .expect("Failed to add attempt");
+        let attempts =
+            database::queries::attempts::get_attempts_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get attempts");
+        database::queries::attempts::update_attempt_result(
+            &pool,
+            &attempts[0].id,
+            &AttemptStatus::Successful.to_string(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to update attempt result");
+        database::queries::results::calculate_results(&pool, &registration.id)
+            .await
+            .expect("Failed to calculate results");
+
+        // Weigh-in correction to 95 kg
+        sqlx::query("UPDATE registrations SET bodyweight = 95.0 WHERE id = ?")
+            .bind(&registration.id)
+            .execute(&pool)
+            .await
+            .expect("Failed to correct bodyweight");
+        let recalculated =
+            recalculation::recalculate_registration(&pool, &federations, &registration.id)
+                .await
+                .expect("Failed to recalculate registration");
+
+        let reshel = crate::coefficients::calculate_reshel_coefficient(95.0, "Male").unwrap();
+        assert_eq!(recalculated.weight_class_id, "M_100");
+        assert_eq!(recalculated.scoring_coefficient, Some(reshel));
+        let result =
+            database::queries::results::get_result_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get result");
+        assert!((result.coefficient_points - 200.0 * reshel).abs() < 1e-9);
+
+        // Birth date correction makes the lifter a veteran
+        database::queries::competitors::update_competitor(
+            &pool,
+            &competitor.id,
+            database::queries::competitors::CreateCompetitorRequest {
+                first_name: "Test".to_string(),
+                last_name: "Lifter".to_string(),
+                birth_date: "1960-01-01".to_string(),
+                gender: "Male".to_string(),
+                club: None,
+                city: None,
+                notes: None,
+                photo_base64: None,
+                photo_filename: None,
+            },
+        )
+        .await
+        .expect("Failed to update competitor");
+        let recalculated =
+            recalculation::recalculate_competitor(&pool, &federations, &competitor.id)
+                .await
+                .expect("Failed to recalculate competitor");
+
+        let mccullough = crate::coefficients::mccullough_factor(64);
+        assert_eq!(recalculated.len(), 1);
+        assert_eq!(recalculated[0].age_category_id, "VETERAN60");
+        assert_eq!(recalculated[0].age_at_contest, Some(64));
+        assert_eq!(recalculated[0].mccullough_coefficient, Some(mccullough));
+        let result =
+            database::queries::results::get_result_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get result");
+        assert!((result.coefficient_points - 200.0 * reshel * mccullough).abs() < 1e-9);
+        assert_eq!(result.place_open, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_failed_recalculation_leaves_contest_and_registrations_unchanged() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+        let federations = FederationRegistry::builtin();
+
+        let contest = database::queries::contests::create_contest(
+            &pool,
+            NewContest {
+                name: "Rollback Test".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 12, 15).unwrap(),
+                location: "Test Gym".to_string(),
+                discipline: Discipline::Powerlifting,
+                federation_rules: None,
+                competition_type: None,
+                organizer: None,
+                notes: None,
+                scoring_formula: None,
+                federation_profile: None,
+                weight_class_set: None,
+                age_rule: None,
+                tie_breaks: None,
+                team_scoring: None,
+                ranking: None,
+                best_lifter: None,
+            },
+        )
+        .await
+        .expect("Failed to create contest");
+
+        // The first lifter's class is assigned, the second one's was chosen by hand
+        let mut registration_ids = Vec::new();
+        for (name, bodyweight, weight_class_id, manual) in [
+            ("Auto", 80.0, "M_82_5", false),
+            ("Manual", 95.0, "M_100", true),
+        ] {
+            let competitor = database::queries::competitors::create_competitor(
+                &pool,
+                database::queries::competitors::CreateCompetitorRequest {
+                    first_name: name.to_string(),
+                    last_name: "Lifter".to_string(),
+                    birth_date: "1990-01-01".to_string(),
+                    gender: "Male".to_string(),
+                    club: None,
+                    city: None,
+                    notes: None,
+                    photo_base64: None,
+                    photo_filename: None,
+                },
+            )
+            .await
+            .expect("Failed to create competitor");
+            let registration = database::queries::registrations::create_registration(
+                &pool,
+                database::queries::registrations::CreateRegistrationRequest {
+                    contest_id: contest.id.clone(),
+                    competitor_id: competitor.id.clone(),
+                    age_category_id: "SENIOR".to_string(),
+                    weight_class_id: weight_class_id.to_string(),
+                    weight_class_manual: manual,
+                    equipment_m: false,
+                    equipment_sm: false,
+                    equipment_t: false,
+                    bodyweight,
+                    lot_number: None,
+                    personal_record_at_entry: None,
+                    reshel_coefficient: None,
+                    mccullough_coefficient: None,
+                    scoring_coefficient: Some(1.0),
+                    age_at_contest: Some(34),
+                    age_rule: Some(AgeRule::ExactDate),
+                    rack_height_squat: None,
+                    rack_height_bench: None,
+                },
+            )
+            .await
+            .expect("Failed to create registration");
+            registration_ids.push(registration.id);
+        }
+
+        // Switching to the IPF classes cannot keep the class chosen by hand
+        let mut changed = contest.clone();
+        changed.weight_class_set = "IPF".to_string();
+        let mut tx = pool.begin().await.expect("Failed to begin transaction");
+        database::queries::contests::update_contest(&mut *tx, &contest.id, changed)
+            .await
+            .expect("Failed to update contest");
+        let error = recalculation::recalculate_contest(&mut *tx, &federations, &contest.id)
+            .await
+            .expect_err("The manual class is not in the IPF set");
+        assert!(error.to_string().contains("class set"), "{error}");
+        drop(tx);
+
+        let stored = database::queries::contests::get_contest_by_id(&pool, &contest.id)
+            .await
+            .expect("Failed to get contest")
+            .expect("Contest not found");
+        assert_eq!(stored.weight_class_set, "PZKFiTS");
+
+        // Without an outer transaction the contest's registrations are still all or nothing
+        sqlx::query("UPDATE contests SET weight_class_set = 'IPF' WHERE id = ?")
+            .bind(&contest.id)
+            .execute(&pool)
+            .await
+            .expect("Failed to change class set");
+        recalculation::recalculate_contest(&pool, &federations, &contest.id)
+            .await
+            .expect_err("The manual class is not in the IPF set");
+        let first =
+            database::queries::registrations::get_registration_by_id(&pool, &registration_ids[0])
+                .await
+                .expect("Failed to get registration");
+        assert_eq!(first.weight_class_id, "M_82_5");
+        assert_eq!(first.scoring_coefficient, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_tied_totals_are_ranked_by_tie_break_chain() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Tie Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+        assert_eq!(contest.tie_breaks, DEFAULT_TIE_BREAKS.to_vec());
+
+        // Same bodyweight and bench; lifted at different times, drawn different lots
+        let lifters = [("Late", "1", 3), ("Early", "3", 1), ("Twin", "2", 1)];
+        let mut registration_ids = Vec::new();
+        for (name, lot, lifted_at) in lifters {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.lot_number = Some(lot.to_string());
+                })
+                .await;
+
+            let attempt_id = judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                150.0,
+                AttemptStatus::Successful,
+            )
+            .await;
+            sqlx::query("UPDATE attempts SET achieved_at = ? WHERE id = ?")
+                .bind(lifted_at)
+                .bind(&attempt_id)
+                .execute(&pool)
+                .await
+                .expect("Failed to set the judging order");
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+
+        let place_of = |results: &[database::queries::results::CompetitionResult], id: &str| {
+            results
+                .iter()
+                .find(|result| result.registration_id == id)
+                .and_then(|result| result.place_open)
+        };
+
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+        let results = database::queries::results::get_contest_results(&pool, &contest.id)
+            .await
+            .expect("Failed to get results");
+        // Earlier lift first; the two lifted at the same time are split by lot number
+        assert_eq!(place_of(&results, &registration_ids[2]), Some(1));
+        assert_eq!(place_of(&results, &registration_ids[1]), Some(2));
+        assert_eq!(place_of(&results, &registration_ids[0]), Some(3));
+        assert_eq!(
+            results[0].place_in_weight_class,
+            Some(results[0].place_open.unwrap())
+        );
+
+        // A contest without tie-breaks lets tied lifters share the place
+        let mut contest = contest;
+        contest.tie_breaks = Vec::new();
+        database::queries::contests::update_contest(&pool, &contest.id.clone(), contest.clone())
+            .await
+            .expect("Failed to update contest");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+        let results = database::queries::results::get_contest_results(&pool, &contest.id)
+            .await
+            .expect("Failed to get results");
+        for id in &registration_ids {
+            assert_eq!(place_of(&results, id), Some(1));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bomb_out_has_no_total_or_place() {
+        use AttemptStatus::{Failed, Pending, Successful};
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Bomb-out Test", |_| {}).await;
+
+        // (name, squat statuses); bench and deadlift are good for everyone
+        let lifters = [
+            ("Bombed", [Failed, Failed, Failed]),
+            ("Finished", [Successful, Failed, Successful]),
+            ("Lifting", [Failed, Pending, Pending]),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, squats) in lifters {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+
+            let attempts = squats
+                .iter()
+                .enumerate()
+                .map(|(index, status)| (LiftType::Squat, index as i32 + 1, 200.0, status.clone()))
+                .chain([
+                    (LiftType::Bench, 1, 120.0, AttemptStatus::Successful),
+                    (LiftType::Deadlift, 1, 250.0, AttemptStatus::Successful),
+                ]);
+            for (lift_type, attempt_number, weight, status) in attempts {
+                judge_attempt(
+                    &pool,
+                    &registration.id,
+                    &lift_type,
+                    attempt_number,
+                    weight,
+                    status,
+                )
+                .await;
+            }
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let bombed =
+            database::queries::results::get_result_by_registration(&pool, &registration_ids[0])
+                .await
+                .expect("Failed to get result");
+        assert!(bombed.is_bombed_out);
+        assert_eq!(bombed.total_weight, 0.0);
+        assert_eq!(bombed.coefficient_points, 0.0);
+        assert_eq!(bombed.best_bench, Some(120.0));
+        assert_eq!(bombed.best_deadlift, Some(250.0));
+        assert_eq!(bombed.place_open, None);
+
+        // Squats still to come: a subtotal, not a bomb-out
+        let lifting =
+            database::queries::results::get_result_by_registration(&pool, &registration_ids[2])
+                .await
+                .expect("Failed to get result");
+        assert!(!lifting.is_bombed_out);
+        assert_eq!(lifting.total_weight, 370.0);
+
+        let ranking = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get ranking");
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0].result.registration_id, registration_ids[1]);
+        assert_eq!(ranking[0].result.total_weight, 570.0);
+
+        let bomb_outs = database::queries::results::get_bombed_out_results(&pool, &contest.id)
+            .await
+            .expect("Failed to get bomb-outs");
+        assert_eq!(bomb_outs.len(), 1);
+        assert_eq!(bomb_outs[0].registration_id, registration_ids[0]);
+    }
+
+    #[tokio::test]
+    async fn test_disqualification_and_dnf_leave_rankings_and_are_logged() {
+        use database::queries::results::ResultStatusAction;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "DSQ Test", |_| {}).await;
+
+        let mut registration_ids = Vec::new();
+        for (name, deadlift) in [("First", 260.0), ("Second", 250.0), ("Third", 240.0)] {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+
+            for (lift_type, weight) in [
+                (LiftType::Squat, 200.0),
+                (LiftType::Bench, 120.0),
+                (LiftType::Deadlift, deadlift),
+            ] {
+                judge_attempt(
+                    &pool,
+                    &registration.id,
+                    &lift_type,
+                    1,
+                    weight,
+                    AttemptStatus::Successful,
+                )
+                .await;
+            }
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let disqualified = database::queries::results::set_result_status(
+            &pool,
+            &registration_ids[0],
+            ResultStatusAction::Disqualify,
+            Some("Positive doping test"),
+        )
+        .await
+        .expect("Failed to disqualify");
+        assert!(disqualified.is_disqualified);
+        assert_eq!(
+            disqualified.disqualification_reason.as_deref(),
+            Some("Positive doping test")
+        );
+        assert!(disqualified.status_changed_at.is_some());
+        database::queries::results::set_result_status(
+            &pool,
+            &registration_ids[2],
+            ResultStatusAction::DidNotFinish,
+            Some("Injured"),
+        )
+        .await
+        .expect("Failed to mark DNF");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let ranking = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get ranking");
+        assert_eq!(ranking.len(), 1);
+        assert_eq!(ranking[0].result.registration_id, registration_ids[1]);
+        assert_eq!(ranking[0].place, Some(1));
+
+        let excluded = database::queries::results::get_excluded_results(&pool, &contest.id)
+            .await
+            .expect("Failed to get excluded results");
+        assert_eq!(excluded.len(), 2);
+        assert!(excluded.iter().all(|result| result.place_open.is_none()));
+
+        // Recalculating a result keeps the officials' decision
+        database::queries::results::calculate_results(&pool, &registration_ids[0])
+            .await
+            .expect("Failed to calculate results");
+        let recalculated =
+            database::queries::results::get_result_by_registration(&pool, &registration_ids[0])
+                .await
+                .expect("Failed to get result");
+        assert!(recalculated.is_disqualified);
+
+        let reinstated = database::queries::results::set_result_status(
+            &pool,
+            &registration_ids[0],
+            ResultStatusAction::Reinstate,
+            None,
+        )
+        .await
+        .expect("Failed to reinstate");
+        assert!(!reinstated.is_disqualified);
+        assert!(reinstated.disqualification_reason.is_none());
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let ranking = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get ranking");
+        assert_eq!(ranking.len(), 2);
+        assert_eq!(ranking[0].result.registration_id, registration_ids[0]);
+
+        let log = database::queries::results::get_result_status_log(&pool, &contest.id)
+            .await
+            .expect("Failed to get status log");
+        let actions: Vec<ResultStatusAction> = log.iter().map(|entry| entry.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                ResultStatusAction::Disqualify,
+                ResultStatusAction::DidNotFinish,
+                ResultStatusAction::Reinstate,
+            ]
+        );
+        assert_eq!(log[1].reason.as_deref(), Some("Injured"));
+    }
+
+    #[tokio::test]
+    async fn test_successful_attempts_break_records() {
+        use crate::models::record::{RecordEntry, RecordLift, RecordScope};
+        use crate::models::registration::Equipment;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Record Test", |_| {}).await;
+        let competitor = create_test_competitor(&pool, "Record", |competitor| {
+            competitor.last_name = "Breaker".to_string();
+            competitor.club = Some("KS Żelazo".to_string());
+        })
+        .await;
+        let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+
+        let record = |scope: RecordScope, scope_name: Option<&str>, lift, weight| RecordEntry {
+            scope,
+            scope_name: scope_name.map(str::to_string),
+            gender: "Male".to_string(),
+            weight_class_id: "M_82_5".to_string(),
+            age_category_id: "SENIOR".to_string(),
+            equipment: Equipment::Raw,
+            lift,
+            weight,
+            holder_name: "Old Holder".to_string(),
+            set_on: "2020-05-01".to_string(),
+        };
+        let imported = records::import_records(
+            &pool,
+            &[
+                record(RecordScope::National, None, RecordLift::Deadlift, 300.0),
+                record(
+                    RecordScope::Club,
+                    Some("KS Żelazo"),
+                    RecordLift::Deadlift,
+                    240.0,
+                ),
+                record(
+                    RecordScope::Club,
+                    Some("KS Żelazo"),
+                    RecordLift::Total,
+                    550.0,
+                ),
+                record(
+                    RecordScope::Club,
+                    Some("Other Club"),
+                    RecordLift::Bench,
+                    100.0,
+                ),
+                record(
+                    RecordScope::Club,
+                    Some("KS Żelazo"),
+                    RecordLift::Squat,
+                    220.0,
+                ),
+                record(
+                    RecordScope::Club,
+                    Some("KS Żelazo"),
+                    RecordLift::Bench,
+                    130.0,
+                ),
+                record(RecordScope::National, None, RecordLift::Squat, 320.0),
+                record(RecordScope::National, None, RecordLift::Bench, 220.0),
+                record(RecordScope::National, None, RecordLift::Total, 800.0),
+            ],
+        )
+        .await
+        .expect("Failed to import records");
+        assert_eq!(imported, 9);
+
+        // A national record without a scope name is fine, a club record needs one
+        let unnamed_club = record(RecordScope::Club, None, RecordLift::Squat, 200.0);
+        assert!(records::import_records(&pool, &[unnamed_club])
+            .await
+            .is_err());
+
+        let mut deadlift_id = String::new();
+        for (lift_type, weight) in [
+            (LiftType::Squat, 200.0),
+            (LiftType::Bench, 120.0),
+            (LiftType::Deadlift, 250.0),
+        ] {
+            let attempt_id = judge_attempt(
+                &pool,
+                &registration.id,
+                &lift_type,
+                1,
+                weight,
+                AttemptStatus::Successful,
+            )
+            .await;
+
+            let broken = records::check_attempt(&pool, &attempt_id)
+                .await
+                .expect("Failed to check records");
+            match lift_type {
+                // Below the club records; the other club's lower bench record is not this
+                // lifter's to break
+                LiftType::Squat | LiftType::Bench => assert!(broken.is_empty()),
+                LiftType::Deadlift => {
+                    // Club deadlift and club total, but not the national deadlift record
+                    let lifts: Vec<(RecordScope, RecordLift, f64)> = broken
+                        .iter()
+                        .map(|record| (record.scope, record.lift, record.weight))
+                        .collect();
+                    assert_eq!(
+                        lifts,
+                        vec![
+                            (RecordScope::Club, RecordLift::Deadlift, 250.0),
+                            (RecordScope::Club, RecordLift::Total, 570.0),
+                        ]
+                    );
+                    assert!(broken
+                        .iter()
+                        .all(|record| record.attempt_id.as_deref() == Some(attempt_id.as_str())));
+                    deadlift_id = attempt_id;
+                }
+            }
+        }
+
+        let result =
+            database::queries::results::get_result_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get result");
+        assert!(result.broke_record);
+        assert_eq!(result.record_type.as_deref(), Some("Club"));
+
+        let set_in_contest = database::queries::records::get_records_by_contest(&pool, &contest.id)
+            .await
+            .expect("Failed to get contest records");
+        assert_eq!(set_in_contest.len(), 2);
+        let current =
+            database::queries::records::get_current_records(&pool, Some(RecordScope::Club))
+                .await
+                .expect("Failed to get records");
+        assert_eq!(current.len(), 5);
+        assert!(current
+            .iter()
+            .any(|record| record.lift == RecordLift::Deadlift && record.weight == 250.0));
+
+        // The jury reverses the deadlift: its records are gone and the flag is cleared
+        database::queries::attempts::update_attempt_result(
+            &pool,
+            &deadlift_id,
+            "Failed",
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to judge attempt");
+        let broken = records::check_attempt(&pool, &deadlift_id)
+            .await
+            .expect("Failed to check records");
+        assert!(broken.is_empty());
+        assert!(
+            database::queries::records::get_records_by_contest(&pool, &contest.id)
+                .await
+                .expect("Failed to get contest records")
+                .is_empty()
+        );
+        let result =
+            database::queries::results::get_result_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get result");
+        assert!(!result.broke_record);
+        assert_eq!(result.record_type, None);
+    }
+
+    #[tokio::test]
+    async fn test_record_attempt_is_kept_out_of_total() {
+        use crate::models::record::{RecordEntry, RecordLift, RecordScope};
+        use crate::models::registration::Equipment;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Record Attempt Test", |_| {}).await;
+        let competitor = create_test_competitor(&pool, "Fourth", |competitor| {
+            competitor.last_name = "Attempt".to_string();
+        })
+        .await;
+        let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+
+        records::import_records(
+            &pool,
+            &[RecordLift::Deadlift, RecordLift::Total].map(|lift| RecordEntry {
+                scope: RecordScope::National,
+                scope_name: None,
+                gender: "Male".to_string(),
+                weight_class_id: "M_82_5".to_string(),
+                age_category_id: "SENIOR".to_string(),
+                equipment: Equipment::Raw,
+                lift,
+                weight: if lift == RecordLift::Total {
+                    560.0
+                } else {
+                    255.0
+                },
+                holder_name: "Old Holder".to_string(),
+                set_on: "2020-05-01".to_string(),
+            }),
+        )
+        .await
+        .expect("Failed to import records");
+
+        // A 4th attempt must go for a record, which may be a vacant one
+        let vacant =
+            records::records_in_reach(&pool, &registration.id, &LiftType::Squat, 200.0, None)
+                .await
+                .expect("Failed to check records");
+        assert_eq!(vacant.len(), 1);
+        assert_eq!(vacant[0].scope, RecordScope::National);
+        assert!(vacant[0].current.is_none());
+        assert!(records::records_in_reach(
+            &pool,
+            &registration.id,
+            &LiftType::Deadlift,
+            255.0,
+            None
+        )
+        .await
+        .expect("Failed to check records")
+        .is_empty());
+        assert_eq!(
+            records::records_in_reach(&pool, &registration.id, &LiftType::Deadlift, 255.5, None)
+                .await
+                .expect("Failed to check records")
+                .len(),
+            1
+        );
+
+        for (lift_type, attempt_number, weight) in [
+            (LiftType::Squat, 1, 200.0),
+            (LiftType::Bench, 1, 120.0),
+            (LiftType::Deadlift, 1, 250.0),
+            (LiftType::Deadlift, 4, 255.5),
+        ] {
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &lift_type,
+                attempt_number,
+                weight,
+                AttemptStatus::Successful,
+            )
+            .await;
+        }
+
+        let result = database::queries::results::calculate_results(&pool, &registration.id)
+            .await
+            .expect("Failed to calculate results");
+        assert_eq!(result.best_deadlift, Some(250.0));
+        assert_eq!(result.record_attempt_deadlift, Some(255.5));
+        assert_eq!(result.record_attempt_squat, None);
+        assert_eq!(result.total_weight, 570.0);
+
+        // The record attempt sets the deadlift record but does not count for the total record
+        let record_attempt_id: String = sqlx::query_scalar(
+            "SELECT id FROM attempts WHERE registration_id = ? AND attempt_number = 4",
+        )
+        .bind(&registration.id)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to get attempt");
+        let broken = records::check_attempt(&pool, &record_attempt_id)
+            .await
+            .expect("Failed to check records");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].lift, RecordLift::Deadlift);
+        assert_eq!(broken[0].weight, 255.5);
+
+        // The record it set does not stop the record attempt itself from being saved again
+        assert!(records::records_in_reach(
+            &pool,
+            &registration.id,
+            &LiftType::Deadlift,
+            255.5,
+            None
+        )
+        .await
+        .expect("Failed to check records")
+        .is_empty());
+        let in_reach = records::records_in_reach(
+            &pool,
+            &registration.id,
+            &LiftType::Deadlift,
+            255.5,
+            Some(&record_attempt_id),
+        )
+        .await
+        .expect("Failed to check records");
+        assert_eq!(in_reach.len(), 1);
+        assert_eq!(
+            in_reach[0].current.as_ref().map(|record| record.weight),
+            Some(255.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_team_rankings_sum_best_place_points_per_club() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Team Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+        assert_eq!(contest.team_scoring, TeamScoring::default());
+
+        // (name, club, bench); all in the same weight class
+        let lifters = [
+            ("Anna", Some("Alpha"), 150.0),
+            ("Bart", Some("Beta"), 140.0),
+            ("Adam", Some("Alpha"), 130.0),
+            ("Solo", None, 120.0),
+        ];
+        for (name, club, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.club = club.map(str::to_string);
+            })
+            .await;
+            let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let teams = database::queries::results::get_team_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to get team rankings");
+        let summary: Vec<(i64, &str, u32)> = teams
+            .iter()
+            .map(|team| (team.place, team.club.as_str(), team.points))
+            .collect();
+        assert_eq!(summary, vec![(1, "Alpha", 20), (2, "Beta", 9)]);
+
+        // Only the best lifter of each club counts
+        let mut contest = contest;
+        contest.team_scoring = TeamScoring {
+            top_n: 1,
+            place_points: vec![12, 9, 8],
+        };
+        database::queries::contests::update_contest(&pool, &contest.id.clone(), contest.clone())
+            .await
+            .expect("Failed to update contest");
+        let teams = database::queries::results::get_team_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to get team rankings");
+        assert_eq!(teams[0].points, 12);
+        assert_eq!(teams[0].scorers.len(), 1);
+        assert_eq!(teams[0].scorers[0].competitor_name, "Anna Lifter");
+        assert_eq!(teams[1].points, 9);
+    }
+
+    #[tokio::test]
+    async fn test_equipment_divisions_are_ranked_separately() {
+        use crate::models::registration::Equipment;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Equipment Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, multi-ply, single-ply, bench)
+        let lifters = [
+            ("Shirted", false, true, 200.0),
+            ("Raw", false, false, 150.0),
+            ("Multi", true, false, 250.0),
+            ("RawToo", false, false, 140.0),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, equipment_m, equipment_sm, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.equipment_m = equipment_m;
+                    registration.equipment_sm = equipment_sm;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        // Each division has its own winner in the open and the weight class ranking
+        for (index, expected_place) in [1, 1, 1, 2].into_iter().enumerate() {
+            let result = database::queries::results::get_result_by_registration(
+                &pool,
+                &registration_ids[index],
+            )
+            .await
+            .expect("Failed to get result");
+            assert_eq!(result.place_open, Some(expected_place));
+            assert_eq!(result.place_in_weight_class, Some(expected_place));
+            assert_eq!(result.place_in_age_class, Some(expected_place));
+        }
+
+        let raw = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            Some(Equipment::Raw),
+        )
+        .await
+        .expect("Failed to get ranking");
+        let raw_ids: Vec<&str> = raw
+            .iter()
+            .map(|placing| placing.result.registration_id.as_str())
+            .collect();
+        assert_eq!(
+            raw_ids,
+            vec![registration_ids[1].as_str(), registration_ids[3].as_str()]
+        );
+
+        // Without a filter the divisions follow each other: raw, single-ply, multi-ply
+        let all = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::WEIGHT_CLASS_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get ranking");
+        let all_ids: Vec<&str> = all
+            .iter()
+            .map(|placing| placing.result.registration_id.as_str())
+            .collect();
+        assert_eq!(
+            all_ids,
+            vec![
+                registration_ids[1].as_str(),
+                registration_ids[3].as_str(),
+                registration_ids[0].as_str(),
+                registration_ids[2].as_str(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ranking_config_sets_basis_and_splits() {
+        use crate::models::contest::{RankingBasis, RankingRule};
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Ranking Config Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, gender, weight class, coefficient, bench)
+        let lifters = [
+            ("Strong", "Male", "M_82_5", 1.0, 200.0),
+            ("Light", "Male", "M_82_5", 1.2, 180.0),
+            ("Woman", "Female", "F_63", 1.5, 100.0),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, gender, weight_class_id, coefficient, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.gender = gender.to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = 62.0;
+                    registration.scoring_coefficient = Some(coefficient);
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        async fn places(
+            pool: &sqlx::SqlitePool,
+            registration_ids: &[String],
+        ) -> Vec<(Option<i64>, Option<i64>)> {
+            let mut places = Vec::new();
+            for registration_id in registration_ids {
+                let result =
+                    database::queries::results::get_result_by_registration(pool, registration_id)
+                        .await
+                        .expect("Failed to get result");
+                places.push((result.place_open, result.place_in_weight_class));
+            }
+            places
+        }
+
+        // By default the open ranking is by points and per sex, weight classes by total
+        assert_eq!(
+            places(&pool, &registration_ids).await,
+            vec![(Some(2), Some(1)), (Some(1), Some(2)), (Some(1), Some(1))]
+        );
+
+        // An open ranking by total with everyone together
+        let mut updated = contest.clone();
+        updated.ranking.open = RankingRule {
+            basis: RankingBasis::Total,
+            split_by: Vec::new(),
+        };
+        let updated = database::queries::contests::update_contest(&pool, &contest.id, updated)
+            .await
+            .expect("Failed to update contest");
+        assert!(updated.ranking.open.split_by.is_empty());
+        assert_eq!(updated.ranking.open.basis, RankingBasis::Total);
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        assert_eq!(
+            places(&pool, &registration_ids).await,
+            vec![(Some(1), Some(1)), (Some(2), Some(2)), (Some(3), Some(1))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_divisions_rank_lifters_matching_their_filters() {
+        use crate::models::contest::RankingBasis;
+        use crate::models::division::NewDivision;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Division Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, age category, weight class, bench)
+        let lifters = [
+            ("Senior", "SENIOR", "M_82_5", 200.0),
+            ("JuniorLight", "JUNIOR16", "M_82_5", 140.0),
+            ("JuniorStrong", "JUNIOR16", "M_82_5", 150.0),
+            ("JuniorHeavy", "JUNIOR16", "M_90", 170.0),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, age_category_id, weight_class_id, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.birth_date = "2008-01-01".to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.age_category_id = age_category_id.to_string();
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = 80.0;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+
+        let division = database::queries::divisions::create_division(
+            &pool,
+            &NewDivision {
+                contest_id: contest.id.clone(),
+                name: "Junior 16 -82.5 kg".to_string(),
+                gender: Some("Male".to_string()),
+                age_category_id: Some("JUNIOR16".to_string()),
+                weight_class_id: Some("M_82_5".to_string()),
+                equipment: None,
+                basis: RankingBasis::Total,
+            },
+        )
+        .await
+        .expect("Failed to create division");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        // Only the juniors of the weight class are placed, the senior keeps the class win
+        let placings = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            &division.id,
+            None,
+        )
+        .await
+        .expect("Failed to get division ranking");
+        let ranked: Vec<(Option<i64>, &str)> = placings
+            .iter()
+            .map(|placing| (placing.place, placing.result.registration_id.as_str()))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (Some(1), registration_ids[2].as_str()),
+                (Some(2), registration_ids[1].as_str()),
+            ]
+        );
+        let senior =
+            database::queries::results::get_result_by_registration(&pool, &registration_ids[0])
+                .await
+                .expect("Failed to get result");
+        assert_eq!(senior.place_in_weight_class, Some(1));
+
+        // Placings are removed with their division
+        database::queries::divisions::delete_division(&pool, &division.id)
+            .await
+            .expect("Failed to delete division");
+        let remaining: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM division_placings WHERE division_id = ?")
+                .bind(&division.id)
+                .fetch_one(&pool)
+                .await
+                .expect("Failed to count placings");
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_entered_lifter_is_ranked_in_every_division() {
+        use crate::models::contest::RankingBasis;
+        use crate::models::division::NewDivision;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Multi Division Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, age category, bench)
+        let lifters = [("Senior", "SENIOR", 150.0), ("Master", "VETERAN40", 160.0)];
+        let mut registration_ids = Vec::new();
+        for (name, age_category_id, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.birth_date = "1980-01-01".to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.age_category_id = age_category_id.to_string();
+                    registration.bodyweight = 80.0;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        let (senior_id, master_id) = (&registration_ids[0], &registration_ids[1]);
+
+        let mut division_ids = Vec::new();
+        for (name, age_category_id) in [("Open -82.5", "SENIOR"), ("Masters 40 -82.5", "VETERAN40")]
+        {
+            let division = database::queries::divisions::create_division(
+                &pool,
+                &NewDivision {
+                    contest_id: contest.id.clone(),
+                    name: name.to_string(),
+                    gender: Some("Male".to_string()),
+                    age_category_id: Some(age_category_id.to_string()),
+                    weight_class_id: Some("M_82_5".to_string()),
+                    equipment: None,
+                    basis: RankingBasis::Total,
+                },
+            )
+            .await
+            .expect("Failed to create division");
+            division_ids.push(division.id);
+        }
+        let open_id = &division_ids[0];
+
+        database::queries::divisions::add_division_entry(&pool, open_id, master_id)
+            .await
+            .expect("Failed to enter division");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        // The master's attempts count in the open as well as in the masters division
+        let open =
+            database::queries::divisions::get_division_ranking(&pool, &contest.id, open_id, None)
+                .await
+                .expect("Failed to get division ranking");
+        let ranked: Vec<(Option<i64>, &str)> = open
+            .iter()
+            .map(|placing| (placing.place, placing.result.registration_id.as_str()))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![(Some(1), master_id.as_str()), (Some(2), senior_id.as_str())]
+        );
+        let placings = database::queries::divisions::get_placings_by_registration(&pool, master_id)
+            .await
+            .expect("Failed to get placings");
+        assert_eq!(placings.len(), 2);
+        assert!(placings.iter().all(|placing| placing.place == 1));
+
+        // Podiums list the master in both divisions
+        let podiums =
+            database::queries::divisions::get_division_rankings(&pool, &contest.id, Some(1))
+                .await
+                .expect("Failed to get podiums");
+        assert_eq!(podiums.len(), 2);
+        assert!(podiums.iter().all(|podium| podium.placings.len() == 1
+            && podium.placings[0].result.registration_id == *master_id));
+
+        // Withdrawing leaves the open to the senior
+        database::queries::divisions::remove_division_entry(&pool, open_id, master_id)
+            .await
+            .expect("Failed to withdraw");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+        let open =
+            database::queries::divisions::get_division_ranking(&pool, &contest.id, open_id, None)
+                .await
+                .expect("Failed to get division ranking");
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].result.registration_id, *senior_id);
+        assert_eq!(open[0].place, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_best_lifter_awards_need_enough_entrants() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Best Lifter Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, gender, weight class, coefficient, bench)
+        let lifters = [
+            ("Heavy", "Male", "M_100", 0.8, 220.0),
+            ("Light", "Male", "M_67_5", 1.3, 150.0),
+            ("Middle", "Male", "M_82_5", 1.0, 180.0),
+            ("Woman", "Female", "F_63", 1.5, 100.0),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, gender, weight_class_id, coefficient, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.gender = gender.to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = 80.0;
+                    registration.scoring_coefficient = Some(coefficient);
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let awards = database::queries::results::get_best_lifter_awards(&pool, &contest.id)
+            .await
+            .expect("Failed to get best lifters");
+
+        // Three men compete for the award, the lone woman's group has too few entrants
+        assert_eq!(awards.len(), 2);
+        let women = &awards[0];
+        assert_eq!(women.group.label(), "Female Senior Raw");
+        assert_eq!(women.entrants, 1);
+        assert!(!women.awarded);
+        let men = &awards[1];
+        assert_eq!(men.group.label(), "Male Senior Raw");
+        assert_eq!(men.entrants, 3);
+        assert!(men.awarded);
+        assert_eq!(men.winners.len(), 1);
+        assert_eq!(men.winners[0].registration_id, registration_ids[1]);
+        assert!((men.winners[0].points - 195.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_judged_attempt_recalculates_result_and_affected_rankings() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Live Results Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // (name, gender, weight class, opener)
+        let lifters = [
+            ("Leader", "Male", "M_82_5", 180.0),
+            ("Chaser", "Male", "M_82_5", 170.0),
+            ("Woman", "Female", "F_63", 100.0),
+        ];
+        let mut registration_ids = Vec::new();
+        for (name, gender, weight_class_id, opener) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.gender = gender.to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = 80.0;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                opener,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+            registration_ids.push(registration.id);
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        // Mark the woman's place to see that her rankings are left alone
+        sqlx::query(
+            r#"
+            UPDATE division_placings SET place = 99
+            WHERE division_id = 'open'
+            AND result_id = (SELECT id FROM results WHERE registration_id = ?)
+            "#,
+        )
+        .bind(&registration_ids[2])
+        .execute(&pool)
+        .await
+        .expect("Failed to mark place");
+
+        // The chaser's second attempt is entered and judged good in one transaction
+        let mut tx = pool.begin().await.expect("Failed to begin transaction");
+        let attempt_id = database::queries::attempts::upsert_attempt_weight(
+            &mut *tx,
+            &registration_ids[1],
+            "Bench",
+            2,
+            190.0,
+        )
+        .await
+        .expect("Failed to add attempt");
+        database::queries::attempts::update_attempt_result(
+            &mut *tx,
+            &attempt_id,
+            "Successful",
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to judge attempt");
+        let result = recalculation::recalculate_attempt(&mut *tx, &attempt_id)
+            .await
+            .expect("Failed to recalculate attempt");
+        tx.commit().await.expect("Failed to commit");
+
+        assert_eq!(result.total_weight, 190.0);
+        let place_of = |registration_id: &str| {
+            let pool = pool.clone();
+            let registration_id = registration_id.to_string();
+            async move {
+                database::queries::results::get_result_by_registration(&pool, &registration_id)
+                    .await
+                    .expect("Failed to get result")
+            }
+        };
+        let chaser = place_of(&registration_ids[1]).await;
+        assert_eq!(chaser.total_weight, 190.0);
+        assert_eq!(chaser.place_open, Some(1));
+        assert_eq!(chaser.place_in_weight_class, Some(1));
+        let leader = place_of(&registration_ids[0]).await;
+        assert_eq!(leader.place_open, Some(2));
+        assert_eq!(leader.place_in_weight_class, Some(2));
+        let woman = place_of(&registration_ids[2]).await;
+        assert_eq!(woman.place_open, Some(99));
+    }
+
+    #[tokio::test]
+    async fn test_ranking_rows_carry_lifter_details_and_attempts() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Ranking Rows Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+        let competitor = create_test_competitor(&pool, "Jan", |competitor| {
+            competitor.last_name = "Kowalski".to_string();
+            competitor.birth_date = "1990-05-01".to_string();
+            competitor.club = Some("KS Wilk".to_string());
+            competitor.city = Some("Kraków".to_string());
+        })
+        .await;
+        let registration = register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+            registration.bodyweight = 81.3;
+            registration.lot_number = Some("7".to_string());
+            registration.scoring_coefficient = Some(1.1);
+        })
+        .await;
+
+        for (attempt_number, weight, status) in [
+            (1, 150.0, AttemptStatus::Successful),
+            (2, 160.0, AttemptStatus::Failed),
+        ] {
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                attempt_number,
+                weight,
+                status,
+            )
+            .await;
+        }
+        database::queries::results::calculate_results(&pool, &registration.id)
+            .await
+            .expect("Failed to calculate results");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let placings = database::queries::divisions::get_division_ranking(
+            &pool,
+            &contest.id,
+            crate::models::division::OPEN_DIVISION,
+            None,
+        )
+        .await
+        .expect("Failed to get ranking");
+        let rows = database::queries::rankings::get_ranking_rows(&pool, &contest.id, placings)
+            .await
+            .expect("Failed to get ranking rows");
+
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.place, Some(1));
+        assert_eq!(row.name(), "Jan Kowalski");
+        assert_eq!(row.club.as_deref(), Some("KS Wilk"));
+        assert_eq!(row.city.as_deref(), Some("Kraków"));
+        assert_eq!(row.birth_year, Some(1990));
+        assert_eq!(row.weight_class_name, "DO 82.5 KG");
+        assert_eq!(row.age_category_name, "Senior");
+        assert_eq!(row.scoring_coefficient, Some(1.1));
+        assert_eq!(row.result.total_weight, 150.0);
+        assert_eq!(row.attempts.len(), 2);
+        let second = row
+            .attempt(&LiftType::Bench, 2)
+            .expect("Missing second attempt");
+        assert_eq!(second.weight, 160.0);
+        assert_eq!(second.status, AttemptStatus::Failed);
+
+        // The frontend reads the lifter's details in camelCase
+        let json = serde_json::to_value(row).expect("Failed to serialize row");
+        assert_eq!(json["firstName"], "Jan");
+        assert_eq!(json["lastName"], "Kowalski");
+        assert_eq!(json["attempts"][1]["attemptNumber"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_xlsx_export_has_a_sheet_per_ranking() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Workbook Test", |contest| {
+            contest.discipline = Discipline::Bench;
+            contest.organizer = Some("KS Wilk".to_string());
+        })
+        .await;
+
+        // (name, weight class, bodyweight, attempts)
+        let lifters = [
+            (
+                "Light",
+                "M_67_5",
+                66.0,
+                [
+                    (120.0, AttemptStatus::Successful),
+                    (125.0, AttemptStatus::Failed),
+                ],
+            ),
+            (
+                "Heavy",
+                "M_100",
+                98.0,
+                [
+                    (180.0, AttemptStatus::Successful),
+                    (190.0, AttemptStatus::Successful),
+                ],
+            ),
+        ];
+        for (name, weight_class_id, bodyweight, attempts) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.club = Some("KS Wilk".to_string());
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = bodyweight;
+                })
+                .await;
+            for (attempt_number, (weight, status)) in (1..).zip(attempts) {
+                judge_attempt(
+                    &pool,
+                    &registration.id,
+                    &LiftType::Bench,
+                    attempt_number,
+                    weight,
+                    status,
+                )
+                .await;
+            }
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let data = export::load_export_data(&pool, &contest.id)
+            .await
+            .expect("Failed to load export data");
+        let mut workbook = export::xlsx::build_workbook(&data).expect("Failed to build workbook");
+        for sheet in ["OPEN", "KATEGORIE WIEKOWE", "KATEGORIE WAGOWE", "DRUŻYNY"] {
+            assert!(
+                workbook.worksheet_from_name(sheet).is_ok(),
+                "missing {sheet}"
+            );
+        }
+        // Neither divisions nor best lifter awards exist in this contest
+        assert!(workbook.worksheet_from_name("DYWIZJE").is_err());
+        assert!(workbook.worksheet_from_name("NAJLEPSI ZAWODNICY").is_err());
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("results.xlsx");
+        export::xlsx::save_workbook(&data, path.to_str().unwrap()).expect("Failed to save");
+        let bytes = std::fs::read(&path).expect("Failed to read workbook");
+        assert!(bytes.starts_with(b"PK"));
+    }
+
+    #[tokio::test]
+    async fn test_pdf_protocol_lists_categories_records_and_jury() {
+        use crate::models::record::{RecordEntry, RecordLift, RecordScope};
+        use crate::models::registration::Equipment;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Protokół Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // A full weight class runs over onto a second page
+        let mut record_attempt = None;
+        for index in 0..40 {
+            let competitor =
+                create_test_competitor(&pool, &format!("Lifter{index}"), |competitor| {
+                    competitor.last_name = "Żółć".to_string();
+                    competitor.club = Some("KS Wilk".to_string());
+                })
+                .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.bodyweight = 80.0;
+                })
+                .await;
+            for (attempt_number, status) in
+                (1..).zip([AttemptStatus::Successful, AttemptStatus::Failed])
+            {
+                let attempt_id = judge_attempt(
+                    &pool,
+                    &registration.id,
+                    &LiftType::Bench,
+                    attempt_number,
+                    100.0 + index as f64 + attempt_number as f64 * 5.0,
+                    status,
+                )
+                .await;
+                record_attempt.get_or_insert((competitor.id.clone(), attempt_id));
+            }
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let (competitor_id, attempt_id) = record_attempt.unwrap();
+        database::queries::records::insert_record(
+            &pool,
+            &RecordEntry {
+                scope: RecordScope::National,
+                scope_name: None,
+                gender: "Male".to_string(),
+                weight_class_id: "M_82_5".to_string(),
+                age_category_id: "SENIOR".to_string(),
+                equipment: Equipment::Raw,
+                lift: RecordLift::Bench,
+                weight: 105.0,
+                holder_name: "Lifter0 Żółć".to_string(),
+                set_on: "2024-12-15".to_string(),
+            },
+            Some(database::queries::records::RecordSource {
+                competitor_id: &competitor_id,
+                contest_id: &contest.id,
+                attempt_id: &attempt_id,
+            }),
+        )
+        .await
+        .expect("Failed to insert record");
+
+        let data = export::load_export_data(&pool, &contest.id)
+            .await
+            .expect("Failed to load export data");
+        assert_eq!(data.records.len(), 1);
+        assert_eq!(data.records[0].weight_class_name, "DO 82.5 KG");
+        assert_eq!(data.records[0].age_category_name, "Senior");
+
+        let bytes = export::pdf::build_protocol(&data).expect("Failed to build protocol");
+        assert!(bytes.starts_with(b"%PDF"));
+        let document = printpdf::lopdf::Document::load_mem(&bytes).expect("Invalid PDF");
+        let pages: Vec<u32> = document.get_pages().into_keys().collect();
+        assert!(pages.len() >= 2, "expected the class to span pages");
+
+        let page_ids = document.get_pages();
+        let first = pdf_page_text(&document, page_ids[&pages[0]]);
+        // Polish letters keep their diacritics
+        assert!(first.contains("Podejścia nieudane"), "{first}");
+        assert!(first.contains("DO 82.5 KG"), "{first}");
+        let last = pdf_page_text(&document, page_ids[&pages[pages.len() - 1]]);
+        assert!(last.contains("Rekord Polski"), "{last}");
+        assert!(last.contains("Przewodniczący jury"), "{last}");
+        assert!(last.contains("Lifter0 Żółć"), "{last}");
+
+        let dir = tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("protokol.pdf");
+        export::pdf::save_protocol(&data, path.to_str().unwrap()).expect("Failed to save");
+        let saved = std::fs::read(&path).expect("Failed to read protocol");
+        assert!(saved.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn test_protocol_keeps_same_named_classes_of_each_sex_apart() {
+        use crate::models::contest::{RankingBasis, RankingConfig, RankingRule};
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        // Weight classes are not split by sex, yet men and women never share a class
+        let contest = create_test_contest(&pool, "Class Names Test", |contest| {
+            contest.discipline = Discipline::Bench;
+            contest.ranking = Some(RankingConfig {
+                weight_class: RankingRule {
+                    basis: RankingBasis::Total,
+                    split_by: Vec::new(),
+                },
+                ..RankingConfig::default()
+            });
+        })
+        .await;
+
+        // (name, gender, weight class, bodyweight, bench), both classes named DO 52 KG
+        let lifters = [
+            ("Man", "Male", "M_52", 51.0, 120.0),
+            ("Woman", "Female", "F_52", 50.0, 80.0),
+        ];
+        for (name, gender, weight_class_id, bodyweight, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |competitor| {
+                competitor.gender = gender.to_string();
+            })
+            .await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = bodyweight;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        let mut data = export::load_export_data(&pool, &contest.id)
+            .await
+            .expect("Failed to load export data");
+        let bytes = export::pdf::build_protocol(&data).expect("Failed to build protocol");
+        let document = printpdf::lopdf::Document::load_mem(&bytes).expect("Invalid PDF");
+        let text: String = document
+            .get_pages()
+            .into_values()
+            .map(|page_id| pdf_page_text(&document, page_id))
+            .collect();
+        assert_eq!(text.matches("DO 52 KG").count(), 2, "{text}");
+
+        // Each lifter wins their own class, the lighter class listed first
+        data.select_ranking(export::RankingKind::WeightClass);
+        let placed: Vec<(&str, Option<i64>)> = data
+            .rankings
+            .iter()
+            .map(|row| (row.first_name.as_str(), row.place))
+            .collect();
+        assert_eq!(placed, vec![("Woman", Some(1)), ("Man", Some(1))]);
+    }
+
+    #[tokio::test]
+    async fn test_export_resolves_contests_and_narrows_to_a_ranking_or_division() {
+        use crate::error::AppError;
+        use crate::models::contest::RankingBasis;
+        use crate::models::division::NewDivision;
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        for name in ["Flight A", "Flight A", "Grand Prix"] {
+            create_test_contest(&pool, name, |contest| {
+                contest.discipline = Discipline::Bench
+            })
+            .await;
+        }
+
+        let contest = export::find_contest(&pool, "grand prix")
+            .await
+            .expect("Contest not found by name");
+        assert_eq!(contest.name, "Grand Prix");
+        let by_id = export::find_contest(&pool, &contest.id)
+            .await
+            .expect("Contest not found by ID");
+        assert_eq!(by_id.id, contest.id);
+        assert!(matches!(
+            export::find_contest(&pool, "Missing").await,
+            Err(AppError::ContestNotFound { .. })
+        ));
+        // Names shared by several contests need an ID
+        assert!(matches!(
+            export::find_contest(&pool, "Flight A").await,
+            Err(AppError::InvalidInput { .. })
+        ));
+
+        // (name, weight class, bodyweight, bench)
+        let lifters = [
+            ("Heavy", "M_100", 95.0, 200.0),
+            ("Strong", "M_82_5", 81.0, 180.0),
+            ("Light", "M_82_5", 80.0, 150.0),
+        ];
+        for (name, weight_class_id, bodyweight, bench) in lifters {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.weight_class_id = weight_class_id.to_string();
+                    registration.bodyweight = bodyweight;
+                })
+                .await;
+            judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                bench,
+                AttemptStatus::Successful,
+            )
+            .await;
+            database::queries::results::calculate_results(&pool, &registration.id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::divisions::create_division(
+            &pool,
+            &NewDivision {
+                contest_id: contest.id.clone(),
+                name: "Seniors -82.5".to_string(),
+                gender: Some("Male".to_string()),
+                age_category_id: None,
+                weight_class_id: Some("M_82_5".to_string()),
+                equipment: None,
+                basis: RankingBasis::Total,
+            },
+        )
+        .await
+        .expect("Failed to create division");
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+
+        // Weight classes are listed from the lightest up with their own places
+        let mut data = export::load_export_data(&pool, &contest.id)
+            .await
+            .expect("Failed to load export data");
+        data.select_ranking(export::RankingKind::WeightClass);
+        let placed: Vec<(&str, Option<i64>)> = data
+            .rankings
+            .iter()
+            .map(|row| (row.first_name.as_str(), row.place))
+            .collect();
+        assert_eq!(
+            placed,
+            vec![("Strong", Some(1)), ("Light", Some(2)), ("Heavy", Some(1))]
+        );
+
+        let mut data = export::load_export_data(&pool, &contest.id)
+            .await
+            .expect("Failed to load export data");
+        assert!(matches!(
+            data.select_division("Juniors"),
+            Err(AppError::InvalidInput { .. })
+        ));
+        data.select_division("seniors -82.5")
+            .expect("Division not found by name");
+        assert_eq!(data.division.as_ref().unwrap().name, "Seniors -82.5");
+        assert_eq!(data.divisions.len(), 1);
+        let csv = export::export_to_csv(&data)
+            .await
+            .expect("CSV export failed");
+        assert!(csv.contains("Strong Lifter") && csv.contains("Light Lifter"));
+        assert!(!csv.contains("Heavy Lifter"));
+
+        let mut workbook = export::xlsx::build_workbook(&data).expect("Failed to build workbook");
+        assert!(workbook.worksheet_from_name("DYWIZJE").is_ok());
+        assert!(workbook.worksheet_from_name("OPEN").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_achieved_at_keeps_first_judging_order() {
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Judging Order Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+
+        // Same bodyweight and bench, judged within the same second; the lot favours the second
+        let mut attempt_ids = Vec::new();
+        let mut registration_ids = Vec::new();
+        for (name, lot) in [("First", "2"), ("Second", "1")] {
+            let competitor = create_test_competitor(&pool, name, |_| {}).await;
+            let registration =
+                register_lifter(&pool, &contest.id, &competitor.id, |registration| {
+                    registration.lot_number = Some(lot.to_string());
+                })
+                .await;
+
+            let attempt_id = judge_attempt(
+                &pool,
+                &registration.id,
+                &LiftType::Bench,
+                1,
+                150.0,
+                AttemptStatus::Successful,
+            )
+            .await;
+            attempt_ids.push(attempt_id);
+            registration_ids.push(registration.id);
+        }
+
+        // Re-judging the first attempt keeps the order in which it was first achieved
+        for status in ["Failed", "Successful"] {
+            database::queries::attempts::update_attempt_result(
+                &pool,
+                &attempt_ids[0],
+                status,
+                Some(true),
+                Some(true),
+                Some(true),
+            )
+            .await
+            .expect("Failed to re-judge attempt");
+        }
+        let achieved_at = |attempt_id: &str| {
+            sqlx::query_scalar::<_, Option<i64>>("SELECT achieved_at FROM attempts WHERE id = ?")
+                .bind(attempt_id.to_string())
+                .fetch_one(&pool)
+        };
+        let first = achieved_at(&attempt_ids[0]).await.unwrap().unwrap();
+        let second = achieved_at(&attempt_ids[1]).await.unwrap().unwrap();
+        assert!(first < second);
+
+        for registration_id in &registration_ids {
+            database::queries::results::calculate_results(&pool, registration_id)
+                .await
+                .expect("Failed to calculate results");
+        }
+        database::queries::results::update_all_rankings(&pool, &contest.id)
+            .await
+            .expect("Failed to update rankings");
+        let results = database::queries::results::get_contest_results(&pool, &contest.id)
+            .await
+            .expect("Failed to get results");
+        let place_of = |id: &str| {
+            results
+                .iter()
+                .find(|result| result.registration_id == id)
+                .and_then(|result| result.place_open)
+        };
+        assert_eq!(place_of(&registration_ids[0]), Some(1));
+        assert_eq!(place_of(&registration_ids[1]), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_successful_attempt_sets_vacant_record() {
+        use crate::models::record::{RecordLift, RecordScope};
+
+        let pool = setup_test_db()
+            .await
+            .expect("Failed to setup test database");
+
+        let contest = create_test_contest(&pool, "Vacant Record Test", |contest| {
+            contest.discipline = Discipline::Bench;
+        })
+        .await;
+        let competitor = create_test_competitor(&pool, "First", |competitor| {
+            competitor.last_name = "Holder".to_string();
+        })
+        .await;
+        let registration = register_lifter(&pool, &contest.id, &competitor.id, |_| {}).await;
+
+        // A missed lift sets nothing, even when nobody holds the record
+        let attempt_id = judge_attempt(
+            &pool,
+            &registration.id,
+            &LiftType::Bench,
+            1,
+            100.0,
+            AttemptStatus::Failed,
+        )
+        .await;
+        let broken = records::check_attempt(&pool, &attempt_id)
+            .await
+            .expect("Failed to check records");
+        assert!(broken.is_empty());
+
+        // Any good lift sets a vacant record
+        database::queries::attempts::update_attempt_result(
+            &pool,
+            &attempt_id,
+            "Successful",
+            None,
+            None,
+            None,
+        )
+        .await
+        .expect("Failed to judge attempt");
+        let broken = records::check_attempt(&pool, &attempt_id)
+            .await
+            .expect("Failed to check records");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].scope, RecordScope::National);
+        assert_eq!(broken[0].lift, RecordLift::Bench);
+        assert_eq!(broken[0].weight, 100.0);
+
+        let result =
+            database::queries::results::get_result_by_registration(&pool, &registration.id)
+                .await
+                .expect("Failed to get result");
+        assert!(result.broke_record);
+        assert_eq!(result.record_type.as_deref(), Some("National"));
+    }
+
     #[tokio::test]
     async fn test_error_handling() {
         let pool = setup_test_db()
@@ -365,6 +2866,14 @@ mod integration_tests {
             competition_type: None,
             organizer: None,
             notes: None,
+            scoring_formula: Some(ScoringFormulaKind::ReshelMcCullough),
+            federation_profile: None,
+            weight_class_set: None,
+            age_rule: None,
+            tie_breaks: None,
+            team_scoring: None,
+            ranking: None,
+            best_lifter: None,
         };
 
         // This should either succeed (if database allows empty names) or fail gracefully
@@ -2,19 +2,28 @@ use std::sync::Arc;
 // Tauri imports - removed unused Manager and State
 use tokio::sync::Mutex;
 
+pub mod best_lifter;
 pub mod coefficients;
 pub mod commands;
 pub mod database;
 pub mod error;
+pub mod export;
+pub mod federation;
 pub mod logging;
 pub mod models;
+pub mod ranking;
+pub mod recalculation;
+pub mod records;
+pub mod scoring;
 pub mod settings;
 pub mod system_health;
+pub mod team_ranking;
 
 #[cfg(test)]
 mod integration_tests;
 
 use database::DatabasePool;
+use federation::FederationRegistry;
 use logging::write_log;
 use settings::SettingsManager;
 use system_health::SystemHealth;
@@ -23,6 +32,7 @@ use system_health::SystemHealth;
 pub struct AppState {
     pub db: Arc<Mutex<DatabasePool>>, // Always available! Even if fallback/in-memory
     pub settings: Arc<Mutex<SettingsManager>>,
+    pub federations: Arc<FederationRegistry>,
     pub system_health: Arc<Mutex<SystemHealth>>,
 }
 
@@ -75,6 +85,12 @@ pub async fn run() {
         }
     }
 
+    let federations = FederationRegistry::load();
+    tracing::info!(
+        "Loaded {} federation profiles",
+        federations.profiles().len()
+    );
+
     // Initialize database - this is now infallible
     tracing::info!("Initializing database before app startup");
     let (database_pool, database_health) = database::initialize_database().await;
@@ -111,6 +127,7 @@ pub async fn run() {
     let app_state = AppState {
         db: Arc::new(Mutex::new(database_pool)),
         settings: Arc::new(Mutex::new(settings_manager)),
+        federations: Arc::new(federations),
         system_health: Arc::new(Mutex::new(system_health)),
     };
 
@@ -149,6 +166,10 @@ pub async fn run() {
             commands::contest_get,
             commands::contest_update,
             commands::contest_delete,
+            commands::contest_recalculate_coefficients,
+            commands::contest_list_scoring_formulas,
+            commands::federation_list_profiles,
+            commands::federation_get_profile,
             // Contest state management
             commands::contest_state_get,
             commands::contest_state_update,
@@ -180,11 +201,34 @@ pub async fn run() {
             // Results and rankings
             commands::result_calculate,
             commands::result_get_rankings,
+            commands::result_get_podiums,
             commands::result_get_competitor_results,
             commands::result_export,
             commands::result_get_scoreboard,
+            commands::result_disqualify,
+            commands::result_mark_dnf,
+            commands::result_reinstate,
+            commands::result_get_status_log,
+            commands::result_get_team_rankings,
+            commands::result_get_best_lifters,
+            // Divisions
+            commands::division_list,
+            commands::division_create,
+            commands::division_create_age_weight,
+            commands::division_delete,
+            commands::division_enter,
+            commands::division_withdraw,
+            commands::division_list_entered,
+            commands::division_get_placings,
+            // Records
+            commands::record_list,
+            commands::record_list_for_contest,
+            commands::record_import,
+            commands::record_export,
+            commands::record_delete,
             // Category management
             commands::weight_class_list,
+            commands::weight_class_set_list,
             commands::age_category_list,
             // Plate set management
             commands::plate_set_create,
@@ -2,10 +2,18 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use clap::{Parser, Subcommand};
+use sqlx::{Pool, Sqlite};
+use std::io::Write;
+use werewolf_lib::database::queries::results::get_best_lifter_awards;
 use werewolf_lib::database::{
     create_pool, demo_data::generate_demo_data, get_database_path, get_migration_info,
     reset_database, run_migrations,
 };
+use werewolf_lib::error::AppError;
+use werewolf_lib::export;
+use werewolf_lib::federation::FederationRegistry;
+use werewolf_lib::models::contest::Contest;
+use werewolf_lib::recalculation::recalculate_contest;
 
 #[derive(Parser)]
 #[command(name = "werewolf")]
@@ -27,11 +35,31 @@ enum Commands {
     },
     /// Export contest results to various formats
     Export {
-        contest_id: String,
+        /// ID or name of the contest
+        contest: String,
         #[arg(value_enum)]
         format: ExportFormat,
+        /// File to write to; the export goes to stdout without it
         #[arg(short, long)]
         output: Option<String>,
+        /// Only list one of the rankings, placed within its groups
+        #[arg(long, value_enum, conflicts_with = "division")]
+        ranking: Option<RankingArg>,
+        /// Only list one named division, given by its ID or name
+        #[arg(long)]
+        division: Option<String>,
+    },
+    /// Export a contest's best lifter awards as JSON
+    BestLifters {
+        /// ID or name of the contest
+        contest: String,
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Recalculate coefficients, categories and results of a contest's registrations
+    Recalculate {
+        /// ID or name of the contest
+        contest: String,
     },
     /// Generate demo competition data with 10 competitors
     Demo {
@@ -62,7 +90,24 @@ enum ExportFormat {
     Excel, // Excel (.xlsx) - Federation standard
     Csv,   // CSV format
     Json,  // JSON format
-    Pdf,   // PDF results sheet
+    Pdf,   // PDF contest protocol
+}
+
+#[derive(clap::ValueEnum, Clone, Copy)]
+enum RankingArg {
+    Open,
+    AgeClass,
+    WeightClass,
+}
+
+impl From<RankingArg> for export::RankingKind {
+    fn from(ranking: RankingArg) -> Self {
+        match ranking {
+            RankingArg::Open => export::RankingKind::Open,
+            RankingArg::AgeClass => export::RankingKind::AgeClass,
+            RankingArg::WeightClass => export::RankingKind::WeightClass,
+        }
+    }
 }
 
 #[tokio::main]
@@ -82,13 +127,28 @@ async fn main() {
             }
         }
         Some(Commands::Export {
-            contest_id,
+            contest,
             format,
             output,
+            ranking,
+            division,
         }) => {
-            if let Err(e) = handle_export_command(contest_id, format, output).await {
+            if let Err(e) = handle_export_command(contest, format, output, ranking, division).await
+            {
                 eprintln!("Export error: {e}");
-                std::process::exit(1);
+                std::process::exit(exit_code(&*e));
+            }
+        }
+        Some(Commands::BestLifters { contest, output }) => {
+            if let Err(e) = handle_best_lifters_command(contest, output).await {
+                eprintln!("Best lifter export error: {e}");
+                std::process::exit(exit_code(&*e));
+            }
+        }
+        Some(Commands::Recalculate { contest }) => {
+            if let Err(e) = handle_recalculate_command(contest).await {
+                eprintln!("Recalculation error: {e}");
+                std::process::exit(exit_code(&*e));
             }
         }
         Some(Commands::Demo { force }) => {
@@ -161,12 +221,95 @@ async fn handle_db_command(action: DbCommands) -> Result<(), Box<dyn std::error:
     Ok(())
 }
 
+/// Open the existing database and find a contest by its ID or name
+async fn open_contest(
+    reference: &str,
+) -> Result<(Pool<Sqlite>, Contest), Box<dyn std::error::Error>> {
+    let db_path = get_database_path();
+    // Opening a missing database would create an empty one
+    if !std::path::Path::new(&db_path).exists() {
+        return Err(format!("No database found at {db_path}").into());
+    }
+    let db_url = format!("sqlite:{db_path}");
+
+    let pool = create_pool(&db_url).await?;
+    let contest = export::find_contest(&pool, reference).await?;
+    Ok((pool, contest))
+}
+
+/// Exit code of a failed contest command; scripts can tell a missing contest apart from
+/// other failures
+fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    match error.downcast_ref::<AppError>() {
+        Some(AppError::ContestNotFound { .. }) => 2,
+        _ => 1,
+    }
+}
+
 async fn handle_export_command(
-    _contest_id: String,
-    _format: ExportFormat,
-    _output: Option<String>,
+    contest: String,
+    format: ExportFormat,
+    output: Option<String>,
+    ranking: Option<RankingArg>,
+    division: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (pool, contest) = open_contest(&contest).await?;
+    let mut data = export::load_export_data(&pool, &contest.id).await?;
+    if let Some(ranking) = ranking {
+        data.select_ranking(ranking.into());
+    }
+    if let Some(division) = division {
+        data.select_division(&division)?;
+    }
+
+    let bytes = match format {
+        ExportFormat::Excel => export::xlsx::build_workbook(&data)?.save_to_buffer()?,
+        ExportFormat::Csv => export::export_to_csv(&data).await?.into_bytes(),
+        ExportFormat::Json => format!("{}\n", export::export_to_json(&data).await?).into_bytes(),
+        ExportFormat::Pdf => export::pdf::build_protocol(&data)?,
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, bytes)?;
+            println!("Exported {} to {path}", contest.name);
+        }
+        None => std::io::stdout().write_all(&bytes)?,
+    }
+    Ok(())
+}
+
+async fn handle_best_lifters_command(
+    contest: String,
+    output: Option<String>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Export functionality will be implemented in a future version");
+    let (pool, contest) = open_contest(&contest).await?;
+    let awards = get_best_lifter_awards(&pool, &contest.id).await?;
+    let json = serde_json::to_string_pretty(&awards)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            println!(
+                "Exported {} best lifter awards to {path}",
+                awards.iter().filter(|award| award.awarded).count()
+            );
+        }
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
+async fn handle_recalculate_command(contest: String) -> Result<(), Box<dyn std::error::Error>> {
+    let (pool, contest) = open_contest(&contest).await?;
+    let federations = FederationRegistry::load();
+    let registrations = recalculate_contest(&pool, &federations, &contest.id).await?;
+
+    println!(
+        "Recalculated {} registrations for {}",
+        registrations.len(),
+        contest.name
+    );
     Ok(())
 }
 
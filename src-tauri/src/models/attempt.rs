@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use specta::Type;
 use strum::{Display, EnumString};
 
+/// The 4th attempt is only allowed as a record attempt and does not count towards the total
+pub const RECORD_ATTEMPT_NUMBER: i32 = 4;
+
 #[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq, Display, EnumString)]
 #[serde(rename_all = "PascalCase")]
 #[strum(serialize_all = "PascalCase")]
@@ -28,6 +31,7 @@ pub struct Attempt {
     pub registration_id: String,
     pub lift_type: LiftType,
     pub attempt_number: i32,
+    pub is_record_attempt: bool,
     pub weight: f64,
     pub status: AttemptStatus,
     pub timestamp: Option<String>,
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct WeightClass {
     pub id: String,
+    #[serde(rename = "classSet")]
+    pub class_set: String,
     pub gender: String,
     pub name: String,
     #[serde(rename = "minWeight")]
@@ -14,6 +16,8 @@ pub struct WeightClass {
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct AgeCategory {
     pub id: String,
+    #[serde(rename = "categorySet")]
+    pub category_set: String,
     pub name: String,
     #[serde(rename = "minAge")]
     pub min_age: Option<i64>,
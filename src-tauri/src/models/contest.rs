@@ -1,3 +1,5 @@
+use crate::federation::DEFAULT_FEDERATION;
+use crate::models::attempt::LiftType;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
@@ -14,6 +16,18 @@ pub enum Discipline {
     Powerlifting,
 }
 
+impl Discipline {
+    /// Lifts that make up the total; missing one of them means no total
+    pub fn lifts(&self) -> &'static [LiftType] {
+        match self {
+            Discipline::Bench => &[LiftType::Bench],
+            Discipline::Squat => &[LiftType::Squat],
+            Discipline::Deadlift => &[LiftType::Deadlift],
+            Discipline::Powerlifting => &[LiftType::Squat, LiftType::Bench, LiftType::Deadlift],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, EnumString, Display, sqlx::Type, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 #[strum(serialize_all = "PascalCase")]
@@ -25,6 +39,190 @@ pub enum ContestStatus {
     Completed,
 }
 
+/// Identifier of the scoring formula persisted on a contest; see `crate::scoring`.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, EnumString, Display, sqlx::Type, PartialEq,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum ScoringFormulaKind {
+    #[default]
+    ReshelMcCullough,
+    IpfGl,
+    Dots,
+    Wilks,
+    Wilks2020,
+}
+
+/// How the age used for age category assignment is determined
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
+    sqlx::Type,
+    specta::Type,
+    PartialEq,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum AgeRule {
+    /// Age in full years on the contest date
+    #[default]
+    ExactDate,
+    /// Age reached during the contest's calendar year
+    BirthYear,
+}
+
+/// Criterion separating lifters with equal points, applied in the contest's order
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+pub enum TieBreak {
+    /// Lighter lifter ranks higher
+    Bodyweight,
+    /// Lifter who reached the total first ranks higher
+    AchievedAt,
+    /// Lower lot number ranks higher
+    LotNumber,
+}
+
+/// Federation tie-break chain: bodyweight, then order of achievement, then lot number
+pub const DEFAULT_TIE_BREAKS: [TieBreak; 3] = [
+    TieBreak::Bodyweight,
+    TieBreak::AchievedAt,
+    TieBreak::LotNumber,
+];
+
+/// Team classification: a club scores the place points of its best N lifters
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamScoring {
+    /// Number of a club's lifters whose points count
+    pub top_n: u32,
+    /// Points for 1st, 2nd, 3rd... place in a weight class; lower places score nothing
+    pub place_points: Vec<u32>,
+}
+
+impl Default for TeamScoring {
+    fn default() -> Self {
+        TeamScoring {
+            top_n: 5,
+            place_points: vec![12, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        }
+    }
+}
+
+impl TeamScoring {
+    /// Points earned for a place
+    pub fn points_for(&self, place: i64) -> u32 {
+        usize::try_from(place - 1)
+            .ok()
+            .and_then(|index| self.place_points.get(index))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// What a ranking is decided by
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
+    sqlx::Type,
+    specta::Type,
+    PartialEq,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum RankingBasis {
+    /// Absolute total in kg
+    Total,
+    /// Coefficient points of the contest's scoring formula
+    Points,
+}
+
+/// Attribute by which a ranking is held separately
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, EnumString, Display, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+pub enum RankingSplit {
+    Sex,
+    Equipment,
+    AgeCategory,
+}
+
+/// How one ranking is placed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingRule {
+    pub basis: RankingBasis,
+    /// Lifters are only ranked against lifters sharing all of these
+    pub split_by: Vec<RankingSplit>,
+}
+
+/// Placing rules of the open, age class and weight class rankings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RankingConfig {
+    pub open: RankingRule,
+    pub age_class: RankingRule,
+    pub weight_class: RankingRule,
+}
+
+impl Default for RankingConfig {
+    /// Open and age class rankings by points, weight classes by total, each held separately
+    /// for men and women and per equipment division
+    fn default() -> Self {
+        let by_points = RankingRule {
+            basis: RankingBasis::Points,
+            split_by: vec![RankingSplit::Sex, RankingSplit::Equipment],
+        };
+        RankingConfig {
+            open: by_points.clone(),
+            age_class: by_points,
+            weight_class: RankingRule {
+                basis: RankingBasis::Total,
+                split_by: vec![RankingSplit::Sex, RankingSplit::Equipment],
+            },
+        }
+    }
+}
+
+/// Best lifter trophies: one per award group, given when the group has enough entrants
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BestLifterAwards {
+    /// Attributes the award groups are formed by; empty for a single overall award
+    pub split_by: Vec<RankingSplit>,
+    /// Entrants a group needs for its award to be given
+    pub min_entrants: u32,
+}
+
+impl Default for BestLifterAwards {
+    fn default() -> Self {
+        BestLifterAwards {
+            split_by: vec![
+                RankingSplit::Sex,
+                RankingSplit::AgeCategory,
+                RankingSplit::Equipment,
+            ],
+            min_entrants: 3,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Contest {
     pub id: String,
@@ -37,6 +235,24 @@ pub struct Contest {
     pub competition_type: Option<String>,
     pub organizer: Option<String>,
     pub notes: Option<String>,
+    #[serde(default)]
+    pub scoring_formula: ScoringFormulaKind,
+    // Federation profile chosen at creation; see `crate::federation`
+    #[serde(default = "default_federation_profile")]
+    pub federation_profile: String,
+    // Set of weight classes lifters are assigned to
+    #[serde(default = "default_federation_profile")]
+    pub weight_class_set: String,
+    #[serde(default)]
+    pub age_rule: AgeRule,
+    #[serde(default = "default_tie_breaks")]
+    pub tie_breaks: Vec<TieBreak>,
+    #[serde(default)]
+    pub team_scoring: TeamScoring,
+    #[serde(default)]
+    pub ranking: RankingConfig,
+    #[serde(default)]
+    pub best_lifter: BestLifterAwards,
     pub is_archived: bool,
     pub created_at: String,
     pub updated_at: String,
@@ -52,4 +268,37 @@ pub struct NewContest {
     pub competition_type: Option<String>,
     pub organizer: Option<String>,
     pub notes: Option<String>,
+    // Defaults to the federation profile's formula when not given
+    #[serde(default)]
+    pub scoring_formula: Option<ScoringFormulaKind>,
+    // Defaults to the configured default federation when not given
+    #[serde(default)]
+    pub federation_profile: Option<String>,
+    // Defaults to the federation profile's own class set when not given
+    #[serde(default)]
+    pub weight_class_set: Option<String>,
+    // Defaults to the federation profile's age rule when not given
+    #[serde(default)]
+    pub age_rule: Option<AgeRule>,
+    // Defaults to the federation tie-break chain when not given
+    #[serde(default)]
+    pub tie_breaks: Option<Vec<TieBreak>>,
+    // Defaults to the best 5 lifters scoring 12-9-8-7-6-5-4-3-2-1
+    #[serde(default)]
+    pub team_scoring: Option<TeamScoring>,
+    // Defaults to points for open and age class rankings and total for weight classes,
+    // split by sex and equipment
+    #[serde(default)]
+    pub ranking: Option<RankingConfig>,
+    // Defaults to awards per sex, age category and equipment with at least 3 entrants
+    #[serde(default)]
+    pub best_lifter: Option<BestLifterAwards>,
+}
+
+fn default_federation_profile() -> String {
+    DEFAULT_FEDERATION.to_string()
+}
+
+fn default_tie_breaks() -> Vec<TieBreak> {
+    DEFAULT_TIE_BREAKS.to_vec()
 }
@@ -0,0 +1,66 @@
+use crate::models::contest::RankingBasis;
+use crate::models::registration::Equipment;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Built-in rankings every contest has, addressed like divisions
+pub const OPEN_DIVISION: &str = "open";
+pub const AGE_CLASS_DIVISION: &str = "age";
+pub const WEIGHT_CLASS_DIVISION: &str = "weight";
+
+/// Named ranking of the lifters matching all of its filters; a filter that is not set
+/// matches every lifter
+#[derive(Serialize, Deserialize, Type, Debug, Clone, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Division {
+    pub id: String,
+    pub contest_id: String,
+    pub name: String,
+    pub gender: Option<String>,
+    pub age_category_id: Option<String>,
+    pub weight_class_id: Option<String>,
+    pub equipment: Option<Equipment>,
+    pub basis: RankingBasis,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewDivision {
+    pub contest_id: String,
+    pub name: String,
+    pub gender: Option<String>,
+    pub age_category_id: Option<String>,
+    pub weight_class_id: Option<String>,
+    pub equipment: Option<Equipment>,
+    pub basis: RankingBasis,
+}
+
+impl Division {
+    /// Whether a lifter of the given categories is ranked in the division without entering it
+    pub fn matches(
+        &self,
+        gender: &str,
+        age_category_id: &str,
+        weight_class_id: &str,
+        equipment: Equipment,
+    ) -> bool {
+        self.age_category_id
+            .as_deref()
+            .map_or(true, |filter| filter == age_category_id)
+            && self.admits(gender, weight_class_id, equipment)
+    }
+
+    /// Whether a lifter of the given categories may enter the division. Lifters can enter
+    /// divisions of another age category, never of another sex, weight class or equipment.
+    pub fn admits(&self, gender: &str, weight_class_id: &str, equipment: Equipment) -> bool {
+        self.gender
+            .as_deref()
+            .map_or(true, |filter| filter == gender)
+            && self
+                .weight_class_id
+                .as_deref()
+                .map_or(true, |filter| filter == weight_class_id)
+            && self.equipment.map_or(true, |filter| filter == equipment)
+    }
+}
@@ -3,5 +3,7 @@ pub mod category;
 pub mod competitor;
 pub mod contest;
 pub mod contest_state;
+pub mod division;
 pub mod plate_set;
+pub mod record;
 pub mod registration;
@@ -0,0 +1,108 @@
+use crate::models::attempt::LiftType;
+use crate::models::registration::Equipment;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use strum::{Display, EnumString};
+
+/// Reach of a record; club and regional records are held per club or region name
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
+    sqlx::Type,
+    Type,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum RecordScope {
+    Club,
+    Regional,
+    National,
+}
+
+/// Lift a record is held in
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, EnumString, Display, sqlx::Type, Type, PartialEq,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum RecordLift {
+    Squat,
+    Bench,
+    Deadlift,
+    Total,
+}
+
+impl From<&LiftType> for RecordLift {
+    fn from(lift_type: &LiftType) -> Self {
+        match lift_type {
+            LiftType::Squat => RecordLift::Squat,
+            LiftType::Bench => RecordLift::Bench,
+            LiftType::Deadlift => RecordLift::Deadlift,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+    pub id: String,
+    pub scope: RecordScope,
+    pub scope_name: Option<String>,
+    pub gender: String,
+    pub weight_class_id: String,
+    pub age_category_id: String,
+    pub equipment: Equipment,
+    pub lift: RecordLift,
+    pub weight: f64,
+    pub holder_name: String,
+    pub set_on: String,
+    // Set when the record was broken in a contest run by the application
+    pub competitor_id: Option<String>,
+    pub contest_id: Option<String>,
+    pub attempt_id: Option<String>,
+    pub created_at: String,
+}
+
+/// Record as imported from, and exported to, a record table
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordEntry {
+    pub scope: RecordScope,
+    pub scope_name: Option<String>,
+    pub gender: String,
+    pub weight_class_id: String,
+    pub age_category_id: String,
+    pub equipment: Equipment,
+    pub lift: RecordLift,
+    pub weight: f64,
+    pub holder_name: String,
+    pub set_on: String,
+}
+
+impl From<Record> for RecordEntry {
+    fn from(record: Record) -> Self {
+        RecordEntry {
+            scope: record.scope,
+            scope_name: record.scope_name,
+            gender: record.gender,
+            weight_class_id: record.weight_class_id,
+            age_category_id: record.age_category_id,
+            equipment: record.equipment,
+            lift: record.lift,
+            weight: record.weight,
+            holder_name: record.holder_name,
+            set_on: record.set_on,
+        }
+    }
+}
@@ -1,5 +1,7 @@
+use crate::models::contest::AgeRule;
 use serde::{Deserialize, Serialize};
 use specta::Type;
+use strum::{Display, EnumString};
 
 #[derive(Serialize, Deserialize, Type, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -9,6 +11,8 @@ pub struct Registration {
     pub competitor_id: String,
     pub age_category_id: String,
     pub weight_class_id: String,
+    // Chosen by hand rather than assigned from the bodyweight
+    pub weight_class_manual: bool,
     // Equipment flags
     pub equipment_m: bool,
     pub equipment_sm: bool,
@@ -20,6 +24,12 @@ pub struct Registration {
     // Calculated coefficients
     pub reshel_coefficient: Option<f64>,
     pub mccullough_coefficient: Option<f64>,
+    // Coefficient of the contest's scoring formula
+    pub scoring_coefficient: Option<f64>,
+    // Age in full years the McCullough factor was computed from
+    pub age_at_contest: Option<u32>,
+    // Rule the age category was assigned by; None when chosen by hand
+    pub age_rule: Option<AgeRule>,
     // Rack heights
     pub rack_height_squat: Option<i32>,
     pub rack_height_bench: Option<i32>,
@@ -44,3 +54,43 @@ pub struct RegistrationCreate {
     pub rack_height_squat: Option<i32>,
     pub rack_height_bench: Option<i32>,
 }
+
+/// Equipment division of a lifter, derived from the registration's equipment flags
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    EnumString,
+    Display,
+    sqlx::Type,
+    Type,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "PascalCase")]
+#[strum(serialize_all = "PascalCase")]
+#[sqlx(rename_all = "PascalCase")]
+pub enum Equipment {
+    Raw,
+    SinglePly,
+    MultiPly,
+}
+
+impl Equipment {
+    /// Division for the equipment flags; multi-ply wins over single-ply, and a shirt or
+    /// suit alone counts as single-ply
+    pub fn from_flags(multi_ply: bool, single_ply: bool, shirt: bool) -> Self {
+        if multi_ply {
+            Equipment::MultiPly
+        } else if single_ply || shirt {
+            Equipment::SinglePly
+        } else {
+            Equipment::Raw
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Placing of lifters within a ranking.
+//!
+//! Lifters are ordered by their score, the total or the coefficient points depending on the
+//! ranking; lifters with an equal score are separated by the contest's tie-break chain.
+//! Lifters still equal after the whole chain share a place, and the next lifter's place skips
+//! the shared ones (1, 1, 3).
+
+use crate::models::contest::TieBreak;
+use std::cmp::Ordering;
+
+/// A result taking part in a ranking
+#[derive(Debug, Clone)]
+pub struct RankEntry {
+    pub result_id: String,
+    /// Total or coefficient points, whichever the ranking is decided by
+    pub score: f64,
+    pub bodyweight: f64,
+    /// Judging order of the last lift contributing to the total; lower was achieved first
+    pub achieved_at: Option<i64>,
+    pub lot_number: Option<String>,
+}
+
+/// Order two entries: higher score first, then by the tie-break chain
+pub fn compare(a: &RankEntry, b: &RankEntry, tie_breaks: &[TieBreak]) -> Ordering {
+    b.score
+        .total_cmp(&a.score)
+        .then_with(|| break_tie(a, b, tie_breaks))
+}
+
+fn break_tie(a: &RankEntry, b: &RankEntry, tie_breaks: &[TieBreak]) -> Ordering {
+    tie_breaks
+        .iter()
+        .map(|tie_break| match tie_break {
+            TieBreak::Bodyweight => a.bodyweight.total_cmp(&b.bodyweight),
+            TieBreak::AchievedAt => {
+                compare_missing_last(a.achieved_at.as_ref(), b.achieved_at.as_ref(), Ord::cmp)
+            }
+            TieBreak::LotNumber => compare_missing_last(
+                a.lot_number.as_deref(),
+                b.lot_number.as_deref(),
+                compare_lot_numbers,
+            ),
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+fn compare_missing_last<T>(
+    a: Option<T>,
+    b: Option<T>,
+    cmp: impl Fn(&T, &T) -> Ordering,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => cmp(&a, &b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+// Lot numbers are entered as text; numeric ones compare by value
+fn compare_lot_numbers(a: &&str, b: &&str) -> Ordering {
+    match (a.trim().parse::<u32>(), b.trim().parse::<u32>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Assign places to the entries of one ranking, returned as (result ID, place) pairs
+pub fn assign_places(mut entries: Vec<RankEntry>, tie_breaks: &[TieBreak]) -> Vec<(String, i64)> {
+    entries.sort_by(|a, b| compare(a, b, tie_breaks));
+
+    let mut places: Vec<(String, i64)> = Vec::with_capacity(entries.len());
+    for (index, entry) in entries.iter().enumerate() {
+        let place = match index.checked_sub(1).map(|previous| &entries[previous]) {
+            Some(previous) if compare(previous, entry, tie_breaks).is_eq() => places[index - 1].1,
+            _ => index as i64 + 1,
+        };
+        places.push((entry.result_id.clone(), place));
+    }
+    places
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::contest::DEFAULT_TIE_BREAKS;
+
+    fn entry(id: &str, score: f64, bodyweight: f64, achieved_at: i64, lot: &str) -> RankEntry {
+        RankEntry {
+            result_id: id.to_string(),
+            score,
+            bodyweight,
+            achieved_at: Some(achieved_at),
+            lot_number: Some(lot.to_string()),
+        }
+    }
+
+    fn place_of(places: &[(String, i64)], id: &str) -> i64 {
+        places
+            .iter()
+            .find(|(result_id, _)| result_id == id)
+            .unwrap()
+            .1
+    }
+
+    #[test]
+    fn test_higher_score_ranks_first() {
+        let places = assign_places(
+            vec![
+                entry("a", 400.0, 80.0, 2, "1"),
+                entry("b", 450.0, 90.0, 3, "2"),
+            ],
+            &DEFAULT_TIE_BREAKS,
+        );
+        assert_eq!(place_of(&places, "b"), 1);
+        assert_eq!(place_of(&places, "a"), 2);
+    }
+
+    #[test]
+    fn test_lighter_lifter_wins_tie() {
+        let places = assign_places(
+            vec![
+                entry("heavy", 400.0, 82.4, 2, "1"),
+                entry("light", 400.0, 81.9, 3, "2"),
+            ],
+            &DEFAULT_TIE_BREAKS,
+        );
+        assert_eq!(place_of(&places, "light"), 1);
+        assert_eq!(place_of(&places, "heavy"), 2);
+    }
+
+    #[test]
+    fn test_earlier_total_wins_tie_at_equal_bodyweight() {
+        let places = assign_places(
+            vec![
+                entry("late", 400.0, 82.0, 6, "1"),
+                entry("early", 400.0, 82.0, 5, "2"),
+            ],
+            &DEFAULT_TIE_BREAKS,
+        );
+        assert_eq!(place_of(&places, "early"), 1);
+        assert_eq!(place_of(&places, "late"), 2);
+    }
+
+    #[test]
+    fn test_lot_number_is_last_tie_break() {
+        let places = assign_places(
+            vec![
+                entry("lot10", 400.0, 82.0, 4, "10"),
+                entry("lot9", 400.0, 82.0, 4, "9"),
+            ],
+            &DEFAULT_TIE_BREAKS,
+        );
+        assert_eq!(place_of(&places, "lot9"), 1);
+        assert_eq!(place_of(&places, "lot10"), 2);
+    }
+
+    #[test]
+    fn test_configured_chain_is_respected() {
+        let entries = vec![
+            entry("a", 400.0, 90.0, 2, "2"),
+            entry("b", 400.0, 80.0, 3, "1"),
+            entry("c", 300.0, 70.0, 1, "3"),
+        ];
+
+        let places = assign_places(entries.clone(), &[TieBreak::AchievedAt]);
+        assert_eq!(place_of(&places, "a"), 1);
+        assert_eq!(place_of(&places, "b"), 2);
+
+        // Without tie-breaks tied lifters share a place and the next place is skipped
+        let places = assign_places(entries, &[]);
+        assert_eq!(place_of(&places, "a"), 1);
+        assert_eq!(place_of(&places, "b"), 1);
+        assert_eq!(place_of(&places, "c"), 3);
+    }
+
+    #[test]
+    fn test_missing_values_rank_last() {
+        let mut without_lot = entry("without_lot", 400.0, 82.0, 4, "1");
+        without_lot.lot_number = None;
+        let places = assign_places(
+            vec![without_lot, entry("with_lot", 400.0, 82.0, 4, "5")],
+            &DEFAULT_TIE_BREAKS,
+        );
+        assert_eq!(place_of(&places, "with_lot"), 1);
+        assert_eq!(place_of(&places, "without_lot"), 2);
+    }
+}
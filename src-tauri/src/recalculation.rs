@@ -0,0 +1,350 @@
+//! Registration values derived from lifter and contest data.
+//!
+//! Coefficients, the age on contest day and auto-assigned age categories and weight classes
+//! depend on the bodyweight, the competitor's birth date and gender, and the contest's date
+//! and rules. They are derived when a registration is saved and re-derived whenever one of
+//! those inputs changes; existing results of the affected registrations are recalculated.
+//! A changed attempt recalculates the result of its registration and re-places it in the
+//! rankings it belongs to.
+
+use crate::coefficients;
+use crate::database::queries;
+use crate::database::queries::competitors::Competitor;
+use crate::database::queries::registrations::{CreateRegistrationRequest, Registration};
+use crate::database::queries::results::CompetitionResult;
+use crate::error::AppError;
+use crate::federation::{FederationProfile, FederationRegistry};
+use crate::models::contest::{AgeRule, Contest};
+use crate::models::registration::Equipment;
+use crate::records;
+use crate::scoring::{self, LifterProfile};
+use sqlx::{Acquire, Pool, Sqlite};
+
+/// Categories and coefficients derived for a registration
+#[derive(Debug, Clone)]
+pub struct DerivedValues {
+    pub age_category_id: String,
+    // None when the age category was chosen by hand
+    pub age_rule: Option<AgeRule>,
+    pub weight_class_id: String,
+    pub weight_class_manual: bool,
+    pub reshel_coefficient: Option<f64>,
+    pub mccullough_coefficient: Option<f64>,
+    pub scoring_coefficient: f64,
+    pub age_at_contest: u32,
+}
+
+/// Lifter data a registration's derived values depend on
+pub struct RegistrationInput<'a> {
+    pub contest: &'a Contest,
+    pub competitor: &'a Competitor,
+    pub bodyweight: f64,
+    pub equipment: Equipment,
+    // Categories chosen by hand; None assigns them automatically
+    pub age_category_id: Option<String>,
+    pub weight_class_id: Option<String>,
+}
+
+/// Derive coefficients and categories for a registration
+pub async fn derive_registration<'a, A>(
+    conn: A,
+    profile: &FederationProfile,
+    input: RegistrationInput<'_>,
+) -> Result<DerivedValues, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let RegistrationInput {
+        contest,
+        competitor,
+        bodyweight,
+        equipment,
+        age_category_id,
+        weight_class_id,
+    } = input;
+
+    let contest_date = contest.date.to_string();
+    let age = coefficients::age_on_date(&competitor.birth_date, &contest_date)?;
+    let lifter = LifterProfile {
+        bodyweight,
+        gender: &competitor.gender,
+        age,
+        // Equipped lifters are scored with the formula's equipped variant
+        equipped: equipment != Equipment::Raw,
+        discipline: &contest.discipline,
+    };
+    let scoring_coefficient = scoring::formula(contest.scoring_formula).coefficient(&lifter)?;
+    // Only Reshel/McCullough contests depend on the Reshel coefficient, so a lifter whose
+    // gender it does not cover can still register for other formulas
+    let reshel_coefficient =
+        coefficients::calculate_reshel_coefficient(bodyweight, &competitor.gender).ok();
+    let mccullough_coefficient = Some(coefficients::mccullough_factor(age));
+
+    let weight_class_manual = weight_class_id.is_some();
+    let (age_category_id, age_rule) =
+        resolve_age_category(profile, contest, &competitor.birth_date, age_category_id)?;
+    let weight_class_id = resolve_weight_class(
+        conn,
+        contest,
+        &competitor.gender,
+        bodyweight,
+        weight_class_id,
+    )
+    .await?;
+
+    Ok(DerivedValues {
+        age_category_id,
+        age_rule,
+        weight_class_id,
+        weight_class_manual,
+        reshel_coefficient,
+        mccullough_coefficient,
+        scoring_coefficient,
+        age_at_contest: age,
+    })
+}
+
+/// Resolve the age category for a registration under the contest's age rule.
+/// Returns the rule applied, or None when the category was chosen by hand.
+fn resolve_age_category(
+    profile: &FederationProfile,
+    contest: &Contest,
+    birth_date: &str,
+    age_category_id: Option<String>,
+) -> Result<(String, Option<AgeRule>), AppError> {
+    if let Some(id) = age_category_id {
+        return Ok((id, None));
+    }
+
+    let age = coefficients::category_age(contest.age_rule, birth_date, &contest.date.to_string())?;
+    let category = profile
+        .age_category_for(age)
+        .ok_or_else(|| AppError::InvalidInput {
+            field: "birth_date".to_string(),
+            reason: format!("No {} age category for age {}", profile.id, age),
+        })?;
+
+    Ok((category.id.clone(), Some(contest.age_rule)))
+}
+
+/// Resolve the weight class for a registration from the contest's class set.
+/// A class chosen by hand must belong to that set and match the lifter's gender.
+async fn resolve_weight_class<'a, A>(
+    conn: A,
+    contest: &Contest,
+    gender: &str,
+    bodyweight: f64,
+    weight_class_id: Option<String>,
+) -> Result<String, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+    let invalid = |reason: String| AppError::InvalidInput {
+        field: "weight_class_id".to_string(),
+        reason,
+    };
+
+    let Some(id) = weight_class_id else {
+        return queries::categories::find_weight_class(
+            &mut *conn,
+            &contest.weight_class_set,
+            gender,
+            bodyweight,
+        )
+        .await?
+        .map(|class| class.id)
+        .ok_or_else(|| AppError::InvalidInput {
+            field: "bodyweight".to_string(),
+            reason: format!(
+                "No {} weight class for {} kg ({})",
+                contest.weight_class_set, bodyweight, gender
+            ),
+        });
+    };
+
+    let class = queries::categories::get_weight_class_by_id(&mut *conn, &id)
+        .await?
+        .ok_or_else(|| invalid(format!("Unknown weight class {}", id)))?;
+    if class.gender != gender {
+        return Err(invalid(format!(
+            "Weight class {} is for {} lifters, not {}",
+            class.name, class.gender, gender
+        )));
+    }
+    if class.class_set != contest.weight_class_set {
+        return Err(invalid(format!(
+            "Weight class {} is not in the contest's {} class set",
+            class.id, contest.weight_class_set
+        )));
+    }
+
+    Ok(class.id)
+}
+
+/// Re-derive a stored registration from the current competitor and contest data.
+/// Categories chosen by hand are kept; results are recalculated if they exist.
+pub async fn recalculate_registration<'a, A>(
+    conn: A,
+    federations: &FederationRegistry,
+    registration_id: &str,
+) -> Result<Registration, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+
+    let registration =
+        queries::registrations::get_registration_by_id(&mut *conn, registration_id).await?;
+    let competitor =
+        queries::competitors::get_competitor_by_id(&mut *conn, &registration.competitor_id).await?;
+    let contest = queries::contests::get_contest_by_id(&mut *conn, &registration.contest_id)
+        .await?
+        .ok_or_else(|| AppError::ContestNotFound {
+            id: registration.contest_id.clone(),
+        })?;
+    let profile = federations.get(&contest.federation_profile)?;
+
+    let derived = derive_registration(
+        &mut *conn,
+        profile,
+        RegistrationInput {
+            contest: &contest,
+            competitor: &competitor,
+            bodyweight: registration.bodyweight,
+            equipment: registration.equipment(),
+            age_category_id: registration
+                .age_rule
+                .is_none()
+                .then(|| registration.age_category_id.clone()),
+            weight_class_id: registration
+                .weight_class_manual
+                .then(|| registration.weight_class_id.clone()),
+        },
+    )
+    .await?;
+
+    let request = CreateRegistrationRequest {
+        contest_id: registration.contest_id,
+        competitor_id: registration.competitor_id,
+        age_category_id: derived.age_category_id,
+        weight_class_id: derived.weight_class_id,
+        weight_class_manual: derived.weight_class_manual,
+        equipment_m: registration.equipment_m,
+        equipment_sm: registration.equipment_sm,
+        equipment_t: registration.equipment_t,
+        bodyweight: registration.bodyweight,
+        lot_number: registration.lot_number,
+        personal_record_at_entry: registration.personal_record_at_entry,
+        reshel_coefficient: derived.reshel_coefficient,
+        mccullough_coefficient: derived.mccullough_coefficient,
+        scoring_coefficient: Some(derived.scoring_coefficient),
+        age_at_contest: Some(derived.age_at_contest),
+        age_rule: derived.age_rule,
+        rack_height_squat: registration.rack_height_squat,
+        rack_height_bench: registration.rack_height_bench,
+    };
+    queries::registrations::update_registration(&mut *conn, registration_id, request).await?;
+
+    if queries::results::result_exists(&mut *conn, registration_id).await? {
+        queries::results::calculate_results(&mut *conn, registration_id).await?;
+    }
+
+    Ok(queries::registrations::get_registration_by_id(&mut *conn, registration_id).await?)
+}
+
+/// Recalculate existing results of a registration and the rankings of its contest
+pub async fn refresh_results(
+    pool: &Pool<Sqlite>,
+    contest_id: &str,
+    registration_id: &str,
+) -> Result<(), AppError> {
+    if queries::results::result_exists(pool, registration_id).await? {
+        queries::results::calculate_results(pool, registration_id).await?;
+        queries::results::update_all_rankings(pool, contest_id).await?;
+    }
+    Ok(())
+}
+
+/// Recalculate the result of an attempt's registration after the attempt changed: its records,
+/// its result and its places in the rankings it belongs to. Takes a connection so it can run
+/// in the transaction that changed the attempt.
+pub async fn recalculate_attempt<'a, A>(
+    conn: A,
+    attempt_id: &str,
+) -> Result<CompetitionResult, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+
+    let attempt = queries::attempts::get_attempt_by_id(&mut *conn, attempt_id)
+        .await?
+        .ok_or_else(|| AppError::InvalidInput {
+            field: "attempt_id".to_string(),
+            reason: format!("Unknown attempt {}", attempt_id),
+        })?;
+
+    // Successful attempts may break records; reversed decisions drop the records they set
+    records::check_attempt(&mut *conn, attempt_id).await?;
+    let result = queries::results::calculate_results(&mut *conn, &attempt.registration_id).await?;
+    queries::results::update_registration_rankings(
+        &mut *conn,
+        &result.contest_id,
+        &attempt.registration_id,
+    )
+    .await?;
+
+    Ok(result)
+}
+
+/// Re-derive every registration of a contest, e.g. after its date or formula changed.
+/// Runs in a transaction, or a savepoint of the caller's, so a registration that can no
+/// longer be derived leaves none of them changed.
+pub async fn recalculate_contest<'a, A>(
+    conn: A,
+    federations: &FederationRegistry,
+    contest_id: &str,
+) -> Result<Vec<Registration>, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut tx = conn.begin().await?;
+    let registrations =
+        queries::registrations::get_registrations_by_contest(&mut *tx, contest_id).await?;
+
+    let mut recalculated = Vec::with_capacity(registrations.len());
+    for registration in registrations {
+        recalculated.push(recalculate_registration(&mut *tx, federations, &registration.id).await?);
+    }
+    queries::results::update_all_rankings(&mut *tx, contest_id).await?;
+    tx.commit().await?;
+
+    Ok(recalculated)
+}
+
+/// Re-derive every registration of a competitor, e.g. after a birth date correction.
+/// Like [`recalculate_contest`], either every registration is re-derived or none is.
+pub async fn recalculate_competitor<'a, A>(
+    conn: A,
+    federations: &FederationRegistry,
+    competitor_id: &str,
+) -> Result<Vec<Registration>, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut tx = conn.begin().await?;
+    let registrations =
+        queries::registrations::get_registrations_by_competitor(&mut *tx, competitor_id).await?;
+
+    let mut recalculated = Vec::with_capacity(registrations.len());
+    for registration in registrations {
+        let registration =
+            recalculate_registration(&mut *tx, federations, &registration.id).await?;
+        queries::results::update_all_rankings(&mut *tx, &registration.contest_id).await?;
+        recalculated.push(registration);
+    }
+    tx.commit().await?;
+
+    Ok(recalculated)
+}
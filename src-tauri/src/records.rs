@@ -0,0 +1,307 @@
+//! Record-break detection.
+//!
+//! Records are held per scope, sex, weight class, age category, equipment and lift or total.
+//! A lifter competes for the national records, the club records of their club and the
+//! regional records of their city. When an attempt is judged successful and beats a current
+//! record in the lifter's category, a new record linked to the attempt is stored and the
+//! lifter's result is flagged. A category nobody holds a record in yet is set by any
+//! successful attempt; an equal weight does not break a record.
+
+use crate::database::queries;
+use crate::database::queries::competitors::Competitor;
+use crate::database::queries::records::{RecordCategory, RecordSource};
+use crate::error::AppError;
+use crate::models::attempt::{AttemptStatus, LiftType, RECORD_ATTEMPT_NUMBER};
+use crate::models::record::{Record, RecordEntry, RecordLift, RecordScope};
+use sqlx::{Acquire, Pool, Sqlite};
+use std::str::FromStr;
+
+/// Check a judged attempt against the records. Records the attempt set earlier are removed
+/// first, so re-judging an attempt, including reversing a good lift, keeps the registry right.
+/// Returns the records the attempt set.
+pub async fn check_attempt<'a, A>(conn: A, attempt_id: &str) -> Result<Vec<Record>, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+    let attempt = queries::attempts::get_attempt_by_id(&mut *conn, attempt_id)
+        .await?
+        .ok_or_else(|| AppError::InvalidInput {
+            field: "attempt_id".to_string(),
+            reason: format!("Unknown attempt {}", attempt_id),
+        })?;
+    let removed = queries::records::delete_records_by_attempt(&mut *conn, attempt_id).await?;
+
+    let mut broken = Vec::new();
+    if attempt.status == AttemptStatus::Successful.to_string() {
+        let lift_type = LiftType::from_str(&attempt.lift_type).map_err(|_| {
+            AppError::Internal(format!(
+                "Invalid lift type '{}' in database for attempt {}",
+                attempt.lift_type, attempt.id
+            ))
+        })?;
+        let registration =
+            queries::registrations::get_registration_by_id(&mut *conn, &attempt.registration_id)
+                .await?;
+        let competitor =
+            queries::competitors::get_competitor_by_id(&mut *conn, &registration.competitor_id)
+                .await?;
+        let contest = queries::contests::get_contest_by_id(&mut *conn, &registration.contest_id)
+            .await?
+            .ok_or_else(|| AppError::ContestNotFound {
+                id: registration.contest_id.clone(),
+            })?;
+
+        let category = RecordCategory {
+            gender: &competitor.gender,
+            weight_class_id: &registration.weight_class_id,
+            age_category_id: &registration.age_category_id,
+            equipment: registration.equipment(),
+        };
+        let source = RecordSource {
+            competitor_id: &competitor.id,
+            contest_id: &contest.id,
+            attempt_id: &attempt.id,
+        };
+        let set_on = contest.date.to_string();
+
+        let mut candidates = vec![(RecordLift::from(&lift_type), attempt.weight)];
+        // A record attempt only counts for the single-lift record, never for the total
+        let lifts = contest.discipline.lifts();
+        if lifts.len() > 1
+            && lifts.contains(&lift_type)
+            && attempt.attempt_number != RECORD_ATTEMPT_NUMBER
+        {
+            if let Some(total) = total_completed_by(
+                &mut *conn,
+                &attempt.registration_id,
+                lifts,
+                &lift_type,
+                attempt.weight,
+            )
+            .await?
+            {
+                candidates.push((RecordLift::Total, total));
+            }
+        }
+
+        for (lift, weight) in candidates {
+            for (scope, scope_name) in scopes_of(&competitor) {
+                // The attempt's own records were removed above
+                let current = queries::records::get_current_record(
+                    &mut *conn, scope, scope_name, &category, lift, None,
+                )
+                .await?;
+                // A vacant record is set by any good lift
+                if current.is_some_and(|record| weight <= record.weight) {
+                    continue;
+                }
+
+                let entry = RecordEntry {
+                    scope,
+                    scope_name: scope_name.map(str::to_string),
+                    gender: competitor.gender.clone(),
+                    weight_class_id: registration.weight_class_id.clone(),
+                    age_category_id: registration.age_category_id.clone(),
+                    equipment: category.equipment,
+                    lift,
+                    weight,
+                    holder_name: format!("{} {}", competitor.first_name, competitor.last_name),
+                    set_on: set_on.clone(),
+                };
+                let record =
+                    queries::records::insert_record(&mut *conn, &entry, Some(source.clone()))
+                        .await?;
+                tracing::info!(
+                    "{} {} record of {} kg set by attempt {}",
+                    scope,
+                    lift,
+                    weight,
+                    attempt.id
+                );
+                broken.push(record);
+            }
+        }
+    }
+
+    if !broken.is_empty()
+        && !queries::results::result_exists(&mut *conn, &attempt.registration_id).await?
+    {
+        queries::results::calculate_results(&mut *conn, &attempt.registration_id).await?;
+    } else if !broken.is_empty() || removed > 0 {
+        queries::results::update_record_flag(&mut *conn, &attempt.registration_id).await?;
+    }
+
+    Ok(broken)
+}
+
+/// A record of a lifter's category that a lift would set or break
+#[derive(Debug, Clone)]
+pub struct RecordInReach {
+    pub scope: RecordScope,
+    pub scope_name: Option<String>,
+    /// Current record; None when the record is vacant
+    pub current: Option<Record>,
+}
+
+/// Records of a lifter's category that a lift of the given weight would set or break.
+/// A 4th (record) attempt is only allowed when this is not empty. Records set by
+/// `attempt_id` are left out, so an attempt that already set a record can still be edited.
+pub async fn records_in_reach<'a, A>(
+    conn: A,
+    registration_id: &str,
+    lift_type: &LiftType,
+    weight: f64,
+    attempt_id: Option<&str>,
+) -> Result<Vec<RecordInReach>, AppError>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+    let registration =
+        queries::registrations::get_registration_by_id(&mut *conn, registration_id).await?;
+    let competitor =
+        queries::competitors::get_competitor_by_id(&mut *conn, &registration.competitor_id).await?;
+    let category = RecordCategory {
+        gender: &competitor.gender,
+        weight_class_id: &registration.weight_class_id,
+        age_category_id: &registration.age_category_id,
+        equipment: registration.equipment(),
+    };
+
+    let mut in_reach = Vec::new();
+    for (scope, scope_name) in scopes_of(&competitor) {
+        let current = queries::records::get_current_record(
+            &mut *conn,
+            scope,
+            scope_name,
+            &category,
+            RecordLift::from(lift_type),
+            attempt_id,
+        )
+        .await?;
+        if current
+            .as_ref()
+            .is_some_and(|record| weight <= record.weight)
+        {
+            continue;
+        }
+        in_reach.push(RecordInReach {
+            scope,
+            scope_name: scope_name.map(str::to_string),
+            current,
+        });
+    }
+
+    Ok(in_reach)
+}
+
+/// Scopes whose records a lifter competes for, with the club or region name
+fn scopes_of(competitor: &Competitor) -> Vec<(RecordScope, Option<&str>)> {
+    let mut scopes = Vec::with_capacity(3);
+    if let Some(club) = named(&competitor.club) {
+        scopes.push((RecordScope::Club, Some(club)));
+    }
+    if let Some(city) = named(&competitor.city) {
+        scopes.push((RecordScope::Regional, Some(city)));
+    }
+    scopes.push((RecordScope::National, None));
+    scopes
+}
+
+fn named(name: &Option<String>) -> Option<&str> {
+    name.as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// Total of a lifter whose last missing or best lift is the given one, or None when the
+/// attempt does not complete or raise the total
+async fn total_completed_by<'a, A>(
+    conn: A,
+    registration_id: &str,
+    lifts: &[LiftType],
+    lift_type: &LiftType,
+    weight: f64,
+) -> Result<Option<f64>, sqlx::Error>
+where
+    A: Acquire<'a, Database = Sqlite>,
+{
+    let mut conn = conn.acquire().await?;
+    let mut total = 0.0;
+    for lift in lifts {
+        let Some(best) =
+            queries::attempts::get_best_attempt(&mut *conn, registration_id, &lift.to_string())
+                .await?
+        else {
+            return Ok(None);
+        };
+        if lift == lift_type && weight < best {
+            return Ok(None);
+        }
+        total += best;
+    }
+    Ok(Some(total))
+}
+
+/// Validate and store a record table
+pub async fn import_records(
+    pool: &Pool<Sqlite>,
+    entries: &[RecordEntry],
+) -> Result<usize, AppError> {
+    for (index, entry) in entries.iter().enumerate() {
+        let invalid = |field: &str, reason: String| AppError::InvalidInput {
+            field: format!("records[{}].{}", index, field),
+            reason,
+        };
+
+        if entry.weight <= 0.0 {
+            return Err(invalid(
+                "weight",
+                "Record weight must be positive".to_string(),
+            ));
+        }
+        if entry.holder_name.trim().is_empty() {
+            return Err(invalid(
+                "holderName",
+                "Record holder is required".to_string(),
+            ));
+        }
+        if chrono::NaiveDate::parse_from_str(&entry.set_on, "%Y-%m-%d").is_err() {
+            return Err(invalid("setOn", format!("Invalid date {}", entry.set_on)));
+        }
+        let named = entry
+            .scope_name
+            .as_deref()
+            .is_some_and(|name| !name.trim().is_empty());
+        if named == (entry.scope == RecordScope::National) {
+            return Err(invalid(
+                "scopeName",
+                format!(
+                    "{} records {} a club or region name",
+                    entry.scope,
+                    if named { "cannot have" } else { "need" }
+                ),
+            ));
+        }
+        let class = queries::categories::get_weight_class_by_id(pool, &entry.weight_class_id)
+            .await?
+            .ok_or_else(|| {
+                invalid(
+                    "weightClassId",
+                    format!("Unknown weight class {}", entry.weight_class_id),
+                )
+            })?;
+        if class.gender != entry.gender {
+            return Err(invalid(
+                "gender",
+                format!(
+                    "Weight class {} is for {} lifters, not {}",
+                    class.id, class.gender, entry.gender
+                ),
+            ));
+        }
+    }
+
+    Ok(queries::records::import_records(pool, entries).await?)
+}
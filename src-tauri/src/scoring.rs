@@ -0,0 +1,234 @@
+//! Scoring formulas that turn a lifter's total into ranking points.
+//!
+//! Each formula reduces the lifter's data to a single coefficient, which is stored on the
+//! registration and multiplied with the total when results are calculated. Contests select
+//! a formula through `ScoringFormulaKind`; `formula` resolves it to its implementation.
+
+use crate::coefficients::{self, CoefficientError};
+use crate::models::contest::{Discipline, ScoringFormulaKind};
+use serde::Serialize;
+
+/// Lifter data a scoring formula may depend on
+#[derive(Debug, Clone)]
+pub struct LifterProfile<'a> {
+    pub bodyweight: f64,
+    pub gender: &'a str,
+    /// Age on contest day in full years
+    pub age: u32,
+    pub equipped: bool,
+    pub discipline: &'a Discipline,
+}
+
+/// A formula that scores a total relative to the lifter's bodyweight, sex, age and equipment
+pub trait ScoringFormula: Send + Sync {
+    /// Identifier persisted on contests and results
+    fn kind(&self) -> ScoringFormulaKind;
+
+    /// Human readable name used in rankings and exports
+    fn name(&self) -> &'static str;
+
+    /// Multiplier applied to the total
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError>;
+
+    /// Points for a total
+    fn points(&self, total_weight: f64, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        Ok(coefficients::calculate_points(
+            total_weight,
+            self.coefficient(lifter)?,
+            1.0,
+        ))
+    }
+}
+
+/// Reshel bodyweight coefficient combined with the McCullough age factor
+pub struct ReshelMcCullough;
+
+impl ScoringFormula for ReshelMcCullough {
+    fn kind(&self) -> ScoringFormulaKind {
+        ScoringFormulaKind::ReshelMcCullough
+    }
+
+    fn name(&self) -> &'static str {
+        "Reshel/McCullough"
+    }
+
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        let reshel = coefficients::calculate_reshel_coefficient(lifter.bodyweight, lifter.gender)?;
+        let mccullough = coefficients::mccullough_factor(lifter.age);
+        Ok(reshel * mccullough)
+    }
+}
+
+/// IPF GL points
+pub struct IpfGl;
+
+impl ScoringFormula for IpfGl {
+    fn kind(&self) -> ScoringFormulaKind {
+        ScoringFormulaKind::IpfGl
+    }
+
+    fn name(&self) -> &'static str {
+        "IPF GL"
+    }
+
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        coefficients::calculate_ipf_gl_coefficient(
+            lifter.bodyweight,
+            lifter.gender,
+            lifter.equipped,
+            lifter.discipline,
+        )
+    }
+}
+
+/// DOTS points
+pub struct Dots;
+
+impl ScoringFormula for Dots {
+    fn kind(&self) -> ScoringFormulaKind {
+        ScoringFormulaKind::Dots
+    }
+
+    fn name(&self) -> &'static str {
+        "DOTS"
+    }
+
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        coefficients::calculate_dots_coefficient(lifter.bodyweight, lifter.gender)
+    }
+}
+
+/// Original Wilks points
+pub struct Wilks;
+
+impl ScoringFormula for Wilks {
+    fn kind(&self) -> ScoringFormulaKind {
+        ScoringFormulaKind::Wilks
+    }
+
+    fn name(&self) -> &'static str {
+        "Wilks"
+    }
+
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        coefficients::calculate_wilks_coefficient(lifter.bodyweight, lifter.gender)
+    }
+}
+
+/// Wilks-2020 points
+pub struct Wilks2020;
+
+impl ScoringFormula for Wilks2020 {
+    fn kind(&self) -> ScoringFormulaKind {
+        ScoringFormulaKind::Wilks2020
+    }
+
+    fn name(&self) -> &'static str {
+        "Wilks-2020"
+    }
+
+    fn coefficient(&self, lifter: &LifterProfile) -> Result<f64, CoefficientError> {
+        coefficients::calculate_wilks2020_coefficient(lifter.bodyweight, lifter.gender)
+    }
+}
+
+/// All built-in formulas, in the order offered for selection
+static FORMULAS: [&dyn ScoringFormula; 5] = [&ReshelMcCullough, &IpfGl, &Dots, &Wilks, &Wilks2020];
+
+/// List the registered formulas
+pub fn formulas() -> &'static [&'static dyn ScoringFormula] {
+    &FORMULAS
+}
+
+/// Resolve a persisted formula identifier to its implementation
+pub fn formula(kind: ScoringFormulaKind) -> &'static dyn ScoringFormula {
+    match kind {
+        ScoringFormulaKind::ReshelMcCullough => &ReshelMcCullough,
+        ScoringFormulaKind::IpfGl => &IpfGl,
+        ScoringFormulaKind::Dots => &Dots,
+        ScoringFormulaKind::Wilks => &Wilks,
+        ScoringFormulaKind::Wilks2020 => &Wilks2020,
+    }
+}
+
+/// Formula entry exposed to the frontend for contest setup
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoringFormulaInfo {
+    pub id: ScoringFormulaKind,
+    pub name: String,
+}
+
+impl From<&dyn ScoringFormula> for ScoringFormulaInfo {
+    fn from(formula: &dyn ScoringFormula) -> Self {
+        ScoringFormulaInfo {
+            id: formula.kind(),
+            name: formula.name().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lifter(bodyweight: f64, gender: &str, age: u32) -> LifterProfile<'_> {
+        LifterProfile {
+            bodyweight,
+            gender,
+            age,
+            equipped: false,
+            discipline: &Discipline::Powerlifting,
+        }
+    }
+
+    fn mccullough_62() -> f64 {
+        coefficients::mccullough_factor(62)
+    }
+
+    #[test]
+    fn test_registry_resolves_every_formula() {
+        for registered in formulas() {
+            assert_eq!(formula(registered.kind()).kind(), registered.kind());
+        }
+        assert_eq!(formulas().len(), 5);
+    }
+
+    #[test]
+    fn test_formula_points_match_coefficient_functions() {
+        let points = formula(ScoringFormulaKind::Dots)
+            .points(700.0, &lifter(100.0, "male", 30))
+            .unwrap();
+        assert!((points - 430.861).abs() < 0.01);
+
+        let points = formula(ScoringFormulaKind::IpfGl)
+            .points(700.0, &lifter(93.0, "male", 30))
+            .unwrap();
+        assert!((points - 91.575).abs() < 0.01);
+
+        assert!(formula(ScoringFormulaKind::Wilks2020)
+            .coefficient(&lifter(100.0, "unknown", 30))
+            .is_err());
+    }
+
+    #[test]
+    fn test_reshel_mccullough_applies_age_factor() {
+        let formula = formula(ScoringFormulaKind::ReshelMcCullough);
+        let senior = formula.coefficient(&lifter(82.5, "male", 30)).unwrap();
+        let veteran = formula.coefficient(&lifter(82.5, "male", 62)).unwrap();
+        let reshel = coefficients::calculate_reshel_coefficient(82.5, "male").unwrap();
+
+        assert_eq!(senior, reshel);
+        assert!((veteran - reshel * mccullough_62()).abs() < 1e-9);
+        // Lifters younger than the McCullough table are scored without an age factor
+        let young = formula.coefficient(&lifter(82.5, "male", 12)).unwrap();
+        assert_eq!(young, reshel);
+
+        // Lifters lighter than the Reshel table are scored by its first row, not turned away
+        let light = formula.coefficient(&lifter(47.0, "male", 30)).unwrap();
+        assert_eq!(
+            light,
+            coefficients::calculate_reshel_coefficient(50.0, "male").unwrap()
+        );
+    }
+}
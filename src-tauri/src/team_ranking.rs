@@ -0,0 +1,167 @@
+//! Team classification.
+//!
+//! Lifters earn their club the contest's place points for their weight class placing. A
+//! club scores the points of its best N lifters. Clubs with equal points are separated by
+//! their lifters' points, best first, so more high placings rank higher; clubs still equal
+//! share a place.
+
+use crate::models::contest::TeamScoring;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// A placed lifter who belongs to a club
+#[derive(Debug, Clone)]
+pub struct TeamEntry {
+    pub registration_id: String,
+    pub competitor_name: String,
+    pub club: String,
+    pub place: i64,
+}
+
+/// A lifter whose points count for the club
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TeamScorer {
+    pub registration_id: String,
+    pub competitor_name: String,
+    pub place: i64,
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TeamRanking {
+    pub place: i64,
+    pub club: String,
+    pub points: u32,
+    pub scorers: Vec<TeamScorer>,
+}
+
+/// Rank the clubs of a contest
+pub fn rank_teams(entries: Vec<TeamEntry>, scoring: &TeamScoring) -> Vec<TeamRanking> {
+    let mut clubs: BTreeMap<String, Vec<TeamScorer>> = BTreeMap::new();
+    for entry in entries {
+        let club = entry.club.trim();
+        if club.is_empty() {
+            continue;
+        }
+        clubs.entry(club.to_string()).or_default().push(TeamScorer {
+            points: scoring.points_for(entry.place),
+            registration_id: entry.registration_id,
+            competitor_name: entry.competitor_name,
+            place: entry.place,
+        });
+    }
+
+    let mut teams: Vec<TeamRanking> = clubs
+        .into_iter()
+        .map(|(club, mut scorers)| {
+            scorers.sort_by(|a, b| b.points.cmp(&a.points).then(a.place.cmp(&b.place)));
+            scorers.truncate(scoring.top_n as usize);
+            TeamRanking {
+                place: 0,
+                points: scorers.iter().map(|scorer| scorer.points).sum(),
+                club,
+                scorers,
+            }
+        })
+        .collect();
+    teams.sort_by(|a, b| compare(a, b).then_with(|| a.club.cmp(&b.club)));
+
+    for index in 0..teams.len() {
+        teams[index].place = match index.checked_sub(1) {
+            Some(previous) if compare(&teams[previous], &teams[index]).is_eq() => {
+                teams[previous].place
+            }
+            _ => index as i64 + 1,
+        };
+    }
+    teams
+}
+
+fn compare(a: &TeamRanking, b: &TeamRanking) -> Ordering {
+    let points = |team: &TeamRanking| {
+        team.scorers
+            .iter()
+            .map(|scorer| scorer.points)
+            .collect::<Vec<_>>()
+    };
+    b.points
+        .cmp(&a.points)
+        .then_with(|| points(b).cmp(&points(a)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(club: &str, place: i64) -> TeamEntry {
+        TeamEntry {
+            registration_id: format!("{}-{}", club, place),
+            competitor_name: format!("Lifter {}", place),
+            club: club.to_string(),
+            place,
+        }
+    }
+
+    #[test]
+    fn test_best_lifters_count_for_the_club() {
+        let scoring = TeamScoring {
+            top_n: 2,
+            place_points: vec![12, 9, 8, 7],
+        };
+        let teams = rank_teams(
+            vec![
+                entry("Alpha", 1),
+                entry("Alpha", 4),
+                entry("Alpha", 2),
+                entry("Beta", 3),
+                entry("Beta", 1),
+                entry(" ", 1),
+            ],
+            &scoring,
+        );
+
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams[0].club, "Alpha");
+        assert_eq!(teams[0].points, 21);
+        assert_eq!(teams[0].scorers.len(), 2);
+        assert_eq!(teams[1].club, "Beta");
+        assert_eq!(teams[1].points, 20);
+        assert_eq!(teams[1].place, 2);
+    }
+
+    #[test]
+    fn test_places_beyond_the_table_score_nothing() {
+        let scoring = TeamScoring {
+            top_n: 3,
+            place_points: vec![3, 2, 1],
+        };
+        let teams = rank_teams(vec![entry("Alpha", 5)], &scoring);
+        assert_eq!(teams[0].points, 0);
+    }
+
+    #[test]
+    fn test_equal_points_are_split_by_best_placings() {
+        let scoring = TeamScoring {
+            top_n: 2,
+            place_points: vec![12, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+        };
+        // 12 + 1 against 9 + 4 (2nd and 7th place)
+        let teams = rank_teams(
+            vec![
+                entry("Alpha", 2),
+                entry("Alpha", 7),
+                entry("Beta", 1),
+                entry("Beta", 10),
+                entry("Gamma", 2),
+                entry("Gamma", 7),
+            ],
+            &scoring,
+        );
+
+        assert_eq!(teams[0].club, "Beta");
+        assert_eq!(teams[0].place, 1);
+        assert_eq!(teams[1].place, 2);
+        assert_eq!(teams[2].place, 2);
+    }
+}